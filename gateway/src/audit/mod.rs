@@ -0,0 +1,89 @@
+use actix_web::http::Method;
+use models::events::{event_types, AuditRecordedData, Event};
+use models::user::Session;
+use rdkafka::producer::FutureProducer;
+use uuid::Uuid;
+
+use crate::policy;
+
+/// Kafka topic the audit trail is published to
+pub const AUDIT_TOPIC: &str = "audit.events";
+
+/// Publishes an audit record for every mutating API call
+#[derive(Clone)]
+pub struct AuditLog {
+    producer: FutureProducer,
+}
+
+impl AuditLog {
+    pub fn new(producer: FutureProducer) -> Self {
+        Self { producer }
+    }
+
+    /// Whether calls with this method change state and are audited
+    pub fn audits(method: &Method) -> bool {
+        !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+    }
+
+    /// Publish a record in the background so auditing never delays the
+    /// response. Failures are logged; the call itself has already completed.
+    pub fn record(&self, data: AuditRecordedData) {
+        let producer = self.producer.clone();
+        let request_id = data.request_id.clone().unwrap_or_else(correlation::generate);
+
+        actix_rt::spawn(correlation::scope(request_id, async move {
+            let key = data.user_id.map(|id| id.to_string());
+            let event = Event::new(event_types::AUDIT_RECORDED.to_string(), data);
+
+            if let Err(e) =
+                kafka_client::publish_message(&producer, AUDIT_TOPIC, key.as_deref(), &event).await
+            {
+                tracing::error!(
+                    "Failed to publish audit record for {} {}: {}",
+                    event.data.method,
+                    event.data.path,
+                    e
+                );
+            }
+        }));
+    }
+}
+
+/// Restaurant a call acted on: the API key's restaurant, otherwise the one
+/// named in the path, if any
+pub fn restaurant_id(session: Option<&Session>, path: &str) -> Option<Uuid> {
+    session
+        .and_then(|session| session.scope.as_ref())
+        .map(|scope| scope.restaurant_id)
+        .or_else(|| policy::path_restaurant(path).and_then(|id| id.parse().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use models::user::{SessionScope, UserRole};
+
+    #[test]
+    fn test_restaurant_id() {
+        let named = Uuid::new_v4();
+        let path = format!("/api/restaurants/{}/foods", named);
+
+        assert_eq!(restaurant_id(None, &path), Some(named));
+        assert_eq!(restaurant_id(None, "/api/restaurants/not-a-uuid"), None);
+        assert_eq!(restaurant_id(None, "/api/foods/1"), None);
+
+        let scoped = Uuid::new_v4();
+        let session = Session {
+            user_id: Uuid::new_v4(),
+            email: "test@example.com".to_string(),
+            role: UserRole::Rest,
+            created_at: Utc::now(),
+            scope: Some(SessionScope {
+                api_key_id: Uuid::new_v4(),
+                restaurant_id: scoped,
+            }),
+        };
+        assert_eq!(restaurant_id(Some(&session), "/api/foods/1"), Some(scoped));
+    }
+}
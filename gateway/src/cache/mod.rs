@@ -0,0 +1,308 @@
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use kafka_client::MeteredConsumer;
+use rdkafka::message::Message;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::policy::match_path;
+
+/// Default cache rules shipped with the gateway
+const DEFAULT_CACHE_RULES: &str = include_str!("../../response_cache.toml");
+
+/// Delay before recreating the purge consumer after it fails
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CacheRule {
+    pub path: String,
+    pub ttl_seconds: u64,
+    pub purge_topic: Option<String>,
+    pub purge_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CacheRules {
+    #[serde(default)]
+    pub routes: Vec<CacheRule>,
+}
+
+/// How a request matched a cache rule
+pub struct CacheMatch {
+    pub ttl: Duration,
+    /// Purge tag for entries stored under this match
+    pub tag: Option<String>,
+}
+
+impl CacheRules {
+    /// Load rules from a TOML/JSON/YAML file, or the bundled defaults when no path is given
+    pub fn load(path: Option<&str>) -> anyhow::Result<Self> {
+        let source = match path {
+            Some(path) => config::Config::builder().add_source(config::File::with_name(path)),
+            None => config::Config::builder()
+                .add_source(config::File::from_str(DEFAULT_CACHE_RULES, config::FileFormat::Toml)),
+        };
+
+        let rules: CacheRules = source.build()?.try_deserialize()?;
+
+        for rule in &rules.routes {
+            if rule.purge_topic.is_some() != rule.purge_key.is_some() {
+                anyhow::bail!(
+                    "Cache rule '{}' must set both purge_topic and purge_key, or neither",
+                    rule.path
+                );
+            }
+        }
+
+        tracing::info!("Loaded {} response cache rules", rules.routes.len());
+        Ok(rules)
+    }
+
+    /// Find the rule for a request path
+    pub fn resolve(&self, path: &str) -> Option<CacheMatch> {
+        self.routes.iter().find_map(|rule| {
+            let params = match_path(&rule.path, path)?;
+
+            let tag = match (&rule.purge_topic, &rule.purge_key) {
+                (Some(topic), Some(key)) => params.get(key.as_str()).map(|value| purge_tag(topic, key, value)),
+                _ => None,
+            };
+
+            Some(CacheMatch {
+                ttl: Duration::from_secs(rule.ttl_seconds),
+                tag,
+            })
+        })
+    }
+
+    /// Topics that carry purge events
+    pub fn purge_topics(&self) -> Vec<String> {
+        let mut topics: Vec<String> = self
+            .routes
+            .iter()
+            .filter_map(|rule| rule.purge_topic.clone())
+            .collect();
+        topics.sort();
+        topics.dedup();
+        topics
+    }
+
+    /// Purge keys read from messages on a topic
+    fn purge_keys(&self, topic: &str) -> Vec<&str> {
+        self.routes
+            .iter()
+            .filter(|rule| rule.purge_topic.as_deref() == Some(topic))
+            .filter_map(|rule| rule.purge_key.as_deref())
+            .collect()
+    }
+}
+
+fn purge_tag(topic: &str, key: &str, value: &str) -> String {
+    format!("{}:{}={}", topic, key, value)
+}
+
+/// A stored upstream response
+#[derive(Clone)]
+pub struct CachedResponse {
+    pub status: StatusCode,
+    pub headers: Vec<(HeaderName, HeaderValue)>,
+    pub body: Bytes,
+    pub etag: HeaderValue,
+    pub stored_at: Instant,
+    expires_at: Instant,
+    tag: Option<String>,
+}
+
+impl CachedResponse {
+    pub fn new(
+        status: StatusCode,
+        headers: Vec<(HeaderName, HeaderValue)>,
+        body: Bytes,
+        etag: HeaderValue,
+        ttl: Duration,
+        tag: Option<String>,
+    ) -> Self {
+        let stored_at = Instant::now();
+        Self {
+            status,
+            headers,
+            body,
+            etag,
+            stored_at,
+            expires_at: stored_at + ttl,
+            tag,
+        }
+    }
+}
+
+/// Bounded in-memory cache of upstream GET responses, keyed by path and query
+pub struct ResponseCache {
+    rules: CacheRules,
+    entries: RwLock<HashMap<String, CachedResponse>>,
+    capacity: usize,
+    max_entry_bytes: usize,
+}
+
+impl ResponseCache {
+    pub fn new(rules: CacheRules, capacity: usize, max_entry_bytes: usize) -> Self {
+        Self {
+            rules,
+            entries: RwLock::new(HashMap::new()),
+            capacity: capacity.max(1),
+            max_entry_bytes,
+        }
+    }
+
+    pub fn rules(&self) -> &CacheRules {
+        &self.rules
+    }
+
+    pub fn max_entry_bytes(&self) -> usize {
+        self.max_entry_bytes
+    }
+
+    pub fn get(&self, key: &str) -> Option<CachedResponse> {
+        let entries = self.entries.read().unwrap();
+        entries
+            .get(key)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .cloned()
+    }
+
+    pub fn insert(&self, key: String, response: CachedResponse) {
+        let mut entries = self.entries.write().unwrap();
+
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let now = Instant::now();
+            entries.retain(|_, entry| entry.expires_at > now);
+
+            // Still full: drop the entry closest to expiry
+            if entries.len() >= self.capacity {
+                if let Some(oldest) = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.expires_at)
+                    .map(|(key, _)| key.clone())
+                {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+
+        entries.insert(key, response);
+    }
+
+    /// Drop every entry stored under a purge tag, returning how many were removed
+    pub fn purge(&self, tag: &str) -> usize {
+        let mut entries = self.entries.write().unwrap();
+        let before = entries.len();
+        entries.retain(|_, entry| entry.tag.as_deref() != Some(tag));
+        before - entries.len()
+    }
+
+    /// Purge entries named by a message on one of the purge topics
+    fn purge_from_message(&self, topic: &str, payload: &str) {
+        let message: serde_json::Value = match serde_json::from_str(payload) {
+            Ok(message) => message,
+            Err(e) => {
+                tracing::warn!("Invalid purge message on {}: {}", topic, e);
+                return;
+            }
+        };
+
+        for key in self.rules.purge_keys(topic) {
+            if let Some(value) = message.get(key).and_then(|v| v.as_str()) {
+                let purged = self.purge(&purge_tag(topic, key, value));
+                if purged > 0 {
+                    tracing::debug!("Purged {} cached responses for {}={}", purged, key, value);
+                }
+            }
+        }
+    }
+}
+
+/// Consume the purge topics and evict matching entries. Every gateway
+/// instance gets its own consumer group so each one sees every purge; while
+/// the consumer is down, entries still expire after their TTL.
+pub fn spawn_purge_listener(brokers: String, cache: Arc<ResponseCache>) {
+    let topics = cache.rules().purge_topics();
+    if topics.is_empty() {
+        return;
+    }
+
+    actix_rt::spawn(async move {
+        let topic_refs: Vec<&str> = topics.iter().map(String::as_str).collect();
+
+        loop {
+            let consumer: MeteredConsumer =
+                match kafka_client::create_broadcast_consumer(&brokers, "gateway-cache", &topic_refs) {
+                    Ok(consumer) => consumer,
+                    Err(e) => {
+                        tracing::error!("Failed to create response cache purge consumer: {}", e);
+                        tokio::time::sleep(RECONNECT_DELAY).await;
+                        continue;
+                    }
+                };
+
+            tracing::info!("Listening for response cache purges on {}", topics.join(", "));
+
+            loop {
+                match consumer.recv().await {
+                    Ok(message) => {
+                        kafka_client::record_consumed(&message);
+
+                        if let Some(Ok(payload)) = message.payload_view::<str>() {
+                            cache.purge_from_message(message.topic(), payload);
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Response cache purge consumer error: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_purge_from_menu_event() {
+        let cache = ResponseCache::new(CacheRules::load(None).unwrap(), 10, 1024);
+        let restaurant_id = "0b6f8f5e-54c4-4d43-a2f0-1f3b3c9d2a10";
+        let path = format!("/api/restaurants/{}/menu", restaurant_id);
+
+        let matched = cache.rules().resolve(&path).unwrap();
+        assert!(cache.rules().resolve("/api/restaurants").is_none());
+
+        cache.insert(
+            path.clone(),
+            CachedResponse::new(
+                StatusCode::OK,
+                Vec::new(),
+                Bytes::from_static(b"{}"),
+                HeaderValue::from_static("\"abc\""),
+                matched.ttl,
+                matched.tag,
+            ),
+        );
+        assert!(cache.get(&path).is_some());
+
+        let event = serde_json::json!({
+            "event_type": "menu.food_updated",
+            "restaurant_id": restaurant_id,
+        });
+        cache.purge_from_message("order.events", &event.to_string());
+        assert!(cache.get(&path).is_some(), "other topics do not purge");
+
+        cache.purge_from_message("menu.events", &event.to_string());
+        assert!(cache.get(&path).is_none());
+    }
+}
@@ -1,10 +1,22 @@
+use ipnet::IpNet;
 use serde::Deserialize;
+use std::net::IpAddr;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub server: ServerConfig,
     pub redis: RedisConfig,
-    pub services: ServicesConfig,
+    pub routes: RoutesConfig,
+    pub identity: IdentityConfig,
+    pub authorization: AuthorizationConfig,
+    pub rate_limit: RateLimitConfig,
+    pub upstream: UpstreamConfig,
+    pub admin: AdminConfig,
+    pub session_cache: SessionCacheConfig,
+    pub cors: CorsConfig,
+    pub kafka: KafkaConfig,
+    pub response_cache: ResponseCacheConfig,
+    pub streaming: StreamingConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -19,13 +31,85 @@ pub struct RedisConfig {
 }
 
 #[derive(Debug, Clone, Deserialize)]
-pub struct ServicesConfig {
-    pub auth_url: String,
-    pub restaurant_url: String,
-    pub order_url: String,
-    pub kitchen_url: String,
-    pub billing_url: String,
-    pub analytics_url: String,
+pub struct RoutesConfig {
+    pub routes_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdentityConfig {
+    pub signing_secret: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthorizationConfig {
+    pub policy_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitConfig {
+    pub limits_file: Option<String>,
+    /// Proxies whose `X-Forwarded-For` is believed
+    pub trusted_proxies: Vec<IpNet>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpstreamConfig {
+    pub connect_timeout_ms: u64,
+    pub request_timeout_ms: u64,
+    /// Longest pause between chunks of a streamed upstream response body
+    pub body_idle_timeout_ms: u64,
+    pub max_retries: u32,
+    pub retry_backoff_ms: u64,
+    pub breaker_failure_threshold: u32,
+    pub breaker_open_seconds: u64,
+    pub health_probe_timeout_ms: u64,
+    /// Consecutive failures before an endpoint is taken out of rotation
+    pub endpoint_eject_failures: u32,
+    /// How often ejected endpoints are probed for re-admission
+    pub endpoint_probe_interval_seconds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdminConfig {
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SessionCacheConfig {
+    pub capacity: usize,
+    pub ttl_seconds: u64,
+    /// Idle timeout a session's TTL is reset to on activity; 0 disables
+    pub sliding_seconds: u64,
+    /// Age after which activity no longer extends a session
+    pub max_age_seconds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CorsConfig {
+    /// Allowed origins; `*` allows any origin
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    pub allow_credentials: bool,
+    pub max_age_seconds: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KafkaConfig {
+    pub brokers: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResponseCacheConfig {
+    pub rules_file: Option<String>,
+    pub capacity: usize,
+    pub max_entry_bytes: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamingConfig {
+    /// How often long-lived connections re-check their session in Redis
+    pub session_check_seconds: u64,
 }
 
 impl Config {
@@ -41,23 +125,51 @@ impl Config {
         let redis_url = std::env::var("REDIS_URL")
             .unwrap_or_else(|_| "redis://localhost:6379".to_string());
 
-        let auth_url = std::env::var("AUTH_SERVICE_URL")
-            .unwrap_or_else(|_| "http://localhost:8001".to_string());
+        let routes_file = std::env::var("GATEWAY_ROUTES_FILE").ok();
+
+        let identity_signing_secret = std::env::var("IDENTITY_SIGNING_SECRET")
+            .map_err(|_| anyhow::anyhow!("IDENTITY_SIGNING_SECRET must be set"))?;
+
+        let policy_file = std::env::var("GATEWAY_POLICY_FILE").ok();
 
-        let restaurant_url = std::env::var("RESTAURANT_SERVICE_URL")
-            .unwrap_or_else(|_| "http://localhost:8002".to_string());
+        let rate_limit_file = std::env::var("GATEWAY_RATE_LIMIT_FILE").ok();
 
-        let order_url = std::env::var("ORDER_SERVICE_URL")
-            .unwrap_or_else(|_| "http://localhost:8003".to_string());
+        let trusted_proxies = std::env::var("GATEWAY_TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|net| !net.is_empty())
+            .map(|net| {
+                net.parse::<IpNet>()
+                    .or_else(|_| net.parse::<IpAddr>().map(IpNet::from))
+                    .map_err(|_| anyhow::anyhow!("Invalid network in GATEWAY_TRUSTED_PROXIES: {}", net))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
 
-        let kitchen_url = std::env::var("KITCHEN_SERVICE_URL")
-            .unwrap_or_else(|_| "http://localhost:8004".to_string());
+        let admin_token = std::env::var("GATEWAY_ADMIN_TOKEN").ok();
 
-        let billing_url = std::env::var("BILLING_SERVICE_URL")
-            .unwrap_or_else(|_| "http://localhost:8005".to_string());
+        let kafka_brokers = std::env::var("KAFKA_BROKERS")
+            .unwrap_or_else(|_| "localhost:9092".to_string());
 
-        let analytics_url = std::env::var("ANALYTICS_SERVICE_URL")
-            .unwrap_or_else(|_| "http://localhost:8006".to_string());
+        let response_cache_file = std::env::var("GATEWAY_RESPONSE_CACHE_FILE").ok();
+
+        let cors = CorsConfig {
+            allowed_origins: env_list("GATEWAY_CORS_ALLOWED_ORIGINS", ""),
+            allowed_methods: env_list("GATEWAY_CORS_ALLOWED_METHODS", "GET,POST,PUT,PATCH,DELETE"),
+            allowed_headers: env_list(
+                "GATEWAY_CORS_ALLOWED_HEADERS",
+                "Authorization,Content-Type,Accept,X-Request-Id",
+            ),
+            allow_credentials: std::env::var("GATEWAY_CORS_ALLOW_CREDENTIALS")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            max_age_seconds: env_or("GATEWAY_CORS_MAX_AGE_SECONDS", 3600),
+        };
+
+        // Browsers reject credentialed responses for a wildcard origin
+        if cors.allow_credentials && cors.allowed_origins.iter().any(|origin| origin == "*") {
+            anyhow::bail!("GATEWAY_CORS_ALLOW_CREDENTIALS cannot be combined with a '*' origin");
+        }
 
         Ok(Config {
             server: ServerConfig {
@@ -67,15 +179,71 @@ impl Config {
             redis: RedisConfig {
                 url: redis_url,
             },
-            services: ServicesConfig {
-                auth_url,
-                restaurant_url,
-                order_url,
-                kitchen_url,
-                billing_url,
-                analytics_url,
+            routes: RoutesConfig {
+                routes_file,
+            },
+            identity: IdentityConfig {
+                signing_secret: identity_signing_secret,
+            },
+            authorization: AuthorizationConfig {
+                policy_file,
+            },
+            rate_limit: RateLimitConfig {
+                limits_file: rate_limit_file,
+                trusted_proxies,
+            },
+            upstream: UpstreamConfig {
+                connect_timeout_ms: env_or("GATEWAY_UPSTREAM_CONNECT_TIMEOUT_MS", 2000),
+                request_timeout_ms: env_or("GATEWAY_UPSTREAM_TIMEOUT_MS", 15000),
+                body_idle_timeout_ms: env_or("GATEWAY_UPSTREAM_BODY_IDLE_TIMEOUT_MS", 30000),
+                max_retries: env_or("GATEWAY_UPSTREAM_MAX_RETRIES", 2),
+                retry_backoff_ms: env_or("GATEWAY_UPSTREAM_RETRY_BACKOFF_MS", 100),
+                breaker_failure_threshold: env_or("GATEWAY_BREAKER_FAILURE_THRESHOLD", 5),
+                breaker_open_seconds: env_or("GATEWAY_BREAKER_OPEN_SECONDS", 30),
+                health_probe_timeout_ms: env_or("GATEWAY_HEALTH_PROBE_TIMEOUT_MS", 2000),
+                endpoint_eject_failures: env_or("GATEWAY_ENDPOINT_EJECT_FAILURES", 3),
+                endpoint_probe_interval_seconds: env_or("GATEWAY_ENDPOINT_PROBE_INTERVAL_SECONDS", 10),
+            },
+            admin: AdminConfig {
+                token: admin_token,
+            },
+            session_cache: SessionCacheConfig {
+                capacity: env_or("GATEWAY_SESSION_CACHE_CAPACITY", 10_000),
+                ttl_seconds: env_or("GATEWAY_SESSION_CACHE_TTL_SECONDS", 30),
+                sliding_seconds: env_or("GATEWAY_SESSION_SLIDING_SECONDS", 0),
+                max_age_seconds: env_or("GATEWAY_SESSION_MAX_AGE_SECONDS", 12 * 60 * 60),
+            },
+            cors,
+            kafka: KafkaConfig {
+                brokers: kafka_brokers,
+            },
+            response_cache: ResponseCacheConfig {
+                rules_file: response_cache_file,
+                capacity: env_or("GATEWAY_RESPONSE_CACHE_CAPACITY", 1000),
+                max_entry_bytes: env_or("GATEWAY_RESPONSE_CACHE_MAX_ENTRY_BYTES", 1024 * 1024),
+            },
+            streaming: StreamingConfig {
+                session_check_seconds: env_or("GATEWAY_STREAM_SESSION_CHECK_SECONDS", 60),
             },
         })
     }
 }
 
+/// Read a numeric environment variable, falling back to a default
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Read a comma-separated environment variable, falling back to a default list
+fn env_list(key: &str, default: &str) -> Vec<String> {
+    std::env::var(key)
+        .unwrap_or_else(|_| default.to_string())
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
@@ -0,0 +1,134 @@
+use openapi::ServiceDefaults;
+use serde_json::{json, Value};
+use utoipa::OpenApi;
+
+use crate::handlers;
+use crate::routes::RouteTable;
+
+/// Routes the gateway serves itself rather than forwarding
+#[derive(OpenApi)]
+#[openapi(paths(handlers::order_view), modifiers(&ServiceDefaults))]
+struct GatewayDoc;
+
+/// Merge the upstream documents into one, keyed by the paths clients call on
+/// the gateway.
+///
+/// A path is kept only if the route table forwards it back to the service
+/// that documented it, so unrouted or shadowed routes are left out. Schemas
+/// are shared through the `models` crate; the first definition of a name wins.
+pub fn merge(routes: &RouteTable, upstream_documents: Vec<(String, Value)>) -> Value {
+    let mut merged = serde_json::to_value(GatewayDoc::openapi()).unwrap_or_default();
+    let mut paths = merged["paths"].as_object().cloned().unwrap_or_default();
+    let mut schemas = merged["components"]["schemas"].as_object().cloned().unwrap_or_default();
+    let mut tags = vec![json!({ "name": "gateway" })];
+
+    for (service_name, document) in upstream_documents {
+        tags.push(json!({ "name": document["info"]["title"].clone() }));
+
+        if let Some(documented) = document["paths"].as_object() {
+            for (path, item) in documented {
+                let (Some(gateway_path), Some(operations)) =
+                    (gateway_path(routes, &service_name, path), item.as_object())
+                else {
+                    continue;
+                };
+
+                let merged_item = paths.entry(gateway_path).or_insert_with(|| json!({}));
+                for (method, operation) in operations {
+                    if merged_item.get(method).is_none() {
+                        merged_item[method] = operation.clone();
+                    }
+                }
+            }
+        }
+
+        if let Some(documented) = document["components"]["schemas"].as_object() {
+            for (name, schema) in documented {
+                schemas.entry(name.clone()).or_insert_with(|| schema.clone());
+            }
+        }
+    }
+
+    merged["info"]["title"] = json!("RestMan API");
+    merged["paths"] = Value::Object(paths);
+    merged["components"]["schemas"] = Value::Object(schemas);
+    merged["tags"] = json!(tags);
+    merged
+}
+
+/// Gateway path a service's documented path is reached at, if the route
+/// table forwards it to that service
+fn gateway_path(routes: &RouteTable, service_name: &str, path: &str) -> Option<String> {
+    routes
+        .routes
+        .iter()
+        .filter(|route| route.upstream == service_name)
+        .map(|route| {
+            // A stripped prefix is added back in front of the upstream path
+            if route.strip_prefix {
+                format!("{}{}", route.prefix.trim_end_matches('/'), path)
+            } else {
+                path.to_string()
+            }
+        })
+        .find(|candidate| {
+            routes
+                .find(candidate)
+                .is_some_and(|route| route.upstream == service_name && route.upstream_path(candidate) == path)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Map;
+
+    fn document(title: &str, paths: &[&str]) -> (String, Value) {
+        let items: Map<String, Value> = paths
+            .iter()
+            .map(|path| (path.to_string(), json!({ "get": { "tags": [title] } })))
+            .collect();
+        let document = json!({
+            "info": { "title": title },
+            "paths": items,
+            "components": { "schemas": { "Shared": { "title": title } } },
+        });
+        (title.to_string(), document)
+    }
+
+    #[test]
+    fn test_merge() {
+        let routes = RouteTable::load(None).unwrap();
+        let merged = merge(
+            &routes,
+            vec![
+                document("order", &["/api/orders/{id}", "/internal/orders"]),
+                document("billing", &["/api/billing/orders/{order_id}", "/api/orders"]),
+            ],
+        );
+
+        let paths = merged["paths"].as_object().unwrap();
+        assert!(paths.contains_key("/api/orders/{id}"));
+        assert!(paths.contains_key("/api/billing/orders/{order_id}"));
+        assert!(paths.contains_key("/api/views/orders/{order_id}"));
+        // Not routed, or routed to another service
+        assert!(!paths.contains_key("/internal/orders"));
+        assert!(!paths.contains_key("/api/orders"));
+
+        assert_eq!(merged["components"]["schemas"]["Shared"]["title"], "order");
+        assert!(merged["components"]["schemas"]["OrderView"].is_object());
+    }
+
+    #[test]
+    fn test_stripped_prefix() {
+        let mut routes = RouteTable::load(None).unwrap();
+        for route in routes.routes.iter_mut().filter(|route| route.prefix == "/api/analytics") {
+            route.strip_prefix = true;
+        }
+
+        assert_eq!(
+            gateway_path(&routes, "analytics", "/top-foods"),
+            Some("/api/analytics/top-foods".to_string())
+        );
+    }
+}
@@ -1,5 +1,10 @@
 use actix_web::{web, HttpRequest, HttpResponse, Responder};
-use crate::proxy::ProxyService;
+use error_handling::{AppError, ErrorResponse};
+use uuid::Uuid;
+use crate::config::AdminConfig;
+use crate::docs;
+use crate::proxy::{HealthStatus, ProxyService};
+use crate::views::{self, OrderView};
 
 pub async fn health_check() -> impl Responder {
     HttpResponse::Ok().json(serde_json::json!({
@@ -8,48 +13,107 @@ pub async fn health_check() -> impl Responder {
     }))
 }
 
-pub async fn proxy_handler(
-    req: HttpRequest,
-    body: web::Bytes,
-    proxy_service: web::Data<ProxyService>,
-) -> Result<HttpResponse, actix_web::Error> {
-    proxy_service.proxy_request(req, body).await
+/// Audit log query served by analytics-service
+const AUDIT_QUERY_PATH: &str = "/api/analytics/audit";
+
+/// Admin endpoints require `X-Admin-Token` to match `GATEWAY_ADMIN_TOKEN`
+fn require_admin(req: &HttpRequest, admin: &AdminConfig) -> Result<(), AppError> {
+    identity::require_token(req, "X-Admin-Token", admin.token.as_deref())
 }
 
-// Health check proxy handlers for each service
-pub async fn auth_health(
+/// Circuit breaker and endpoint state for each upstream, guarded by `GATEWAY_ADMIN_TOKEN`
+pub async fn upstream_status(
+    req: HttpRequest,
+    admin: web::Data<AdminConfig>,
     proxy_service: web::Data<ProxyService>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    proxy_service.proxy_health_check("auth", "/api/auth/health").await
+    require_admin(&req, &admin)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "upstreams": proxy_service.upstream_snapshots()
+    })))
 }
 
-pub async fn restaurant_health(
+/// Query the audit log of mutating calls, guarded by `GATEWAY_ADMIN_TOKEN`.
+/// Filters in the query string are passed on to analytics-service.
+pub async fn audit_log(
+    req: HttpRequest,
+    admin: web::Data<AdminConfig>,
     proxy_service: web::Data<ProxyService>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    proxy_service.proxy_health_check("restaurant", "/api/health").await
+    require_admin(&req, &admin)?;
+
+    let path = match req.query_string() {
+        "" => AUDIT_QUERY_PATH.to_string(),
+        query => format!("{}?{}", AUDIT_QUERY_PATH, query),
+    };
+    let (status, body) = proxy_service.fetch(&req, &path).await?;
+
+    Ok(HttpResponse::build(status)
+        .content_type("application/json")
+        .body(body))
 }
 
-pub async fn order_health(
+pub async fn proxy_handler(
+    req: HttpRequest,
+    payload: web::Payload,
     proxy_service: web::Data<ProxyService>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    proxy_service.proxy_health_check("order", "/api/health").await
+    proxy_service.proxy_request(req, payload).await
 }
 
-pub async fn kitchen_health(
+/// Proxy a single service's health endpoint
+pub async fn service_health(
+    req: HttpRequest,
     proxy_service: web::Data<ProxyService>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    proxy_service.proxy_health_check("kitchen", "/api/health").await
+    proxy_service.proxy_health_check(req.path()).await
 }
 
-pub async fn billing_health(
-    proxy_service: web::Data<ProxyService>,
-) -> Result<HttpResponse, actix_web::Error> {
-    proxy_service.proxy_health_check("billing", "/api/health").await
+/// Probe all upstreams; 503 only when every service is down
+pub async fn deep_health_check(proxy_service: web::Data<ProxyService>) -> impl Responder {
+    let health = proxy_service.deep_health().await;
+
+    let mut response = match health.status {
+        HealthStatus::Down => HttpResponse::ServiceUnavailable(),
+        HealthStatus::Healthy | HealthStatus::Degraded => HttpResponse::Ok(),
+    };
+
+    response.json(health)
 }
 
-pub async fn analytics_health(
+/// Order, kitchen ticket and bill in one document; 502 only when every
+/// section failed
+#[utoipa::path(
+    get,
+    path = "/api/views/orders/{order_id}",
+    summary = "Order with its kitchen ticket and bill",
+    params(("order_id" = Uuid, Path, description = "Order id")),
+    responses(
+        (status = 200, description = "Sections that loaded, with errors for those that failed", body = OrderView),
+        (status = 404, description = "Order not found", body = ErrorResponse),
+        (status = 502, description = "No section could be loaded", body = OrderView),
+    ),
+    security(("session" = []))
+)]
+pub async fn order_view(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
     proxy_service: web::Data<ProxyService>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    proxy_service.proxy_health_check("analytics", "/api/health").await
+    let view = views::order_view(&proxy_service, &req, path.into_inner()).await?;
+
+    let mut response = if view.is_unavailable() {
+        HttpResponse::BadGateway()
+    } else {
+        HttpResponse::Ok()
+    };
+    Ok(response.json(view))
 }
 
+/// OpenAPI document of every upstream, merged and rewritten to gateway paths.
+/// Services that cannot be reached are left out.
+pub async fn openapi_spec(proxy_service: web::Data<ProxyService>) -> impl Responder {
+    let documents = proxy_service.upstream_documents().await;
+    HttpResponse::Ok().json(docs::merge(&proxy_service.route_table(), documents))
+}
@@ -1,11 +1,20 @@
+mod audit;
+mod cache;
 mod config;
+mod docs;
 mod handlers;
 mod middleware;
+mod policy;
 mod proxy;
+mod routes;
+mod sessions;
+mod views;
 
-use actix_cors::Cors;
 use actix_web::{middleware::Logger, web, App, HttpServer};
+use std::sync::Arc;
+use std::time::Duration;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use utoipa_swagger_ui::SwaggerUi;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -37,20 +46,131 @@ async fn main() -> std::io::Result<()> {
 
     tracing::info!("Redis connection established");
 
+    // Cache validated sessions in memory, evicted via Redis pub/sub
+    let mut session_cache = sessions::SessionCache::new(
+        redis_conn.clone(),
+        config.session_cache.capacity,
+        Duration::from_secs(config.session_cache.ttl_seconds),
+    );
+    if config.session_cache.sliding_seconds > 0 {
+        session_cache = session_cache.with_sliding_expiry(sessions::SlidingExpiry {
+            idle_timeout: Duration::from_secs(config.session_cache.sliding_seconds),
+            max_age: Duration::from_secs(config.session_cache.max_age_seconds),
+        });
+    }
+    let session_cache = Arc::new(session_cache);
+    let redis_client =
+        redis::Client::open(config.redis.url.as_str()).expect("Failed to create Redis client");
+    sessions::spawn_invalidation_listener(redis_client, session_cache.clone());
+
+    // Load the route table
+    let route_table = routes::RouteTable::load(config.routes.routes_file.as_deref())
+        .expect("Failed to load route table");
+    tracing::info!(
+        "Loaded {} routes to {} upstreams",
+        route_table.routes.len(),
+        route_table.upstreams.len()
+    );
+    let shared_routes = routes::SharedRoutes::new(route_table);
+
     // Create proxy service
     let proxy_service = proxy::ProxyService::new(
-        config.services.auth_url.clone(),
-        config.services.restaurant_url.clone(),
-        config.services.order_url.clone(),
-        config.services.kitchen_url.clone(),
-        config.services.billing_url.clone(),
-        config.services.analytics_url.clone(),
+        shared_routes.clone(),
+        &config.upstream,
+        identity::IdentitySigner::new(&config.identity.signing_secret),
+        session_cache.clone(),
+        Duration::from_secs(config.streaming.session_check_seconds),
+        config.rate_limit.trusted_proxies.clone(),
     );
 
     let proxy_service_data = web::Data::new(proxy_service);
+    proxy::spawn_endpoint_probes(
+        proxy_service_data.clone(),
+        Duration::from_secs(config.upstream.endpoint_probe_interval_seconds),
+    );
+    let admin_config_data = web::Data::new(config.admin.clone());
+
+    // Hot-reload the route table when it comes from a file
+    if let Some(routes_file) = config.routes.routes_file.clone() {
+        let proxy_service = proxy_service_data.clone();
+        routes::watch(
+            routes_file,
+            |path| routes::RouteTable::load(Some(path)),
+            move |table| {
+                tracing::info!(
+                    "Reloaded {} routes and {} upstreams",
+                    table.routes.len(),
+                    table.upstreams.len()
+                );
+                proxy_service.reload(table);
+            },
+        );
+    }
+
+    // Load route authorization policies, hot-reloaded like the route table
+    // so a new upstream can be opened up without a restart
+    let policies = routes::Shared::new(
+        policy::PolicyTable::load(config.authorization.policy_file.as_deref())
+            .expect("Failed to load route authorization policies"),
+    );
+    if let Some(policy_file) = config.authorization.policy_file.clone() {
+        let policies = policies.clone();
+        routes::watch(
+            policy_file,
+            |path| policy::PolicyTable::load(Some(path)),
+            move |table| policies.replace(table),
+        );
+    }
 
     // Create auth middleware
-    let auth_middleware = middleware::AuthMiddleware::new(redis_conn);
+    let auth_middleware = middleware::AuthMiddleware::new(session_cache, policies, shared_routes.clone());
+
+    // Load per-route rate limits, hot-reloaded too, and create rate limit middleware
+    let rate_limits = routes::Shared::new(
+        middleware::RateLimitTable::load(config.rate_limit.limits_file.as_deref())
+            .expect("Failed to load rate limits"),
+    );
+    if let Some(limits_file) = config.rate_limit.limits_file.clone() {
+        let rate_limits = rate_limits.clone();
+        routes::watch(
+            limits_file,
+            |path| middleware::RateLimitTable::load(Some(path)),
+            move |table| rate_limits.replace(table),
+        );
+    }
+    let rate_limit_middleware = middleware::RateLimitMiddleware::new(
+        redis_conn,
+        rate_limits,
+        config.rate_limit.trusted_proxies.clone(),
+    );
+
+    // Cache configured public GET routes, purged by Kafka events
+    let cache_rules = cache::CacheRules::load(config.response_cache.rules_file.as_deref())
+        .expect("Failed to load response cache rules");
+    let response_cache = Arc::new(cache::ResponseCache::new(
+        cache_rules,
+        config.response_cache.capacity,
+        config.response_cache.max_entry_bytes,
+    ));
+    cache::spawn_purge_listener(config.kafka.brokers.clone(), response_cache.clone());
+    let response_cache_middleware = middleware::ResponseCacheMiddleware::new(response_cache);
+
+    // Audit every mutating call to Kafka
+    let audit_producer =
+        kafka_client::create_producer(&config.kafka.brokers).expect("Failed to create Kafka producer");
+    let audit_middleware = middleware::AuditMiddleware::new(audit::AuditLog::new(audit_producer));
+
+    // CORS allowlist and per-route security headers
+    let cors_config = config.cors.clone();
+    let security_headers_middleware = middleware::SecurityHeadersMiddleware::new(shared_routes.clone());
+
+    // Proxied requests reach the default service, so label them by route table entry
+    let metrics_middleware = metrics::MetricsMiddleware::new("gateway").with_route_label(move |path| {
+        shared_routes
+            .current()
+            .find(path)
+            .map(|route| route.prefix.clone())
+    });
 
     // Start HTTP server
     let server_host = config.server.host.clone();
@@ -59,26 +179,39 @@ async fn main() -> std::io::Result<()> {
     tracing::info!("API Gateway ready to accept requests");
 
     HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_method()
-            .allow_any_header()
-            .max_age(3600);
-
         App::new()
-            .wrap(cors)
+            // Innermost so auth and rate limits also apply to cache hits
+            .wrap(response_cache_middleware.clone())
             .wrap(Logger::default())
-            .wrap(tracing_actix_web::TracingLogger::default())
+            .wrap(tracing_actix_web::TracingLogger::<correlation::RequestIdRootSpan>::new())
+            // Rate limiting runs inside auth so it can key on the session user
+            .wrap(rate_limit_middleware.clone())
             .wrap(auth_middleware.clone())
+            // Outside auth so the session is known and rejected calls are audited too
+            .wrap(audit_middleware.clone())
+            // Turns rejections into responses, so CORS headers are added to them too
+            .wrap(security_headers_middleware.clone())
+            // Outside auth so preflight requests are answered without a session
+            .wrap(middleware::cors::build(&cors_config))
+            // Outside auth and rate limiting so rejected requests are counted
+            .wrap(metrics_middleware.clone())
+            // Outermost so every response, including auth failures, carries an id
+            .wrap(correlation::RequestIdMiddleware)
             .app_data(proxy_service_data.clone())
+            .app_data(admin_config_data.clone())
             .route("/health", web::get().to(handlers::health_check))
-            // Explicit health check routes for each service
-            .route("/api/auth/health", web::get().to(handlers::auth_health))
-            .route("/api/restaurants/health", web::get().to(handlers::restaurant_health))
-            .route("/api/orders/health", web::get().to(handlers::order_health))
-            .route("/api/kitchen/health", web::get().to(handlers::kitchen_health))
-            .route("/api/billing/health", web::get().to(handlers::billing_health))
-            .route("/api/analytics/health", web::get().to(handlers::analytics_health))
+            .route("/health/deep", web::get().to(handlers::deep_health_check))
+            .route("/admin/upstreams", web::get().to(handlers::upstream_status))
+            .route("/admin/audit", web::get().to(handlers::audit_log))
+            .route("/metrics", web::get().to(metrics::metrics_handler))
+            // Health check for each upstream service
+            .route("/api/{service}/health", web::get().to(handlers::service_health))
+            // Views combining several services
+            .route("/api/views/orders/{order_id}", web::get().to(handlers::order_view))
+            // Merged OpenAPI document of every service, and a Swagger UI for it
+            .route("/api/openapi.json", web::get().to(handlers::openapi_spec))
+            .service(web::redirect("/api/docs", "/api/docs/"))
+            .service(SwaggerUi::new("/api/docs/{_:.*}").config(utoipa_swagger_ui::Config::from("/api/openapi.json")))
             .default_service(web::to(handlers::proxy_handler))
     })
     .bind((server_host.as_str(), server_port))?
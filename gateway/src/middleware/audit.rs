@@ -0,0 +1,109 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpMessage, HttpRequest,
+};
+use correlation::RequestId;
+use futures_util::future::LocalBoxFuture;
+use models::events::AuditRecordedData;
+use models::user::Session;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::audit::{self, AuditLog};
+
+/// Records every mutating call, including ones rejected by auth or rate
+/// limiting, to the audit log once its response status is known.
+///
+/// Register it outside auth so the session is known and rejections are seen.
+#[derive(Clone)]
+pub struct AuditMiddleware {
+    log: AuditLog,
+}
+
+impl AuditMiddleware {
+    pub fn new(log: AuditLog) -> Self {
+        Self { log }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AuditMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AuditMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuditMiddlewareService {
+            service: Rc::new(service),
+            log: self.log.clone(),
+        }))
+    }
+}
+
+pub struct AuditMiddlewareService<S> {
+    service: Rc<S>,
+    log: AuditLog,
+}
+
+impl<S, B> Service<ServiceRequest> for AuditMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let log = self.log.clone();
+
+        Box::pin(async move {
+            if !AuditLog::audits(req.method()) {
+                return service.call(req).await;
+            }
+
+            let started = Instant::now();
+            let request = req.request().clone();
+            let result = service.call(req).await;
+
+            let status = match &result {
+                Ok(res) => res.status(),
+                Err(e) => e.as_response_error().status_code(),
+            };
+            log.record(audit_record(&request, status.as_u16(), started));
+
+            result
+        })
+    }
+}
+
+/// Build the record of a completed call from what auth left in the request
+fn audit_record(req: &HttpRequest, status: u16, started: Instant) -> AuditRecordedData {
+    let extensions = req.extensions();
+    let session = extensions.get::<Session>();
+
+    AuditRecordedData {
+        user_id: session.map(|session| session.user_id),
+        role: session.map(|session| session.role.clone()),
+        api_key_id: session
+            .and_then(|session| session.scope.as_ref())
+            .map(|scope| scope.api_key_id),
+        restaurant_id: audit::restaurant_id(session, req.path()),
+        method: req.method().to_string(),
+        path: req.path().to_string(),
+        status,
+        latency_ms: started.elapsed().as_millis().try_into().unwrap_or(u32::MAX),
+        request_id: extensions.get::<RequestId>().map(|id| id.0.clone()),
+    }
+}
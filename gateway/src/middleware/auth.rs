@@ -2,22 +2,29 @@ use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
     Error, HttpMessage,
 };
+use error_handling::AppError;
 use futures_util::future::LocalBoxFuture;
-use models::user::Session;
-use redis::aio::ConnectionManager;
-use redis::AsyncCommands;
+use identity::Identity;
 use std::future::{ready, Ready};
 use std::sync::Arc;
 
+use crate::policy::{Decision, PolicyTable};
+use crate::routes::{Shared, SharedRoutes};
+use crate::sessions::{Credential, SessionCache};
+
 #[derive(Clone)]
 pub struct AuthMiddleware {
-    redis: Arc<tokio::sync::Mutex<ConnectionManager>>,
+    sessions: Arc<SessionCache>,
+    policies: Shared<PolicyTable>,
+    routes: SharedRoutes,
 }
 
 impl AuthMiddleware {
-    pub fn new(redis: ConnectionManager) -> Self {
+    pub fn new(sessions: Arc<SessionCache>, policies: Shared<PolicyTable>, routes: SharedRoutes) -> Self {
         Self {
-            redis: Arc::new(tokio::sync::Mutex::new(redis)),
+            sessions,
+            policies,
+            routes,
         }
     }
 }
@@ -37,14 +44,18 @@ where
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(AuthMiddlewareService {
             service: Arc::new(service),
-            redis: self.redis.clone(),
+            sessions: self.sessions.clone(),
+            policies: self.policies.clone(),
+            routes: self.routes.clone(),
         }))
     }
 }
 
 pub struct AuthMiddlewareService<S> {
     service: Arc<S>,
-    redis: Arc<tokio::sync::Mutex<ConnectionManager>>,
+    sessions: Arc<SessionCache>,
+    policies: Shared<PolicyTable>,
+    routes: SharedRoutes,
 }
 
 impl<S, B> Service<ServiceRequest> for AuthMiddlewareService<S>
@@ -61,29 +72,39 @@ where
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let service = self.service.clone();
-        let redis = self.redis.clone();
+        let sessions = self.sessions.clone();
+        let policies = self.policies.current();
+        let routes = self.routes.current();
 
         Box::pin(async move {
             let path = req.path();
 
-            // Skip auth for public endpoints
-            if path.starts_with("/api/auth") || path.ends_with("/health") {
+            // Skip auth for public routes, health checks and metrics; admin endpoints check their own token
+            let is_public = !routes.requires_auth(path)
+                || path.starts_with("/admin/")
+                || path.ends_with("/health")
+                || path == "/health/deep"
+                || path == "/metrics"
+                || path == "/api/openapi.json"
+                || path == "/api/docs"
+                || path.starts_with("/api/docs/");
+
+            if is_public {
                 return service.call(req).await;
             }
 
-            // Extract session token from Authorization header
+            // Extract the session token or API key from the Authorization header
             let auth_header = req.headers().get("Authorization");
             
-            let session_token = match auth_header {
+            let credential = match auth_header {
                 Some(header_value) => {
                     match header_value.to_str() {
-                        Ok(value) => {
-                            if value.starts_with("Bearer ") {
-                                value.trim_start_matches("Bearer ").to_string()
-                            } else {
+                        Ok(value) => match Credential::from_header(value) {
+                            Some(credential) => credential,
+                            None => {
                                 return Err(actix_web::error::ErrorUnauthorized("Invalid authorization header format"));
                             }
-                        }
+                        },
                         Err(_) => {
                             return Err(actix_web::error::ErrorUnauthorized("Invalid authorization header"));
                         }
@@ -94,32 +115,45 @@ where
                 }
             };
 
-            // Validate session token from Redis
-            let session_key = format!("session:{}", session_token);
-            let mut redis_conn = redis.lock().await;
-            
-            let session_json: Result<String, redis::RedisError> = redis_conn.get(&session_key).await;
-            
-            match session_json {
-                Ok(json) => {
-                    match serde_json::from_str::<Session>(&json) {
-                        Ok(session) => {
-                            // Store session in request extensions for downstream handlers
-                            req.extensions_mut().insert(session.clone());
-                            
-                            // Continue to the service
-                            drop(redis_conn);
-                            service.call(req).await
-                        }
-                        Err(_) => {
-                            Err(actix_web::error::ErrorUnauthorized("Invalid session data"))
-                        }
-                    }
+            // Validate the credential against the cache, falling back to Redis.
+            // API keys resolve to a session synthesised by auth-service.
+            let session = match sessions.get(&credential).await {
+                Ok(Some(session)) => session,
+                Ok(None) => {
+                    return Err(actix_web::error::ErrorUnauthorized("Session not found or expired"));
                 }
-                Err(_) => {
-                    Err(actix_web::error::ErrorUnauthorized("Session not found or expired"))
+                Err(e) => {
+                    tracing::error!("Failed to look up session: {}", e);
+                    return Err(actix_web::error::ErrorUnauthorized("Session not found or expired"));
                 }
+            };
+
+            // Enforce route authorization policy
+            if let Decision::Deny(reason) = policies.evaluate(req.method(), req.path(), &session) {
+                tracing::warn!(
+                    "Denied {} {} for user {} ({}): {}",
+                    req.method(),
+                    req.path(),
+                    session.user_id,
+                    session.role,
+                    reason
+                );
+                return Err(AppError::Forbidden(reason).into());
             }
+
+            // Store session and derived identity in request extensions for downstream handlers
+            req.extensions_mut().insert(Identity {
+                user_id: session.user_id,
+                email: session.email.clone(),
+                role: session.role.clone(),
+                session_id: credential.session_id(&session),
+                restaurant_id: session.scope.as_ref().map(|scope| scope.restaurant_id),
+            });
+            req.extensions_mut().insert(session);
+            req.extensions_mut().insert(credential);
+
+            // Continue to the service
+            service.call(req).await
         })
     }
 }
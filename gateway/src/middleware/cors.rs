@@ -0,0 +1,36 @@
+use actix_cors::Cors;
+
+use crate::config::CorsConfig;
+
+/// Response headers browsers may read on cross-origin responses
+const EXPOSED_HEADERS: &[&str] = &[
+    "X-Request-Id",
+    "RateLimit-Limit",
+    "RateLimit-Remaining",
+    "RateLimit-Reset",
+    "Retry-After",
+];
+
+/// Build the gateway's CORS middleware from the configured allowlist.
+/// With no allowed origins, cross-origin browser requests are not allowed.
+pub fn build(config: &CorsConfig) -> Cors {
+    let mut cors = Cors::default()
+        .allowed_methods(config.allowed_methods.iter().map(String::as_str))
+        .allowed_headers(config.allowed_headers.iter().map(String::as_str))
+        .expose_headers(EXPOSED_HEADERS.iter().copied())
+        .max_age(config.max_age_seconds);
+
+    for origin in &config.allowed_origins {
+        cors = if origin == "*" {
+            cors.allow_any_origin().send_wildcard()
+        } else {
+            cors.allowed_origin(origin)
+        };
+    }
+
+    if config.allow_credentials {
+        cors = cors.supports_credentials();
+    }
+
+    cors
+}
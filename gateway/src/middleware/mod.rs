@@ -1,4 +1,12 @@
+pub mod audit;
 pub mod auth;
+pub mod cors;
+pub mod rate_limit;
+pub mod response_cache;
+pub mod security_headers;
 
+pub use audit::AuditMiddleware;
 pub use auth::AuthMiddleware;
-
+pub use rate_limit::{RateLimitMiddleware, RateLimitTable};
+pub use response_cache::ResponseCacheMiddleware;
+pub use security_headers::SecurityHeadersMiddleware;
@@ -0,0 +1,270 @@
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{
+        header::{self, HeaderName, HeaderValue},
+        Method,
+    },
+    Error, HttpMessage, HttpResponse,
+};
+use futures_util::future::LocalBoxFuture;
+use ipnet::IpNet;
+use models::user::Session;
+use redis::aio::ConnectionManager;
+use serde::Deserialize;
+use std::future::{ready, Ready};
+use std::sync::Arc;
+
+use crate::policy::match_path;
+use crate::proxy::client_ip;
+use crate::routes::Shared;
+
+/// Default rate limits shipped with the gateway
+const DEFAULT_RATE_LIMITS: &str = include_str!("../../rate_limits.toml");
+
+/// Sliding window log: drop expired entries, admit the request if under the
+/// limit, and report the remaining time until the oldest entry expires.
+const SLIDING_WINDOW_SCRIPT: &str = r#"
+local key = KEYS[1]
+local now = tonumber(ARGV[1])
+local window = tonumber(ARGV[2])
+local limit = tonumber(ARGV[3])
+local member = ARGV[4]
+
+redis.call('ZREMRANGEBYSCORE', key, 0, now - window)
+local count = redis.call('ZCARD', key)
+local allowed = 0
+if count < limit then
+    redis.call('ZADD', key, now, member)
+    count = count + 1
+    allowed = 1
+end
+redis.call('PEXPIRE', key, window)
+
+local reset = window
+local oldest = redis.call('ZRANGE', key, 0, 0, 'WITHSCORES')
+if oldest[2] then
+    reset = tonumber(oldest[2]) + window - now
+end
+return {allowed, count, reset}
+"#;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub window_seconds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RouteRateLimit {
+    #[serde(default)]
+    pub methods: Vec<String>,
+    pub path: String,
+    pub limit: u64,
+    pub window_seconds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitTable {
+    pub default: RateLimit,
+    #[serde(default)]
+    pub routes: Vec<RouteRateLimit>,
+}
+
+impl RateLimitTable {
+    /// Load limits from a TOML/JSON/YAML file, or the bundled defaults when no path is given
+    pub fn load(path: Option<&str>) -> anyhow::Result<Self> {
+        let source = match path {
+            Some(path) => config::Config::builder().add_source(config::File::with_name(path)),
+            None => config::Config::builder()
+                .add_source(config::File::from_str(DEFAULT_RATE_LIMITS, config::FileFormat::Toml)),
+        };
+
+        let table: RateLimitTable = source.build()?.try_deserialize()?;
+        tracing::info!("Loaded {} route rate limits", table.routes.len());
+
+        Ok(table)
+    }
+
+    /// Find the limit for a request, returning a bucket name and the limit
+    fn resolve(&self, method: &Method, path: &str) -> (String, RateLimit) {
+        self.routes
+            .iter()
+            .find(|route| {
+                (route.methods.is_empty()
+                    || route.methods.iter().any(|m| m.eq_ignore_ascii_case(method.as_str())))
+                    && match_path(&route.path, path).is_some()
+            })
+            .map(|route| {
+                (
+                    format!("{}:{}", route.methods.join(","), route.path),
+                    RateLimit {
+                        limit: route.limit,
+                        window_seconds: route.window_seconds,
+                    },
+                )
+            })
+            .unwrap_or_else(|| ("default".to_string(), self.default.clone()))
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitMiddleware {
+    redis: ConnectionManager,
+    table: Shared<RateLimitTable>,
+    script: Arc<redis::Script>,
+    trusted_proxies: Arc<Vec<IpNet>>,
+}
+
+impl RateLimitMiddleware {
+    pub fn new(redis: ConnectionManager, table: Shared<RateLimitTable>, trusted_proxies: Vec<IpNet>) -> Self {
+        Self {
+            redis,
+            table,
+            script: Arc::new(redis::Script::new(SLIDING_WINDOW_SCRIPT)),
+            trusted_proxies: Arc::new(trusted_proxies),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimitMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RateLimitMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddlewareService {
+            service: Arc::new(service),
+            config: self.clone(),
+        }))
+    }
+}
+
+pub struct RateLimitMiddlewareService<S> {
+    service: Arc<S>,
+    config: RateLimitMiddleware,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let config = self.config.clone();
+
+        Box::pin(async move {
+            // Skip health checks and metrics scrapes
+            if req.path().ends_with("/health")
+                || req.path() == "/health/deep"
+                || req.path() == "/metrics"
+            {
+                return service.call(req).await.map(|res| res.map_into_left_body());
+            }
+
+            // Key by API key or session user for authenticated routes, client IP otherwise
+            let subject = match req.extensions().get::<Session>() {
+                Some(Session { scope: Some(scope), .. }) => format!("api_key:{}", scope.api_key_id),
+                Some(session) => format!("user:{}", session.user_id),
+                None => {
+                    let ip = client_ip(req.request(), &config.trusted_proxies);
+                    format!("ip:{}", ip.unwrap_or_else(|| "unknown".to_string()))
+                }
+            };
+
+            let (bucket, limit) = config.table.current().resolve(req.method(), req.path());
+            let key = format!("ratelimit:{}:{}", bucket, subject);
+            let window_ms = limit.window_seconds * 1000;
+            let now_ms = chrono::Utc::now().timestamp_millis();
+
+            let mut redis = config.redis.clone();
+            let result: Result<(i64, u64, u64), redis::RedisError> = config
+                .script
+                .key(&key)
+                .arg(now_ms)
+                .arg(window_ms)
+                .arg(limit.limit)
+                .arg(uuid::Uuid::new_v4().to_string())
+                .invoke_async(&mut redis)
+                .await;
+
+            let (allowed, count, reset_ms) = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    // Fail open: an unavailable Redis must not take the gateway down
+                    tracing::warn!("Rate limit check failed for {}: {}", key, e);
+                    return service.call(req).await.map(|res| res.map_into_left_body());
+                }
+            };
+
+            let remaining = limit.limit.saturating_sub(count);
+            let reset_seconds = reset_ms.div_ceil(1000).max(1);
+
+            let headers = [
+                ("ratelimit-limit", limit.limit.to_string()),
+                ("ratelimit-remaining", remaining.to_string()),
+                ("ratelimit-reset", reset_seconds.to_string()),
+            ];
+
+            if allowed == 0 {
+                tracing::warn!("Rate limit exceeded for {} on {} {}", subject, req.method(), req.path());
+
+                let mut response = HttpResponse::TooManyRequests();
+                for header in headers {
+                    response.insert_header(header);
+                }
+                response.insert_header((header::RETRY_AFTER, reset_seconds.to_string()));
+
+                let response = response.json(serde_json::json!({
+                    "error": "TOO_MANY_REQUESTS",
+                    "message": "Too many requests, please retry later",
+                    "retry_after": reset_seconds,
+                }));
+
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+
+            let mut res = service.call(req).await?;
+            for (name, value) in headers {
+                if let Ok(value) = HeaderValue::from_str(&value) {
+                    res.headers_mut().insert(HeaderName::from_static(name), value);
+                }
+            }
+
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_route_and_default_limits() {
+        let table = RateLimitTable::load(None).unwrap();
+
+        let (bucket, limit) = table.resolve(&Method::POST, "/api/auth/request-otp");
+        assert_eq!(bucket, "POST:/api/auth/request-otp");
+        assert_eq!(limit.limit, 5);
+
+        let (bucket, limit) = table.resolve(&Method::GET, "/api/restaurants");
+        assert_eq!(bucket, "default");
+        assert_eq!(limit.limit, table.default.limit);
+    }
+}
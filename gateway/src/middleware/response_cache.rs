@@ -0,0 +1,296 @@
+use actix_web::{
+    body::{self, EitherBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{
+        header::{self, HeaderMap, HeaderName, HeaderValue},
+        Method, StatusCode,
+    },
+    Error, HttpResponse,
+};
+use futures_util::future::LocalBoxFuture;
+use sha2::{Digest, Sha256};
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::cache::{CachedResponse, ResponseCache};
+
+/// Headers sent with a 304 in place of the full response headers
+const NOT_MODIFIED_HEADERS: &[HeaderName] = &[
+    header::CACHE_CONTROL,
+    header::CONTENT_LOCATION,
+    header::EXPIRES,
+    header::VARY,
+];
+
+/// Serves configured GET routes from the in-memory response cache, with
+/// `If-None-Match` revalidation. Registered inside auth and rate limiting so
+/// cached responses are still only served to permitted callers.
+#[derive(Clone)]
+pub struct ResponseCacheMiddleware {
+    cache: Arc<ResponseCache>,
+}
+
+impl ResponseCacheMiddleware {
+    pub fn new(cache: Arc<ResponseCache>) -> Self {
+        Self { cache }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ResponseCacheMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: body::MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ResponseCacheMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ResponseCacheMiddlewareService {
+            service: Rc::new(service),
+            cache: self.cache.clone(),
+        }))
+    }
+}
+
+pub struct ResponseCacheMiddlewareService<S> {
+    service: Rc<S>,
+    cache: Arc<ResponseCache>,
+}
+
+impl<S, B> Service<ServiceRequest> for ResponseCacheMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: body::MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let cache = self.cache.clone();
+
+        Box::pin(async move {
+            let cache_match = if req.method() == Method::GET {
+                cache.rules().resolve(req.path())
+            } else {
+                None
+            };
+            let request_directives = CacheControl::parse(req.headers());
+
+            let cache_match = match cache_match {
+                Some(cache_match) if !request_directives.no_store => cache_match,
+                _ => return service.call(req).await.map(|res| res.map_into_left_body()),
+            };
+
+            let key = match req.query_string() {
+                "" => req.path().to_string(),
+                query => format!("{}?{}", req.path(), query),
+            };
+            let if_none_match = req
+                .headers()
+                .get(header::IF_NONE_MATCH)
+                .and_then(|h| h.to_str().ok())
+                .map(str::to_string);
+
+            // `no-cache` asks for a fresh response, which then replaces the entry
+            if !request_directives.no_cache {
+                if let Some(entry) = cache.get(&key) {
+                    metrics::record_cache("gateway_response", true);
+                    let response = cached_response(&entry, true, if_none_match.as_deref());
+                    return Ok(ServiceResponse::new(req.into_parts().0, response).map_into_right_body());
+                }
+            }
+            metrics::record_cache("gateway_response", false);
+
+            let res = service.call(req).await?;
+
+            let ttl = match storable_ttl(&res, cache_match.ttl, cache.max_entry_bytes()) {
+                Some(ttl) => ttl,
+                None => return Ok(res.map_into_left_body()),
+            };
+
+            let (http_req, res) = res.into_parts();
+            let (head, response_body) = res.into_parts();
+
+            let bytes = match body::to_bytes_limited(response_body, cache.max_entry_bytes()).await {
+                Ok(Ok(bytes)) => bytes,
+                Ok(Err(e)) => {
+                    let e: Box<dyn std::error::Error> = e.into();
+                    tracing::error!("Failed to read response body for {}: {}", key, e);
+                    return Err(actix_web::error::ErrorBadGateway("Failed to read upstream response"));
+                }
+                Err(_) => {
+                    // Content-Length was within the limit, so the upstream sent more than it declared
+                    tracing::error!("Response body for {} exceeded its Content-Length", key);
+                    return Err(actix_web::error::ErrorBadGateway("Invalid upstream response"));
+                }
+            };
+
+            let etag = head
+                .headers()
+                .get(header::ETAG)
+                .cloned()
+                .unwrap_or_else(|| compute_etag(&bytes));
+
+            let headers = head
+                .headers()
+                .iter()
+                .filter(|(name, _)| **name != header::CONTENT_LENGTH && **name != header::ETAG)
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+
+            let entry = CachedResponse::new(head.status(), headers, bytes, etag, ttl, cache_match.tag);
+            let response = cached_response(&entry, false, if_none_match.as_deref());
+            cache.insert(key, entry);
+
+            Ok(ServiceResponse::new(http_req, response).map_into_right_body())
+        })
+    }
+}
+
+/// Cache-Control directives relevant to a shared cache
+#[derive(Default)]
+struct CacheControl {
+    no_store: bool,
+    no_cache: bool,
+    private: bool,
+    max_age: Option<u64>,
+}
+
+impl CacheControl {
+    fn parse(headers: &HeaderMap) -> Self {
+        let mut directives = CacheControl::default();
+        let mut s_maxage = None;
+
+        for value in headers.get_all(header::CACHE_CONTROL) {
+            let Ok(value) = value.to_str() else { continue };
+
+            for directive in value.split(',').map(str::trim) {
+                let (name, argument) = match directive.split_once('=') {
+                    Some((name, argument)) => (name.trim(), Some(argument.trim().trim_matches('"'))),
+                    None => (directive, None),
+                };
+
+                match name.to_ascii_lowercase().as_str() {
+                    "no-store" => directives.no_store = true,
+                    "no-cache" => directives.no_cache = true,
+                    "private" => directives.private = true,
+                    "max-age" => directives.max_age = argument.and_then(|a| a.parse().ok()),
+                    "s-maxage" => s_maxage = argument.and_then(|a| a.parse().ok()),
+                    _ => {}
+                }
+            }
+        }
+
+        // s-maxage overrides max-age for shared caches
+        directives.max_age = s_maxage.or(directives.max_age);
+        directives
+    }
+}
+
+/// How long a response may be cached, or `None` if it must not be
+fn storable_ttl<B>(res: &ServiceResponse<B>, rule_ttl: Duration, max_entry_bytes: usize) -> Option<Duration> {
+    if res.status() != StatusCode::OK || res.headers().contains_key(header::SET_COOKIE) {
+        return None;
+    }
+
+    let directives = CacheControl::parse(res.headers());
+    if directives.no_store || directives.no_cache || directives.private {
+        return None;
+    }
+
+    // Only bodies of a declared, bounded size are buffered
+    let content_length = res
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok())?;
+    if content_length > max_entry_bytes {
+        return None;
+    }
+
+    let ttl = directives
+        .max_age
+        .map(|max_age| rule_ttl.min(Duration::from_secs(max_age)))
+        .unwrap_or(rule_ttl);
+
+    (!ttl.is_zero()).then_some(ttl)
+}
+
+fn compute_etag(body: &[u8]) -> HeaderValue {
+    let digest = Sha256::digest(body);
+    HeaderValue::from_str(&format!("\"{}\"", hex::encode(&digest[..16])))
+        .expect("hex etags are valid header values")
+}
+
+/// Whether an `If-None-Match` header matches an entity tag (weak comparison)
+fn etag_matches(if_none_match: &str, etag: &HeaderValue) -> bool {
+    let Ok(etag) = etag.to_str() else { return false };
+    let etag = etag.trim_start_matches("W/");
+
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+/// Build a response from a cache entry, or a 304 when the client's copy is current
+fn cached_response(entry: &CachedResponse, hit: bool, if_none_match: Option<&str>) -> HttpResponse {
+    let not_modified = if_none_match.is_some_and(|value| etag_matches(value, &entry.etag));
+
+    let mut builder = HttpResponse::build(if not_modified {
+        StatusCode::NOT_MODIFIED
+    } else {
+        entry.status
+    });
+
+    for (name, value) in &entry.headers {
+        if !not_modified || NOT_MODIFIED_HEADERS.contains(name) {
+            builder.append_header((name.clone(), value.clone()));
+        }
+    }
+
+    builder
+        .insert_header((header::ETAG, entry.etag.clone()))
+        .insert_header((header::AGE, entry.stored_at.elapsed().as_secs().to_string()))
+        .insert_header(("X-Cache", if hit { "HIT" } else { "MISS" }));
+
+    if not_modified {
+        builder.finish()
+    } else {
+        builder.body(entry.body.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_control_and_etag_matching() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("public, max-age=60, s-maxage=30"),
+        );
+        let directives = CacheControl::parse(&headers);
+        assert_eq!(directives.max_age, Some(30));
+        assert!(!directives.no_store && !directives.private);
+
+        let etag = HeaderValue::from_static("\"abc\"");
+        assert!(etag_matches("\"abc\"", &etag));
+        assert!(etag_matches("\"xyz\", W/\"abc\"", &etag));
+        assert!(etag_matches("*", &etag));
+        assert!(!etag_matches("\"xyz\"", &etag));
+    }
+}
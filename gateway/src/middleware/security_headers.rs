@@ -0,0 +1,86 @@
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error,
+};
+use futures_util::future::LocalBoxFuture;
+use std::future::{ready, Ready};
+
+use crate::routes::SharedRoutes;
+
+/// Adds the route's security headers (HSTS, X-Content-Type-Options and a
+/// `frame-ancestors` CSP) to every response. Headers already set by the
+/// upstream are left alone.
+///
+/// Errors from inner middleware are turned into responses here so that
+/// rejections such as 401s also carry these and the CORS headers.
+#[derive(Clone)]
+pub struct SecurityHeadersMiddleware {
+    routes: SharedRoutes,
+}
+
+impl SecurityHeadersMiddleware {
+    pub fn new(routes: SharedRoutes) -> Self {
+        Self { routes }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for SecurityHeadersMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = SecurityHeadersMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(SecurityHeadersMiddlewareService {
+            service,
+            routes: self.routes.clone(),
+        }))
+    }
+}
+
+pub struct SecurityHeadersMiddlewareService<S> {
+    service: S,
+    routes: SharedRoutes,
+}
+
+impl<S, B> Service<ServiceRequest> for SecurityHeadersMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let headers = self.routes.current().security_headers(req.path());
+        let request = req.request().clone();
+        let future = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = match future.await {
+                Ok(res) => res.map_into_left_body(),
+                Err(e) => ServiceResponse::from_err(e, request).map_into_right_body(),
+            };
+
+            let response_headers = res.headers_mut();
+            for (name, value) in headers {
+                if !response_headers.contains_key(&name) {
+                    response_headers.insert(name, value);
+                }
+            }
+
+            Ok(res)
+        })
+    }
+}
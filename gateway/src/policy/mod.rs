@@ -0,0 +1,304 @@
+use actix_web::http::Method;
+use models::user::{Session, UserRole};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Default policy table shipped with the gateway
+const DEFAULT_POLICIES: &str = include_str!("../../policies.toml");
+
+/// Ownership constraint applied on top of the role check
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum OwnershipRule {
+    /// The captured path parameter must equal the session user id
+    PathMatchesUser { param: String },
+}
+
+/// A single route authorization policy
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoutePolicy {
+    #[serde(default)]
+    pub methods: Vec<String>,
+    pub path: String,
+    pub roles: Vec<UserRole>,
+    #[serde(default)]
+    pub ownership: Option<OwnershipRule>,
+    /// Whether API key sessions may use the route
+    #[serde(default)]
+    pub api_keys: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PolicyFile {
+    policies: Vec<RoutePolicy>,
+}
+
+/// Outcome of evaluating a request against the policy table
+#[derive(Debug, PartialEq, Eq)]
+pub enum Decision {
+    Allow,
+    Deny(String),
+}
+
+/// Ordered table of route policies; the first matching policy wins
+#[derive(Debug, Clone)]
+pub struct PolicyTable {
+    policies: Vec<RoutePolicy>,
+}
+
+impl PolicyTable {
+    pub fn new(policies: Vec<RoutePolicy>) -> Self {
+        Self { policies }
+    }
+
+    /// Load policies from a TOML/JSON/YAML file, or the bundled defaults when no path is given
+    pub fn load(path: Option<&str>) -> anyhow::Result<Self> {
+        let source = match path {
+            Some(path) => config::Config::builder().add_source(config::File::with_name(path)),
+            None => config::Config::builder()
+                .add_source(config::File::from_str(DEFAULT_POLICIES, config::FileFormat::Toml)),
+        };
+
+        let file: PolicyFile = source.build()?.try_deserialize()?;
+        tracing::info!("Loaded {} route authorization policies", file.policies.len());
+
+        Ok(Self::new(file.policies))
+    }
+
+    /// Decide whether the session may call `method path`
+    pub fn evaluate(&self, method: &Method, path: &str, session: &Session) -> Decision {
+        let policy = self.policies.iter().find_map(|policy| {
+            let method_matches = policy.methods.is_empty()
+                || policy.methods.iter().any(|m| m.eq_ignore_ascii_case(method.as_str()));
+
+            if !method_matches {
+                return None;
+            }
+
+            match_path(&policy.path, path).map(|params| (policy, params))
+        });
+
+        let (policy, params) = match policy {
+            Some(found) => found,
+            None => return Decision::Deny(format!("No access policy for {} {}", method, path)),
+        };
+
+        if !policy.roles.contains(&session.role) {
+            return Decision::Deny(format!(
+                "Role '{}' is not allowed to {} {}",
+                session.role, method, path
+            ));
+        }
+
+        if let Some(scope) = &session.scope {
+            if !policy.api_keys {
+                return Decision::Deny(format!("API keys are not allowed to {} {}", method, path));
+            }
+
+            // Records addressed by their own id are confined by the upstream,
+            // from the restaurant in the signed identity
+            let other_restaurant = path_restaurant(path)
+                .is_some_and(|value| !value.eq_ignore_ascii_case(&scope.restaurant_id.to_string()));
+
+            if other_restaurant {
+                return Decision::Deny("API key is not bound to this restaurant".to_string());
+            }
+        }
+
+        if let Some(OwnershipRule::PathMatchesUser { param }) = &policy.ownership {
+            let owns = params
+                .get(param.as_str())
+                .map(|value| value.eq_ignore_ascii_case(&session.user_id.to_string()))
+                .unwrap_or(false);
+
+            if !owns {
+                return Decision::Deny(format!(
+                    "Path parameter '{}' must match the authenticated user",
+                    param
+                ));
+            }
+        }
+
+        Decision::Allow
+    }
+}
+
+/// Restaurant a path addresses: the segment following `restaurants`
+pub fn path_restaurant(path: &str) -> Option<&str> {
+    let mut segments = path.split('/');
+    segments.find(|segment| *segment == "restaurants")?;
+    segments.next().filter(|segment| !segment.is_empty())
+}
+
+/// Match a path against a pattern, returning captured `{name}` parameters on success
+pub fn match_path<'a>(pattern: &'a str, path: &'a str) -> Option<HashMap<&'a str, &'a str>> {
+    let mut params = HashMap::new();
+    let mut pattern_segments = pattern.trim_matches('/').split('/');
+    let mut path_segments = path.trim_matches('/').split('/');
+
+    loop {
+        match (pattern_segments.next(), path_segments.next()) {
+            (Some("**"), _) => return Some(params),
+            (Some(expected), Some(actual)) => {
+                if expected == "*" {
+                    continue;
+                }
+                if let Some(name) = expected.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                    params.insert(name, actual);
+                } else if expected != actual {
+                    return None;
+                }
+            }
+            (None, None) => return Some(params),
+            _ => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use models::user::SessionScope;
+    use uuid::Uuid;
+
+    fn session(role: UserRole) -> Session {
+        Session {
+            user_id: Uuid::new_v4(),
+            email: "test@example.com".to_string(),
+            role,
+            created_at: Utc::now(),
+            scope: None,
+        }
+    }
+
+    #[test]
+    fn test_match_path() {
+        assert!(match_path("/api/orders", "/api/orders").is_some());
+        assert!(match_path("/api/orders", "/api/orders/1").is_none());
+        assert!(match_path("/api/analytics/**", "/api/analytics/top-foods").is_some());
+        assert!(match_path("/api/analytics/**", "/api/analytics").is_some());
+        assert!(match_path("/api/*/health", "/api/orders/health").is_some());
+
+        let params = match_path("/api/billing/users/{user_id}", "/api/billing/users/abc").unwrap();
+        assert_eq!(params.get("user_id"), Some(&"abc"));
+    }
+
+    #[test]
+    fn test_default_policies_load() {
+        assert!(PolicyTable::load(None).is_ok());
+    }
+
+    #[test]
+    fn test_role_denied() {
+        let table = PolicyTable::load(None).unwrap();
+        let decision = table.evaluate(
+            &Method::PATCH,
+            "/api/kitchen/tickets/1/status",
+            &session(UserRole::User),
+        );
+        assert!(matches!(decision, Decision::Deny(_)));
+
+        let decision = table.evaluate(
+            &Method::PATCH,
+            "/api/kitchen/tickets/1/status",
+            &session(UserRole::Kitch),
+        );
+        assert_eq!(decision, Decision::Allow);
+
+        // The audit log is only reachable through the admin endpoint
+        let rest = session(UserRole::Rest);
+        assert!(matches!(table.evaluate(&Method::GET, "/api/analytics/audit", &rest), Decision::Deny(_)));
+        assert_eq!(table.evaluate(&Method::GET, "/api/analytics/revenue", &rest), Decision::Allow);
+    }
+
+    #[test]
+    fn test_ownership_rule() {
+        let table = PolicyTable::load(None).unwrap();
+        let user = session(UserRole::User);
+
+        let own = format!("/api/billing/users/{}", user.user_id);
+        assert_eq!(table.evaluate(&Method::GET, &own, &user), Decision::Allow);
+
+        let other = format!("/api/billing/users/{}", Uuid::new_v4());
+        assert!(matches!(table.evaluate(&Method::GET, &other, &user), Decision::Deny(_)));
+    }
+
+    #[test]
+    fn test_api_key_scope() {
+        let table = PolicyTable::load(None).unwrap();
+        let restaurant_id = Uuid::new_v4();
+        let mut key_session = session(UserRole::Rest);
+        key_session.scope = Some(SessionScope {
+            api_key_id: Uuid::new_v4(),
+            restaurant_id,
+        });
+
+        let own_menu = format!("/api/restaurants/{}/foods", restaurant_id);
+        assert_eq!(table.evaluate(&Method::POST, &own_menu, &key_session), Decision::Allow);
+
+        let other_menu = format!("/api/restaurants/{}/foods", Uuid::new_v4());
+        assert!(matches!(table.evaluate(&Method::POST, &other_menu, &key_session), Decision::Deny(_)));
+
+        // Creating restaurants needs the owner's session
+        assert!(matches!(
+            table.evaluate(&Method::POST, "/api/restaurants", &key_session),
+            Decision::Deny(_)
+        ));
+        assert_eq!(
+            table.evaluate(&Method::POST, "/api/restaurants", &session(UserRole::Rest)),
+            Decision::Allow
+        );
+
+        // Keys cannot manage keys
+        assert!(matches!(
+            table.evaluate(&Method::POST, "/api/auth/api-keys", &key_session),
+            Decision::Deny(_)
+        ));
+        assert_eq!(
+            table.evaluate(&Method::POST, "/api/auth/api-keys", &session(UserRole::Rest)),
+            Decision::Allow
+        );
+    }
+
+    #[test]
+    fn test_api_key_reaches_orders_and_tickets() {
+        let table = PolicyTable::load(None).unwrap();
+        let mut key_session = session(UserRole::Kitch);
+        key_session.scope = Some(SessionScope {
+            api_key_id: Uuid::new_v4(),
+            restaurant_id: Uuid::new_v4(),
+        });
+
+        // Addressed by their own ids; the upstream confines them to the
+        // key's restaurant
+        let order_id = Uuid::new_v4();
+        let ticket_id = Uuid::new_v4();
+        let requests = [
+            (Method::PATCH, format!("/api/orders/{}/status", order_id)),
+            (Method::GET, format!("/api/kitchen/orders/{}/ticket", order_id)),
+            (Method::GET, "/api/kitchen/tickets".to_string()),
+            (Method::GET, format!("/api/kitchen/tickets/{}", ticket_id)),
+            (Method::PATCH, format!("/api/kitchen/tickets/{}/status", ticket_id)),
+        ];
+        for (method, path) in &requests {
+            assert_eq!(table.evaluate(method, path, &key_session), Decision::Allow, "{} {}", method, path);
+        }
+
+        // Still bound by the key's role
+        let bill = format!("/api/billing/orders/{}", order_id);
+        assert!(matches!(table.evaluate(&Method::GET, &bill, &key_session), Decision::Deny(_)));
+
+        let mut pos_key = key_session.clone();
+        pos_key.role = UserRole::Rest;
+        let finalize = format!("/api/billing/orders/{}/finalize", order_id);
+        assert_eq!(table.evaluate(&Method::POST, &finalize, &pos_key), Decision::Allow);
+
+        // Routes not marked for keys stay closed
+        assert_eq!(table.evaluate(&Method::GET, "/api/analytics/revenue", &session(UserRole::Rest)), Decision::Allow);
+        assert!(matches!(
+            table.evaluate(&Method::GET, "/api/analytics/revenue", &pos_key),
+            Decision::Deny(_)
+        ));
+    }
+}
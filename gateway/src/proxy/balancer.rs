@@ -0,0 +1,186 @@
+use serde::Serialize;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::routes::BalanceStrategy;
+
+/// One instance of an upstream service
+#[derive(Debug)]
+pub struct Endpoint {
+    pub url: String,
+    outstanding: AtomicUsize,
+    consecutive_failures: AtomicU32,
+    ejected: AtomicBool,
+}
+
+impl Endpoint {
+    fn new(url: String) -> Self {
+        Self {
+            url,
+            outstanding: AtomicUsize::new(0),
+            consecutive_failures: AtomicU32::new(0),
+            ejected: AtomicBool::new(false),
+        }
+    }
+
+    pub fn is_ejected(&self) -> bool {
+        self.ejected.load(Ordering::Acquire)
+    }
+}
+
+/// Endpoint state as reported on the admin endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointSnapshot {
+    pub url: String,
+    pub ejected: bool,
+    pub outstanding: usize,
+    pub consecutive_failures: u32,
+}
+
+/// An endpoint picked for a request, counted as outstanding until dropped
+pub struct Lease {
+    endpoint: Arc<Endpoint>,
+}
+
+impl Deref for Lease {
+    type Target = Endpoint;
+
+    fn deref(&self) -> &Endpoint {
+        &self.endpoint
+    }
+}
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        self.endpoint.outstanding.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Spreads requests over an upstream's endpoints.
+///
+/// Endpoints are ejected after `eject_after` consecutive failures (passive
+/// health checking) and re-admitted once an active health probe succeeds.
+/// If every endpoint is ejected, all of them are used rather than none.
+#[derive(Debug)]
+pub struct Balancer {
+    endpoints: Vec<Arc<Endpoint>>,
+    strategy: BalanceStrategy,
+    next: AtomicUsize,
+    eject_after: u32,
+}
+
+impl Balancer {
+    pub fn new(urls: &[String], strategy: BalanceStrategy, eject_after: u32) -> Self {
+        Self {
+            endpoints: urls.iter().cloned().map(|url| Arc::new(Endpoint::new(url))).collect(),
+            strategy,
+            next: AtomicUsize::new(0),
+            eject_after: eject_after.max(1),
+        }
+    }
+
+    /// Pick an endpoint for the next request
+    pub fn pick(&self) -> Lease {
+        let admitted: Vec<&Arc<Endpoint>> =
+            self.endpoints.iter().filter(|endpoint| !endpoint.is_ejected()).collect();
+        let candidates = if admitted.is_empty() {
+            self.endpoints.iter().collect()
+        } else {
+            admitted
+        };
+
+        // Rotate the starting point so ties are also spread round-robin
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % candidates.len();
+        let rotated = candidates[start..].iter().chain(&candidates[..start]);
+
+        let endpoint = match self.strategy {
+            BalanceStrategy::RoundRobin => candidates[start],
+            BalanceStrategy::LeastOutstanding => rotated
+                .min_by_key(|endpoint| endpoint.outstanding.load(Ordering::Acquire))
+                .expect("upstreams have at least one endpoint"),
+        };
+
+        endpoint.outstanding.fetch_add(1, Ordering::AcqRel);
+        Lease {
+            endpoint: endpoint.clone(),
+        }
+    }
+
+    pub fn record_success(&self, endpoint: &Endpoint) {
+        endpoint.consecutive_failures.store(0, Ordering::Release);
+    }
+
+    /// Count a failure, returning true if it ejected the endpoint
+    pub fn record_failure(&self, endpoint: &Endpoint) -> bool {
+        let failures = endpoint.consecutive_failures.fetch_add(1, Ordering::AcqRel) + 1;
+        failures >= self.eject_after && !endpoint.ejected.swap(true, Ordering::AcqRel)
+    }
+
+    /// Return an endpoint to rotation after a successful health probe,
+    /// returning true if it had been ejected
+    pub fn readmit(&self, endpoint: &Endpoint) -> bool {
+        endpoint.consecutive_failures.store(0, Ordering::Release);
+        endpoint.ejected.swap(false, Ordering::AcqRel)
+    }
+
+    pub fn endpoints(&self) -> &[Arc<Endpoint>] {
+        &self.endpoints
+    }
+
+    pub fn snapshot(&self) -> Vec<EndpointSnapshot> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| EndpointSnapshot {
+                url: endpoint.url.clone(),
+                ejected: endpoint.is_ejected(),
+                outstanding: endpoint.outstanding.load(Ordering::Acquire),
+                consecutive_failures: endpoint.consecutive_failures.load(Ordering::Acquire),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls() -> Vec<String> {
+        vec!["http://a".to_string(), "http://b".to_string()]
+    }
+
+    #[test]
+    fn test_ejection_and_readmission() {
+        let balancer = Balancer::new(&urls(), BalanceStrategy::RoundRobin, 2);
+
+        let first = balancer.pick();
+        let second = balancer.pick();
+        assert_ne!(first.url, second.url);
+
+        let failing = balancer.endpoints()[0].clone();
+        assert!(!balancer.record_failure(&failing));
+        assert!(balancer.record_failure(&failing));
+        assert!(!balancer.record_failure(&failing), "ejection is reported once");
+
+        for _ in 0..4 {
+            assert_eq!(balancer.pick().url, "http://b");
+        }
+
+        assert!(balancer.readmit(&failing));
+        let picked: Vec<String> = (0..2).map(|_| balancer.pick().url.clone()).collect();
+        assert!(picked.contains(&"http://a".to_string()));
+    }
+
+    #[test]
+    fn test_least_outstanding() {
+        let balancer = Balancer::new(&urls(), BalanceStrategy::LeastOutstanding, 3);
+
+        let held = balancer.pick();
+        for _ in 0..3 {
+            assert_ne!(balancer.pick().url, held.url);
+        }
+
+        drop(held);
+        assert_eq!(balancer.snapshot().iter().map(|e| e.outstanding).sum::<usize>(), 0);
+    }
+}
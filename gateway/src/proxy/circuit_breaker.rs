@@ -0,0 +1,209 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Breaker state as reported on the admin endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+    /// Incremented for every trial admitted, so a permit only ends its own
+    trial_id: u64,
+}
+
+/// Snapshot of a breaker for reporting
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerSnapshot {
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+    pub retry_in_seconds: Option<u64>,
+}
+
+/// Per-upstream circuit breaker.
+///
+/// Opens after `failure_threshold` consecutive failures and rejects calls
+/// for `open_duration`. After that a single trial call is let through
+/// (half-open); its outcome closes or re-opens the breaker.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_duration: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            open_duration,
+            inner: Mutex::new(Inner {
+                state: BreakerState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                trial_in_flight: false,
+                trial_id: 0,
+            }),
+        }
+    }
+
+    /// Returns a permit if a call may be attempted now. Hold it until the
+    /// call's outcome is recorded: dropping the permit of a half-open trial
+    /// that never recorded one (the request was cancelled, the client was at
+    /// fault) lets the next call be the trial instead.
+    pub fn try_acquire(&self) -> Option<BreakerPermit<'_>> {
+        let mut inner = self.inner.lock().unwrap();
+
+        let admit_trial = match inner.state {
+            BreakerState::Closed => return Some(BreakerPermit { breaker: self, trial: None }),
+            BreakerState::Open => {
+                let elapsed = inner.opened_at.map(|t| t.elapsed()).unwrap_or_default();
+                elapsed >= self.open_duration
+            }
+            BreakerState::HalfOpen => !inner.trial_in_flight,
+        };
+
+        if !admit_trial {
+            return None;
+        }
+
+        inner.state = BreakerState::HalfOpen;
+        inner.trial_in_flight = true;
+        inner.trial_id = inner.trial_id.wrapping_add(1);
+
+        Some(BreakerPermit {
+            breaker: self,
+            trial: Some(inner.trial_id),
+        })
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.state = BreakerState::Closed;
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+        inner.trial_in_flight = false;
+    }
+
+    /// Record a failure; returns true if this failure opened the breaker
+    pub fn record_failure(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        inner.trial_in_flight = false;
+
+        let should_open = inner.state == BreakerState::HalfOpen
+            || (inner.state == BreakerState::Closed
+                && inner.consecutive_failures >= self.failure_threshold);
+
+        if should_open {
+            inner.state = BreakerState::Open;
+            inner.opened_at = Some(Instant::now());
+        }
+
+        should_open
+    }
+
+    pub fn snapshot(&self) -> BreakerSnapshot {
+        let inner = self.inner.lock().unwrap();
+        let retry_in_seconds = match (inner.state, inner.opened_at) {
+            (BreakerState::Open, Some(opened_at)) => Some(
+                self.open_duration
+                    .saturating_sub(opened_at.elapsed())
+                    .as_secs(),
+            ),
+            _ => None,
+        };
+
+        BreakerSnapshot {
+            state: inner.state,
+            consecutive_failures: inner.consecutive_failures,
+            retry_in_seconds,
+        }
+    }
+}
+
+/// Admission of one call through a breaker; see `CircuitBreaker::try_acquire`
+#[derive(Debug)]
+pub struct BreakerPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    /// Id of the half-open trial this permit admitted, if any
+    trial: Option<u64>,
+}
+
+impl Drop for BreakerPermit<'_> {
+    fn drop(&mut self) {
+        let Some(trial_id) = self.trial else {
+            return;
+        };
+
+        let mut inner = self.breaker.inner.lock().unwrap();
+        if inner.state == BreakerState::HalfOpen && inner.trial_in_flight && inner.trial_id == trial_id {
+            inner.trial_in_flight = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold_and_recovers() {
+        let breaker = CircuitBreaker::new(2, Duration::ZERO);
+
+        assert!(breaker.try_acquire().is_some());
+        assert!(!breaker.record_failure());
+        assert!(breaker.record_failure());
+        assert_eq!(breaker.snapshot().state, BreakerState::Open);
+
+        // Open duration elapsed: one trial call only
+        let trial = breaker.try_acquire();
+        assert!(trial.is_some());
+        assert_eq!(breaker.snapshot().state, BreakerState::HalfOpen);
+        assert!(breaker.try_acquire().is_none());
+
+        breaker.record_success();
+        drop(trial);
+        assert_eq!(breaker.snapshot().state, BreakerState::Closed);
+    }
+
+    #[test]
+    fn test_rejects_while_open() {
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(60));
+        breaker.record_failure();
+        assert!(breaker.try_acquire().is_none());
+    }
+
+    #[test]
+    fn test_dropped_trial_admits_another() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+        breaker.record_failure();
+
+        // The trial request is cancelled before an outcome is recorded
+        let trial = breaker.try_acquire();
+        assert!(trial.is_some());
+        assert!(breaker.try_acquire().is_none());
+        drop(trial);
+
+        assert_eq!(breaker.snapshot().state, BreakerState::HalfOpen);
+        let next = breaker.try_acquire();
+        assert!(next.is_some());
+
+        // A stale permit does not end the trial that replaced it
+        let stale = BreakerPermit {
+            breaker: &breaker,
+            trial: Some(0),
+        };
+        drop(stale);
+        assert!(breaker.try_acquire().is_none());
+    }
+}
@@ -1,103 +1,423 @@
-use actix_web::{web, HttpRequest, HttpResponse};
-use reqwest::Client;
-use std::collections::HashMap;
+mod balancer;
+pub mod circuit_breaker;
+mod websocket;
 
-pub struct ProxyService {
+use actix_web::{
+    http::{header, Method},
+    web, HttpMessage, HttpRequest, HttpResponse,
+};
+use error_handling::AppError;
+use identity::{Identity, IdentitySigner};
+use futures_util::{StreamExt, TryStreamExt};
+use ipnet::IpNet;
+use models::user::Session;
+use rand::Rng;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use std::collections::{BTreeMap, HashMap};
+use serde::Serialize;
+use std::future::Future;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use crate::config::UpstreamConfig;
+use crate::routes::{BalanceStrategy, RouteTable, SharedRoutes, UpstreamDef};
+use crate::sessions::{Credential, SessionCache};
+use balancer::{Balancer, Endpoint, EndpointSnapshot, Lease};
+use circuit_breaker::{BreakerSnapshot, BreakerState, CircuitBreaker};
+
+/// Chunks buffered between the client and upstream connections
+const BODY_CHANNEL_CAPACITY: usize = 8;
+
+/// Per-service HTTP client, circuit breaker and endpoint balancer
+struct Upstream {
+    def: UpstreamDef,
     client: Client,
-    service_urls: HashMap<String, String>,
+    request_timeout: Duration,
+    breaker: CircuitBreaker,
+    balancer: Balancer,
+}
+
+/// Upstream and target path resolved from the route table
+struct Target {
+    service_name: String,
+    upstream: Arc<Upstream>,
+    /// Prefix of the matched route, used as the metrics label
+    route: String,
+    path: String,
+    timeout: Duration,
+    max_body_bytes: u64,
+    long_lived: bool,
+    /// Issued by the gateway for an aggregated view rather than proxied:
+    /// only the caller's identity and request id are passed on
+    subrequest: bool,
+    started: Instant,
+}
+
+/// Failure to get a response from an upstream
+#[derive(Debug)]
+enum UpstreamError {
+    /// No response headers within the route timeout
+    Timeout,
+    Request(reqwest::Error),
+}
+
+impl UpstreamError {
+    fn is_timeout(&self) -> bool {
+        match self {
+            UpstreamError::Timeout => true,
+            UpstreamError::Request(e) => e.is_timeout(),
+        }
+    }
+
+    fn is_connect(&self) -> bool {
+        matches!(self, UpstreamError::Request(e) if e.is_connect())
+    }
+}
+
+impl std::fmt::Display for UpstreamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpstreamError::Timeout => write!(f, "no response headers within the timeout"),
+            UpstreamError::Request(e) => e.fmt(f),
+        }
+    }
+}
+
+/// Why an inbound body stream stopped early, if it did
+#[derive(Default)]
+struct BodyState {
+    too_large: AtomicBool,
+    client_error: AtomicBool,
+}
+
+pub struct ProxyService {
+    routes: SharedRoutes,
+    upstreams: RwLock<HashMap<String, Arc<Upstream>>>,
+    identity_signer: IdentitySigner,
+    upstream_config: UpstreamConfig,
+    /// Watched by long-lived connections for session revocation
+    sessions: Arc<SessionCache>,
+    session_check: Duration,
+    /// Proxies in front whose `X-Forwarded-For` gives the client address
+    trusted_proxies: Vec<IpNet>,
 }
 
 impl ProxyService {
     pub fn new(
-        auth_url: String,
-        restaurant_url: String,
-        order_url: String,
-        kitchen_url: String,
-        billing_url: String,
-        analytics_url: String,
+        routes: SharedRoutes,
+        upstream_config: &UpstreamConfig,
+        identity_signer: IdentitySigner,
+        sessions: Arc<SessionCache>,
+        session_check: Duration,
+        trusted_proxies: Vec<IpNet>,
     ) -> Self {
-        let mut service_urls = HashMap::new();
-        service_urls.insert("auth".to_string(), auth_url);
-        service_urls.insert("restaurant".to_string(), restaurant_url);
-        service_urls.insert("order".to_string(), order_url);
-        service_urls.insert("kitchen".to_string(), kitchen_url);
-        service_urls.insert("billing".to_string(), billing_url);
-        service_urls.insert("analytics".to_string(), analytics_url);
+        let upstreams = routes
+            .current()
+            .upstreams
+            .iter()
+            .map(|(name, def)| (name.clone(), Arc::new(build_upstream(def, upstream_config))))
+            .collect();
 
         Self {
-            client: Client::new(),
-            service_urls,
-        }
-    }
-
-    fn get_service_url(&self, path: &str) -> Option<(&str, String)> {
-        if path.starts_with("/api/auth") {
-            Some(("auth", self.service_urls.get("auth")?.clone()))
-        } else if path.starts_with("/api/restaurants") || path.starts_with("/api/categories") || path.starts_with("/api/foods") {
-            Some(("restaurant", self.service_urls.get("restaurant")?.clone()))
-        } else if path.starts_with("/api/orders") {
-            Some(("order", self.service_urls.get("order")?.clone()))
-        } else if path.starts_with("/api/kitchen") {
-            Some(("kitchen", self.service_urls.get("kitchen")?.clone()))
-        } else if path.starts_with("/api/billing") {
-            Some(("billing", self.service_urls.get("billing")?.clone()))
-        } else if path.starts_with("/api/analytics") {
-            Some(("analytics", self.service_urls.get("analytics")?.clone()))
+            routes,
+            upstreams: RwLock::new(upstreams),
+            identity_signer,
+            upstream_config: upstream_config.clone(),
+            sessions,
+            session_check,
+            trusted_proxies,
+        }
+    }
+
+    /// Swap in a new route table. Upstreams whose definition is unchanged
+    /// keep their client, circuit breaker and endpoint state.
+    pub fn reload(&self, table: RouteTable) {
+        {
+            let mut upstreams = self.upstreams.write().unwrap();
+            let reloaded = table
+                .upstreams
+                .iter()
+                .map(|(name, def)| {
+                    let upstream = match upstreams.get(name) {
+                        Some(existing) if existing.def == *def => existing.clone(),
+                        _ => Arc::new(build_upstream(def, &self.upstream_config)),
+                    };
+                    (name.clone(), upstream)
+                })
+                .collect();
+            *upstreams = reloaded;
+        }
+
+        self.routes.replace(table);
+    }
+
+    /// Current circuit breaker and endpoint state for every upstream service
+    pub fn upstream_snapshots(&self) -> BTreeMap<String, UpstreamSnapshot> {
+        self.upstreams
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, upstream)| {
+                let snapshot = UpstreamSnapshot {
+                    circuit: upstream.breaker.snapshot(),
+                    balance: upstream.def.balance,
+                    endpoints: upstream.balancer.snapshot(),
+                };
+                (name.clone(), snapshot)
+            })
+            .collect()
+    }
+
+    fn upstream_list(&self) -> Vec<(String, Arc<Upstream>)> {
+        self.upstreams
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, upstream)| (name.clone(), upstream.clone()))
+            .collect()
+    }
+
+    fn resolve(&self, path: &str) -> Option<Target> {
+        let routes = self.routes.current();
+        let route = routes.find(path)?;
+        let upstream = self.upstreams.read().unwrap().get(&route.upstream)?.clone();
+
+        let timeout = route
+            .timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(upstream.request_timeout);
+
+        Some(Target {
+            service_name: route.upstream.clone(),
+            route: route.prefix.clone(),
+            path: route.upstream_path(path),
+            upstream,
+            timeout,
+            max_body_bytes: route.max_body_bytes.unwrap_or(routes.max_body_bytes),
+            long_lived: route.long_lived,
+            subrequest: false,
+            started: Instant::now(),
+        })
+    }
+
+    /// Build one attempt of the upstream request against an endpoint
+    fn build_backend_request(
+        &self,
+        target: &Target,
+        req: &HttpRequest,
+        endpoint: &Endpoint,
+        body: Option<reqwest::Body>,
+    ) -> RequestBuilder {
+        let target_url = match req.query_string() {
+            query if query.is_empty() || target.subrequest => format!("{}{}", endpoint.url, target.path),
+            query => format!("{}{}?{}", endpoint.url, target.path, query),
+        };
+
+        tracing::debug!("Proxying {} {} to {}", req.method(), req.path(), target_url);
+
+        let mut backend_req = target
+            .upstream
+            .client
+            .request(req.method().clone(), &target_url);
+
+        if target.subrequest {
+            backend_req = backend_req.header(header::ACCEPT, "application/json");
+            if let Some(request_id) = req.headers().get(correlation::REQUEST_ID_HEADER) {
+                backend_req = backend_req.header(correlation::REQUEST_ID_HEADER, request_id);
+            }
+        } else {
+            // Forward headers (except Host, Connection and client-supplied identity headers)
+            for (key, value) in req.headers().iter() {
+                let key_str = key.as_str();
+                if key_str != "host"
+                    && key_str != "connection"
+                    && key_str != "x-forwarded-for"
+                    && !identity::is_identity_header(key_str)
+                {
+                    backend_req = backend_req.header(key, value);
+                }
+            }
+
+            // Replaced rather than appended, so services see the one address
+            // the gateway vouches for
+            if let Some(ip) = client_ip(req, &self.trusted_proxies) {
+                backend_req = backend_req.header("X-Forwarded-For", ip);
+            }
+        }
+
+        // Attach signed identity headers for authenticated requests
+        if let Some(identity) = req.extensions().get::<Identity>() {
+            for (key, value) in self.identity_signer.sign(identity) {
+                backend_req = backend_req.header(key, value);
+            }
+        }
+
+        // Add body if present
+        if let Some(body) = body {
+            backend_req = backend_req.body(body);
+        }
+
+        backend_req
+    }
+
+    /// Send one attempt. The route timeout only covers the wait for response
+    /// headers, also on long-lived routes; a streamed body is bounded by
+    /// `forward_response` instead.
+    async fn send(&self, target: &Target, request: RequestBuilder) -> Result<reqwest::Response, UpstreamError> {
+        match tokio::time::timeout(target.timeout, request.send()).await {
+            Ok(result) => result.map_err(UpstreamError::Request),
+            Err(_) => Err(UpstreamError::Timeout),
+        }
+    }
+
+    fn record_failure(&self, service_name: &str, upstream: &Upstream) {
+        if upstream.breaker.record_failure() {
+            tracing::error!("Circuit opened for {} service", service_name);
+        }
+    }
+
+    /// Passive health check: count one attempt against its endpoint
+    fn record_endpoint(&self, target: &Target, endpoint: &Endpoint, failed: bool) {
+        let balancer = &target.upstream.balancer;
+
+        if !failed {
+            balancer.record_success(endpoint);
+        } else if balancer.record_failure(endpoint) {
+            tracing::error!(
+                "Ejected {} endpoint {} after consecutive failures",
+                target.service_name,
+                endpoint.url
+            );
+        }
+    }
+
+    fn record_outcome(&self, target: &Target, endpoint: &Endpoint, status: StatusCode) {
+        record_upstream_metrics(target, status.as_str());
+
+        let failed = is_upstream_failure(status);
+        self.record_endpoint(target, endpoint, failed);
+        if failed {
+            self.record_failure(&target.service_name, &target.upstream);
         } else {
-            None
+            target.upstream.breaker.record_success();
         }
     }
 
+    /// Record a transport failure and map it to a gateway error
+    fn upstream_error(&self, target: &Target, endpoint: &Endpoint, e: UpstreamError) -> actix_web::Error {
+        let service_name = target.service_name.as_str();
+        tracing::error!("Failed to proxy request to {} at {}: {}", service_name, endpoint.url, e);
+        record_upstream_metrics(target, if e.is_timeout() { "timeout" } else { "error" });
+        self.record_endpoint(target, endpoint, true);
+        self.record_failure(service_name, &target.upstream);
+
+        if e.is_timeout() {
+            actix_web::error::ErrorGatewayTimeout(format!("{} service timed out", service_name))
+        } else {
+            actix_web::error::ErrorBadGateway(format!("Failed to reach {} service", service_name))
+        }
+    }
+
+    /// Exponential backoff with full jitter
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self.upstream_config.retry_backoff_ms * 2u64.pow(attempt.min(10) - 1);
+        Duration::from_millis(rand::thread_rng().gen_range(0..=ceiling))
+    }
+
     pub async fn proxy_request(
         &self,
         req: HttpRequest,
-        body: web::Bytes,
+        payload: web::Payload,
     ) -> Result<HttpResponse, actix_web::Error> {
         let path = req.path();
-        let query = req.query_string();
 
         // Determine target service
-        let (service_name, base_url) = self
-            .get_service_url(path)
+        let target = self
+            .resolve(path)
             .ok_or_else(|| actix_web::error::ErrorNotFound("Service not found"))?;
+        let service_name = target.service_name.as_str();
+        let upstream = target.upstream.as_ref();
 
-        // Build target URL
-        let target_url = if query.is_empty() {
-            format!("{}{}", base_url, path)
-        } else {
-            format!("{}{}?{}", base_url, path, query)
-        };
-
-        tracing::debug!("Proxying {} {} to {}", req.method(), path, target_url);
+        // Reject declared oversized bodies before touching the upstream
+        let content_length = req
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
 
-        // Build request to backend service
-        let mut backend_req = self.client.request(req.method().clone(), &target_url);
+        if content_length.is_some_and(|len| len > target.max_body_bytes) {
+            return Err(payload_too_large(target.max_body_bytes));
+        }
 
-        // Forward headers (except Host and Connection)
-        for (key, value) in req.headers().iter() {
-            let key_str = key.as_str();
-            if key_str != "host" && key_str != "connection" {
-                backend_req = backend_req.header(key, value);
-            }
+        if websocket::is_websocket_upgrade(&req) && !target.long_lived {
+            return Err(actix_web::error::ErrorBadRequest(
+                "WebSocket upgrades are not enabled for this route",
+            ));
         }
 
-        // Add body if present
-        if !body.is_empty() {
-            backend_req = backend_req.body(body.to_vec());
+        let has_body = match content_length {
+            Some(len) => len > 0,
+            None => req.headers().contains_key(header::TRANSFER_ENCODING),
+        };
+
+        // Fail fast while the upstream's breaker is open. The permit lives
+        // until the outcome is recorded, or the request is abandoned.
+        let Some(_permit) = upstream.breaker.try_acquire() else {
+            tracing::warn!("Circuit open for {}, rejecting {} {}", service_name, req.method(), path);
+            record_upstream_metrics(&target, "circuit_open");
+            return Err(AppError::ServiceUnavailable(format!(
+                "{} service is temporarily unavailable",
+                service_name
+            ))
+            .into());
+        };
+
+        if websocket::is_websocket_upgrade(&req) {
+            return self.proxy_websocket(&target, &req, payload).await;
         }
 
-        // Send request to backend
-        let backend_resp = backend_req
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to proxy request to {}: {}", service_name, e);
-                actix_web::error::ErrorBadGateway(format!("Failed to reach {} service", service_name))
-            })?;
+        let (backend_resp, lease) = if has_body {
+            // A streamed body can only be sent once, so there are no retries
+            let body_state = Arc::new(BodyState::default());
+            let body = stream_payload(payload, target.max_body_bytes, body_state.clone());
+            let lease = upstream.balancer.pick();
 
-        // Build response
-        let status = backend_resp.status();
-        let mut client_resp = HttpResponse::build(status);
+            let request = self.build_backend_request(&target, &req, &lease, Some(body));
+
+            match self.send(&target, request).await {
+                Ok(resp) => {
+                    self.record_outcome(&target, &lease, resp.status());
+                    (resp, lease)
+                }
+                Err(_) if body_state.too_large.load(Ordering::Relaxed) => {
+                    return Err(payload_too_large(target.max_body_bytes));
+                }
+                Err(e) if body_state.client_error.load(Ordering::Relaxed) => {
+                    tracing::warn!("Client aborted request body to {}: {}", service_name, e);
+                    return Err(actix_web::error::ErrorBadRequest("Failed to read request body"));
+                }
+                Err(e) => return Err(self.upstream_error(&target, &lease, e)),
+            }
+        } else {
+            self.send_with_retries(&target, &req).await?
+        };
+
+        Ok(self.forward_response(&target, &req, backend_resp, lease))
+    }
+
+    /// Stream an upstream response back as the client reads it. The endpoint
+    /// lease is held until the body finishes, so streams count as outstanding.
+    fn forward_response(
+        &self,
+        target: &Target,
+        req: &HttpRequest,
+        backend_resp: reqwest::Response,
+        lease: Lease,
+    ) -> HttpResponse {
+        let mut client_resp = HttpResponse::build(backend_resp.status());
 
         // Forward response headers (except Connection and Transfer-Encoding)
         for (key, value) in backend_resp.headers().iter() {
@@ -107,36 +427,174 @@ impl ProxyService {
             }
         }
 
-        // Get response body
+        let event_stream = target.long_lived && is_event_stream(&backend_resp);
+        let service_name = target.service_name.clone();
         let body = backend_resp
-            .bytes()
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to read response body from {}: {}", service_name, e);
-                actix_web::error::ErrorBadGateway("Failed to read response from backend service")
-            })?;
+            .bytes_stream()
+            .map_err(io::Error::other);
 
-        Ok(client_resp.body(body))
+        // Server-Sent Events may go quiet for long, and end when the session
+        // is revoked; other bodies must keep flowing
+        let body = if event_stream {
+            body.take_until(self.session_revoked(target, req)).boxed()
+        } else {
+            idle_timeout(body, Duration::from_millis(self.upstream_config.body_idle_timeout_ms)).boxed()
+        };
+
+        let body = body.map_err(move |e| {
+            tracing::error!("Failed to read response body from {} at {}: {}", service_name, lease.url, e);
+            e
+        });
+
+        client_resp.streaming(body)
+    }
+
+    /// Resolves when the session behind a long-lived connection is revoked;
+    /// never resolves for other requests
+    fn session_revoked(&self, target: &Target, req: &HttpRequest) -> impl Future<Output = ()> + 'static {
+        let extensions = req.extensions();
+        let watch = extensions
+            .get::<Credential>()
+            .cloned()
+            .zip(extensions.get::<Session>().cloned())
+            .filter(|_| target.long_lived)
+            .map(|(credential, session)| {
+                self.sessions
+                    .clone()
+                    .wait_for_revocation(credential, session, self.session_check)
+            });
+        let service_name = target.service_name.clone();
+
+        async move {
+            match watch {
+                Some(watch) => {
+                    watch.await;
+                    tracing::info!("Closing long-lived {} connection: session revoked", service_name);
+                }
+                None => std::future::pending().await,
+            }
+        }
     }
 
-    pub async fn proxy_health_check(
+    /// Send a body-less request, retrying idempotent methods on upstream
+    /// failures. Each attempt picks an endpoint, so retries can move to
+    /// another instance.
+    async fn send_with_retries(
         &self,
-        service_name: &str,
-        health_path: &str,
-    ) -> Result<HttpResponse, actix_web::Error> {
-        let base_url = self
-            .service_urls
-            .get(service_name)
+        target: &Target,
+        req: &HttpRequest,
+    ) -> Result<(reqwest::Response, Lease), actix_web::Error> {
+        let service_name = target.service_name.as_str();
+
+        // Only idempotent requests are safe to replay
+        let max_retries = if is_idempotent(req.method()) {
+            self.upstream_config.max_retries
+        } else {
+            0
+        };
+        let mut attempt = 0;
+
+        loop {
+            let lease = target.upstream.balancer.pick();
+            let request = self.build_backend_request(target, req, &lease, None);
+            let result = self.send(target, request).await;
+
+            let retryable = match &result {
+                Ok(resp) => is_upstream_failure(resp.status()),
+                Err(e) => e.is_connect() || e.is_timeout(),
+            };
+
+            if retryable && attempt < max_retries {
+                self.record_endpoint(target, &lease, true);
+                drop(lease);
+                attempt += 1;
+                let delay = self.backoff(attempt);
+                tracing::warn!(
+                    "Retrying {} {} to {} (attempt {}/{}) in {:?}",
+                    req.method(),
+                    req.path(),
+                    service_name,
+                    attempt,
+                    max_retries,
+                    delay
+                );
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+
+            return match result {
+                Ok(resp) => {
+                    self.record_outcome(target, &lease, resp.status());
+                    Ok((resp, lease))
+                }
+                Err(e) => Err(self.upstream_error(target, &lease, e)),
+            };
+        }
+    }
+
+    /// GET `path` from the upstream that owns it on behalf of the caller, for
+    /// views that combine several services. Goes through the same breaker,
+    /// endpoint balancing and retries as proxied requests. `path` may carry
+    /// its own query string; the caller's is not forwarded.
+    pub async fn fetch(&self, req: &HttpRequest, path: &str) -> Result<(StatusCode, web::Bytes), actix_web::Error> {
+        let mut target = self
+            .resolve(path)
+            .ok_or_else(|| actix_web::error::ErrorNotFound("Service not found"))?;
+        target.subrequest = true;
+        let service_name = target.service_name.as_str();
+
+        let Some(_permit) = target.upstream.breaker.try_acquire() else {
+            record_upstream_metrics(&target, "circuit_open");
+            return Err(AppError::ServiceUnavailable(format!(
+                "{} service is temporarily unavailable",
+                service_name
+            ))
+            .into());
+        };
+
+        let (backend_resp, _lease) = self.send_with_retries(&target, req).await?;
+        let status = backend_resp.status();
+
+        // Views buffer small JSON bodies, so the route timeout bounds the read too
+        let body = match tokio::time::timeout(target.timeout, backend_resp.bytes()).await {
+            Ok(Ok(body)) => body,
+            Ok(Err(e)) => {
+                tracing::error!("Failed to read response body from {}: {}", service_name, e);
+                return Err(actix_web::error::ErrorBadGateway(format!(
+                    "Failed to read response from {} service",
+                    service_name
+                )));
+            }
+            Err(_) => {
+                tracing::error!("Timed out reading response body from {}", service_name);
+                return Err(actix_web::error::ErrorGatewayTimeout(format!(
+                    "{} service timed out",
+                    service_name
+                )));
+            }
+        };
+
+        Ok((status, body))
+    }
+
+    /// Proxy a `/api/<service>/health` request to the owning service
+    pub async fn proxy_health_check(&self, path: &str) -> Result<HttpResponse, actix_web::Error> {
+        let target = self
+            .resolve(path)
             .ok_or_else(|| actix_web::error::ErrorNotFound("Service not found"))?;
+        let service_name = target.service_name.as_str();
+        let upstream = target.upstream.as_ref();
 
-        let target_url = format!("{}{}", base_url, health_path);
+        let endpoint = upstream.balancer.pick();
+        let target_url = format!("{}{}", endpoint.url, upstream.def.health_path);
 
         tracing::debug!("Health check for {} at {}", service_name, target_url);
 
         // Send GET request to health endpoint
-        let backend_resp = self
+        let backend_resp = upstream
             .client
             .get(&target_url)
+            .timeout(upstream.request_timeout)
             .send()
             .await
             .map_err(|e| {
@@ -167,5 +625,524 @@ impl ProxyService {
 
         Ok(client_resp.body(body))
     }
+
+    /// Probe every upstream endpoint's health endpoint concurrently
+    pub async fn deep_health(&self) -> DeepHealth {
+        let probes = self.upstream_list().into_iter().map(|(name, upstream)| async move {
+            let health = self.probe_service(&name, &upstream).await;
+            (name, health)
+        });
+
+        let services: BTreeMap<String, ServiceHealth> =
+            futures_util::future::join_all(probes).await.into_iter().collect();
+
+        let up = services.values().filter(|s| s.status == ProbeStatus::Up).count();
+        let status = if up == services.len() {
+            HealthStatus::Healthy
+        } else if up == 0 {
+            HealthStatus::Down
+        } else {
+            HealthStatus::Degraded
+        };
+
+        DeepHealth {
+            status,
+            checked_at: chrono::Utc::now(),
+            services,
+        }
+    }
+
+    /// A service is up while any of its endpoints is
+    async fn probe_service(&self, service_name: &str, upstream: &Upstream) -> ServiceHealth {
+        let probes = upstream
+            .balancer
+            .endpoints()
+            .iter()
+            .map(|endpoint| self.probe(service_name, upstream, endpoint));
+        let endpoints: Vec<EndpointHealth> = futures_util::future::join_all(probes).await;
+
+        let status = if endpoints.iter().any(|endpoint| endpoint.status == ProbeStatus::Up) {
+            ProbeStatus::Up
+        } else {
+            ProbeStatus::Down
+        };
+
+        ServiceHealth {
+            status,
+            circuit: upstream.breaker.snapshot().state,
+            endpoints,
+        }
+    }
+
+    async fn probe(&self, service_name: &str, upstream: &Upstream, endpoint: &Endpoint) -> EndpointHealth {
+        let target_url = format!("{}{}", endpoint.url, upstream.def.health_path);
+        let started = Instant::now();
+
+        let result = upstream
+            .client
+            .get(&target_url)
+            .timeout(Duration::from_millis(self.upstream_config.health_probe_timeout_ms))
+            .send()
+            .await;
+
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let (status, http_status, error) = match result {
+            Ok(resp) if resp.status().is_success() => (ProbeStatus::Up, Some(resp.status().as_u16()), None),
+            Ok(resp) => (ProbeStatus::Down, Some(resp.status().as_u16()), None),
+            Err(e) => {
+                tracing::warn!("Health probe for {} at {} failed: {}", service_name, endpoint.url, e);
+                let error = if e.is_timeout() { "timed out" } else { "unreachable" };
+                (ProbeStatus::Down, None, Some(error.to_string()))
+            }
+        };
+
+        // Active health check: a passing probe returns an ejected endpoint to rotation
+        if status == ProbeStatus::Up && upstream.balancer.readmit(endpoint) {
+            tracing::info!("Re-admitted {} endpoint {}", service_name, endpoint.url);
+        }
+
+        EndpointHealth {
+            url: endpoint.url.clone(),
+            status,
+            http_status,
+            latency_ms,
+            error,
+            ejected: endpoint.is_ejected(),
+        }
+    }
+
+    /// Fetch each upstream's `/openapi.json` from one of its endpoints.
+    /// Services that fail to answer are logged and skipped.
+    pub async fn upstream_documents(&self) -> Vec<(String, serde_json::Value)> {
+        let fetches = self.upstream_list().into_iter().map(|(name, upstream)| async move {
+            let endpoint = upstream.balancer.pick();
+            let result = upstream
+                .client
+                .get(format!("{}/openapi.json", endpoint.url))
+                .timeout(Duration::from_millis(self.upstream_config.health_probe_timeout_ms))
+                .send()
+                .await
+                .and_then(|resp| resp.error_for_status());
+
+            let document = match result {
+                Ok(resp) => resp.json::<serde_json::Value>().await,
+                Err(e) => Err(e),
+            };
+
+            match document {
+                Ok(document) => Some((name, document)),
+                Err(e) => {
+                    tracing::warn!("Failed to fetch OpenAPI document of {} from {}: {}", name, endpoint.url, e);
+                    None
+                }
+            }
+        });
+
+        let mut documents: Vec<_> = futures_util::future::join_all(fetches)
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+        // Stable order, so the first definition of a shared schema is too
+        documents.sort_by(|a, b| a.0.cmp(&b.0));
+        documents
+    }
+
+    /// Route table currently in effect
+    pub fn route_table(&self) -> Arc<RouteTable> {
+        self.routes.current()
+    }
+
+    /// Probe ejected endpoints until they pass, re-admitting them
+    pub async fn probe_ejected_endpoints(&self) {
+        let mut ejected = Vec::new();
+        for (name, upstream) in self.upstream_list() {
+            for endpoint in upstream.balancer.endpoints().iter().filter(|e| e.is_ejected()) {
+                ejected.push((name.clone(), upstream.clone(), endpoint.clone()));
+            }
+        }
+
+        let probes = ejected
+            .iter()
+            .map(|(name, upstream, endpoint)| self.probe(name, upstream, endpoint));
+        futures_util::future::join_all(probes).await;
+    }
+}
+
+/// Periodically probe ejected upstream endpoints
+pub fn spawn_endpoint_probes(proxy_service: web::Data<ProxyService>, interval: Duration) {
+    actix_rt::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            proxy_service.probe_ejected_endpoints().await;
+        }
+    });
+}
+
+/// Address of the calling client: the peer, or when the peer is one of the
+/// trusted proxies, the last `X-Forwarded-For` entry, which that proxy added.
+/// Earlier entries come from the client and prove nothing, and so does the
+/// header from anyone who reaches the gateway directly.
+pub fn client_ip(req: &HttpRequest, trusted_proxies: &[IpNet]) -> Option<String> {
+    let peer = req.peer_addr()?.ip();
+
+    if !trusted_proxies.iter().any(|net| net.contains(&peer)) {
+        return Some(peer.to_string());
+    }
+
+    let forwarded = req
+        .headers()
+        .get("X-Forwarded-For")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .map(str::trim)
+        .and_then(|ip| ip.parse::<std::net::IpAddr>().ok());
+
+    Some(forwarded.unwrap_or(peer).to_string())
+}
+
+/// Circuit breaker and endpoint state reported on the admin endpoint
+#[derive(Debug, Serialize)]
+pub struct UpstreamSnapshot {
+    #[serde(flatten)]
+    pub circuit: BreakerSnapshot,
+    pub balance: BalanceStrategy,
+    pub endpoints: Vec<EndpointSnapshot>,
+}
+
+/// Overall verdict of a deep health check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Healthy,
+    Degraded,
+    Down,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeStatus {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EndpointHealth {
+    pub url: String,
+    pub status: ProbeStatus,
+    pub http_status: Option<u16>,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+    pub ejected: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServiceHealth {
+    pub status: ProbeStatus,
+    pub circuit: BreakerState,
+    pub endpoints: Vec<EndpointHealth>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeepHealth {
+    pub status: HealthStatus,
+    pub checked_at: chrono::DateTime<chrono::Utc>,
+    pub services: BTreeMap<String, ServiceHealth>,
+}
+
+/// Forward the client body through a bounded channel, so the client is only
+/// read as fast as the upstream accepts data, and cut it off past `limit` bytes
+fn stream_payload(mut payload: web::Payload, limit: u64, state: Arc<BodyState>) -> reqwest::Body {
+    let (tx, rx) = mpsc::channel::<Result<web::Bytes, io::Error>>(BODY_CHANNEL_CAPACITY);
+
+    actix_rt::spawn(async move {
+        let mut received: u64 = 0;
+
+        while let Some(chunk) = payload.next().await {
+            let item = match chunk {
+                Ok(chunk) => {
+                    received += chunk.len() as u64;
+                    if received > limit {
+                        state.too_large.store(true, Ordering::Relaxed);
+                        Err(io::Error::other("request body too large"))
+                    } else {
+                        Ok(chunk)
+                    }
+                }
+                Err(e) => {
+                    state.client_error.store(true, Ordering::Relaxed);
+                    Err(io::Error::other(e.to_string()))
+                }
+            };
+
+            let failed = item.is_err();
+            if tx.send(item).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    reqwest::Body::wrap_stream(futures_util::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|item| (item, rx))
+    }))
+}
+
+/// Whether an upstream response is a Server-Sent Events stream
+fn is_event_stream(resp: &reqwest::Response) -> bool {
+    resp.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|h| h.to_str().ok())
+        .is_some_and(|value| value.trim_start().to_ascii_lowercase().starts_with("text/event-stream"))
+}
+
+/// End a response body with an error once the upstream sends nothing for `idle`
+fn idle_timeout<S>(stream: S, idle: Duration) -> impl futures_util::Stream<Item = io::Result<web::Bytes>>
+where
+    S: futures_util::Stream<Item = io::Result<web::Bytes>> + Send + 'static,
+{
+    futures_util::stream::unfold(Some(stream.boxed()), move |stream| async move {
+        let mut stream = stream?;
+        match tokio::time::timeout(idle, stream.next()).await {
+            Ok(Some(chunk)) => Some((chunk, Some(stream))),
+            Ok(None) => None,
+            Err(_) => Some((
+                Err(io::Error::new(io::ErrorKind::TimedOut, "upstream response body stalled")),
+                None,
+            )),
+        }
+    })
+}
+
+fn payload_too_large(limit: u64) -> actix_web::Error {
+    actix_web::error::ErrorPayloadTooLarge(format!("Request body exceeds {} bytes", limit))
+}
+
+/// Count a proxied request by upstream, route and outcome
+fn record_upstream_metrics(target: &Target, outcome: &str) {
+    metrics::record_upstream(
+        &target.service_name,
+        &target.route,
+        outcome,
+        target.started.elapsed(),
+    );
+}
+
+fn build_upstream(def: &UpstreamDef, config: &UpstreamConfig) -> Upstream {
+    let client = Client::builder()
+        .connect_timeout(Duration::from_millis(
+            def.connect_timeout_ms.unwrap_or(config.connect_timeout_ms),
+        ))
+        .build()
+        .expect("Failed to build HTTP client");
+
+    Upstream {
+        def: def.clone(),
+        client,
+        request_timeout: Duration::from_millis(def.timeout_ms.unwrap_or(config.request_timeout_ms)),
+        breaker: CircuitBreaker::new(
+            config.breaker_failure_threshold,
+            Duration::from_secs(config.breaker_open_seconds),
+        ),
+        balancer: Balancer::new(&def.urls, def.balance, config.endpoint_eject_failures),
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+    )
+}
+
+/// Statuses that indicate the upstream itself (not the request) failed
+fn is_upstream_failure(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sessions::testing;
+    use actix_web::body::MessageBody;
+    use actix_web::FromRequest;
+    use models::user::{SessionInvalidation, UserRole};
+    use std::pin::Pin;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Upstream accepting one connection, which is handed to `serve` after
+    /// its request head is read
+    pub(super) async fn upstream<F, Fut>(serve: F) -> std::net::SocketAddr
+    where
+        F: FnOnce(TcpStream, String) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut head = Vec::new();
+            while !head.ends_with(b"\r\n\r\n") {
+                let mut byte = [0u8; 1];
+                if socket.read(&mut byte).await.unwrap_or(0) == 0 {
+                    return;
+                }
+                head.push(byte[0]);
+            }
+            serve(socket, String::from_utf8_lossy(&head).to_ascii_lowercase()).await;
+        });
+
+        addr
+    }
+
+    /// Proxy with a single `/live` route to `addr`
+    pub(super) async fn live_proxy(addr: std::net::SocketAddr) -> (ProxyService, Arc<SessionCache>) {
+        let table = RouteTable::from_toml(&format!(
+            r#"
+            [upstreams.live]
+            url = "http://{}"
+
+            [[routes]]
+            prefix = "/live"
+            upstream = "live"
+            long_lived = true
+            timeout_ms = 1000
+            "#,
+            addr
+        ))
+        .unwrap();
+
+        let config = UpstreamConfig {
+            connect_timeout_ms: 1000,
+            request_timeout_ms: 1000,
+            body_idle_timeout_ms: 1000,
+            max_retries: 0,
+            retry_backoff_ms: 10,
+            breaker_failure_threshold: 5,
+            breaker_open_seconds: 30,
+            health_probe_timeout_ms: 1000,
+            endpoint_eject_failures: 3,
+            endpoint_probe_interval_seconds: 10,
+        };
+        let sessions = testing::empty_cache().await;
+
+        let proxy = ProxyService::new(
+            SharedRoutes::new(table),
+            &config,
+            IdentitySigner::new("test-secret"),
+            sessions.clone(),
+            Duration::from_secs(3600),
+            Vec::new(),
+        );
+        (proxy, sessions)
+    }
+
+    #[actix_web::test]
+    async fn test_event_stream_closed_on_revocation() {
+        let addr = upstream(|mut socket, _| async move {
+            socket
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n\
+                      a\r\ndata: hi\n\n\r\n",
+                )
+                .await
+                .unwrap();
+            // Hold the stream open
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        })
+        .await;
+        let (proxy, sessions) = live_proxy(addr).await;
+
+        let session = Session {
+            user_id: uuid::Uuid::new_v4(),
+            email: "cook@example.com".to_string(),
+            role: UserRole::Kitch,
+            created_at: chrono::Utc::now(),
+            scope: None,
+        };
+        let (req, mut payload) = actix_web::test::TestRequest::get().uri("/live/events").to_http_parts();
+        req.extensions_mut().insert(Credential::Session("token".to_string()));
+        req.extensions_mut().insert(session.clone());
+        let payload = web::Payload::from_request(&req, &mut payload).await.unwrap();
+
+        let mut body = proxy.proxy_request(req, payload).await.unwrap().into_body();
+        let wait = Duration::from_secs(1);
+
+        let chunk = std::future::poll_fn(|cx| Pin::new(&mut body).poll_next(cx));
+        let chunk = tokio::time::timeout(wait, chunk).await.unwrap();
+        assert_eq!(chunk.unwrap().unwrap(), "data: hi\n\n");
+
+        testing::publish(&sessions, SessionInvalidation::Session { session_id: "token".to_string() });
+        let end = std::future::poll_fn(|cx| Pin::new(&mut body).poll_next(cx));
+        assert!(tokio::time::timeout(wait, end).await.unwrap().is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_long_lived_route_keeps_headers_timeout() {
+        // The upstream accepts the request but never answers
+        let addr = upstream(|socket, _| async move {
+            let _open = socket;
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        })
+        .await;
+        let (proxy, _) = live_proxy(addr).await;
+
+        let (req, mut payload) = actix_web::test::TestRequest::get().uri("/live/events").to_http_parts();
+        let payload = web::Payload::from_request(&req, &mut payload).await.unwrap();
+
+        let result = tokio::time::timeout(Duration::from_secs(5), proxy.proxy_request(req, payload))
+            .await
+            .expect("the route timeout should end the request");
+        let error = result.unwrap_err();
+        assert_eq!(error.as_response_error().status_code().as_u16(), 504);
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let req = actix_web::test::TestRequest::default()
+            .peer_addr("10.0.0.2:4000".parse().unwrap())
+            .insert_header(("X-Forwarded-For", "198.51.100.1, 203.0.113.7"))
+            .to_http_request();
+
+        let proxies =
+            |nets: &[&str]| -> Vec<IpNet> { nets.iter().map(|net| net.parse().unwrap()).collect() };
+
+        assert_eq!(client_ip(&req, &[]).as_deref(), Some("10.0.0.2"));
+        assert_eq!(client_ip(&req, &proxies(&["10.0.0.0/8"])).as_deref(), Some("203.0.113.7"));
+
+        // A client outside the trusted networks cannot pick its own address
+        let elsewhere = proxies(&["192.168.0.0/16", "10.0.0.3/32"]);
+        assert_eq!(client_ip(&req, &elsewhere).as_deref(), Some("10.0.0.2"));
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout_ends_stalled_body() {
+        let chunks = futures_util::stream::iter(vec![Ok(web::Bytes::from_static(b"first"))])
+            .chain(futures_util::stream::pending());
+        let mut body = Box::pin(idle_timeout(chunks, Duration::from_millis(50)));
+
+        assert_eq!(body.next().await.unwrap().unwrap(), "first");
+        let stalled = body.next().await.unwrap().unwrap_err();
+        assert_eq!(stalled.kind(), io::ErrorKind::TimedOut);
+        assert!(body.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout_allows_slow_long_body() {
+        // The whole body takes several idle timeouts, each pause less than one
+        let chunks = futures_util::stream::iter(0..10).then(|i| async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(web::Bytes::from(i.to_string()))
+        });
+        let body: Vec<_> = idle_timeout(chunks, Duration::from_millis(100)).collect().await;
+
+        assert_eq!(body.len(), 10);
+        assert!(body.iter().all(Result::is_ok));
+    }
+}
@@ -0,0 +1,132 @@
+use actix_web::{http::header, web, HttpRequest, HttpResponse};
+use futures_util::StreamExt;
+use reqwest::StatusCode;
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
+
+use super::{ProxyService, Target};
+
+/// Whether a request asks to switch to the WebSocket protocol
+pub(super) fn is_websocket_upgrade(req: &HttpRequest) -> bool {
+    req.headers()
+        .get(header::UPGRADE)
+        .and_then(|h| h.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("websocket"))
+}
+
+impl ProxyService {
+    /// Forward a WebSocket handshake and, once the upstream switches
+    /// protocols, tunnel raw bytes in both directions until either side
+    /// closes or the caller's session is revoked.
+    pub(super) async fn proxy_websocket(
+        &self,
+        target: &Target,
+        req: &HttpRequest,
+        mut payload: web::Payload,
+    ) -> Result<HttpResponse, actix_web::Error> {
+        let service_name = target.service_name.as_str();
+        let lease = target.upstream.balancer.pick();
+
+        let request = self
+            .build_backend_request(target, req, &lease, None)
+            .header(header::CONNECTION, "upgrade");
+
+        let backend_resp = match self.send(target, request).await {
+            Ok(resp) => resp,
+            Err(e) => return Err(self.upstream_error(target, &lease, e)),
+        };
+        self.record_outcome(target, &lease, backend_resp.status());
+
+        // The upstream refused the upgrade: relay its answer as a normal response
+        if backend_resp.status() != StatusCode::SWITCHING_PROTOCOLS {
+            return Ok(self.forward_response(target, req, backend_resp, lease));
+        }
+
+        let mut client_resp = HttpResponse::SwitchingProtocols();
+        client_resp.upgrade("websocket");
+        for (key, value) in backend_resp.headers().iter() {
+            if key != header::CONNECTION && key != header::UPGRADE {
+                client_resp.insert_header((key.clone(), value.clone()));
+            }
+        }
+
+        let revoked = self.session_revoked(target, req);
+
+        let upgraded = backend_resp.upgrade().await.map_err(|e| {
+            tracing::error!("Failed to upgrade connection to {}: {}", service_name, e);
+            actix_web::error::ErrorBadGateway(format!("Failed to reach {} service", service_name))
+        })?;
+        let (upstream_reader, mut upstream_writer) = tokio::io::split(upgraded);
+
+        // Client to upstream: the request payload carries the client's frames.
+        // The task holds the endpoint lease for the life of the connection.
+        let client_service = service_name.to_string();
+        actix_rt::spawn(async move {
+            let _lease = lease;
+            while let Some(chunk) = payload.next().await {
+                let written = match chunk {
+                    Ok(bytes) => upstream_writer.write_all(&bytes).await,
+                    Err(e) => {
+                        tracing::debug!("WebSocket client to {} closed: {}", client_service, e);
+                        break;
+                    }
+                };
+                if written.is_err() {
+                    break;
+                }
+            }
+            let _ = upstream_writer.shutdown().await;
+        });
+
+        tracing::debug!("WebSocket connection to {} established", service_name);
+
+        // Upstream to client, ending the connection if the session is revoked
+        Ok(client_resp.streaming(ReaderStream::new(upstream_reader).take_until(revoked)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{live_proxy, upstream};
+    use super::*;
+    use crate::handlers::proxy_handler;
+    use actix_web::{test, App};
+    use tokio::io::AsyncReadExt;
+
+    #[actix_web::test]
+    async fn test_proxy_websocket_tunnels_both_ways() {
+        let addr = upstream(|mut socket, head| async move {
+            assert!(head.contains("upgrade: websocket"));
+            assert!(head.contains("connection: upgrade"));
+            socket
+                .write_all(b"HTTP/1.1 101 Switching Protocols\r\nconnection: upgrade\r\nupgrade: websocket\r\n\r\n")
+                .await
+                .unwrap();
+
+            // Echo the client's frames until it closes its side
+            let mut frames = Vec::new();
+            socket.read_to_end(&mut frames).await.unwrap();
+            socket.write_all(&frames).await.unwrap();
+        })
+        .await;
+        let (proxy, _) = live_proxy(addr).await;
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(proxy))
+                .default_service(web::to(proxy_handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/live/socket")
+            .insert_header((header::UPGRADE, "websocket"))
+            .insert_header((header::CONNECTION, "upgrade"))
+            .set_payload("frame")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status().as_u16(), 101);
+        assert_eq!(test::read_body(resp).await, "frame");
+    }
+}
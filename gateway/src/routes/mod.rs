@@ -0,0 +1,431 @@
+use actix_web::http::header::{self, HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// Default route table shipped with the gateway
+const DEFAULT_ROUTES: &str = include_str!("../../routes.toml");
+
+/// How often a watched file's modification time is checked
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How requests are spread over an upstream's endpoints
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceStrategy {
+    #[default]
+    RoundRobin,
+    LeastOutstanding,
+}
+
+/// A backend service the gateway can forward to
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UpstreamDef {
+    /// Single endpoint, shorthand for `urls = [url]`
+    pub url: Option<String>,
+    /// Endpoints of the service's instances
+    #[serde(default)]
+    pub urls: Vec<String>,
+    #[serde(default)]
+    pub balance: BalanceStrategy,
+    #[serde(default = "default_health_path")]
+    pub health_path: String,
+    pub connect_timeout_ms: Option<u64>,
+    pub timeout_ms: Option<u64>,
+}
+
+fn default_health_path() -> String {
+    "/api/health".to_string()
+}
+
+fn default_max_body_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Security headers added to responses. Unset fields inherit from the
+/// table-level defaults; an empty string disables the header.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SecurityHeaders {
+    pub strict_transport_security: Option<String>,
+    pub content_type_options: Option<String>,
+    /// Value of the `frame-ancestors` directive in `Content-Security-Policy`
+    pub frame_ancestors: Option<String>,
+}
+
+impl SecurityHeaders {
+    /// Baseline applied when the route table leaves a header unset
+    fn baseline() -> Self {
+        Self {
+            strict_transport_security: Some("max-age=31536000; includeSubDomains".to_string()),
+            content_type_options: Some("nosniff".to_string()),
+            frame_ancestors: Some("'none'".to_string()),
+        }
+    }
+
+    /// Overlay `overrides` on these headers
+    fn merge(&self, overrides: &SecurityHeaders) -> Self {
+        Self {
+            strict_transport_security: overrides
+                .strict_transport_security
+                .clone()
+                .or_else(|| self.strict_transport_security.clone()),
+            content_type_options: overrides
+                .content_type_options
+                .clone()
+                .or_else(|| self.content_type_options.clone()),
+            frame_ancestors: overrides
+                .frame_ancestors
+                .clone()
+                .or_else(|| self.frame_ancestors.clone()),
+        }
+    }
+
+    /// Header name/value pairs to set, skipping disabled headers
+    pub fn headers(&self) -> anyhow::Result<Vec<(HeaderName, HeaderValue)>> {
+        let frame_ancestors = self
+            .frame_ancestors
+            .as_deref()
+            .filter(|value| !value.is_empty())
+            .map(|value| format!("frame-ancestors {}", value));
+
+        [
+            (header::STRICT_TRANSPORT_SECURITY, self.strict_transport_security.clone()),
+            (header::X_CONTENT_TYPE_OPTIONS, self.content_type_options.clone()),
+            (header::CONTENT_SECURITY_POLICY, frame_ancestors),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.filter(|value| !value.is_empty()).map(|value| (name, value)))
+        .map(|(name, value)| {
+            HeaderValue::from_str(&value)
+                .map(|value| (name.clone(), value))
+                .map_err(|_| anyhow::anyhow!("Invalid value for {} header: '{}'", name, value))
+        })
+        .collect()
+    }
+}
+
+/// A path prefix forwarded to an upstream
+#[derive(Debug, Clone, Deserialize)]
+pub struct Route {
+    pub prefix: String,
+    pub upstream: String,
+    #[serde(default)]
+    pub strip_prefix: bool,
+    #[serde(default = "default_true")]
+    pub auth: bool,
+    pub timeout_ms: Option<u64>,
+    pub max_body_bytes: Option<u64>,
+    /// Allow SSE streams and WebSocket upgrades, which are exempt from the
+    /// body idle timeout once the upstream has answered
+    #[serde(default)]
+    pub long_lived: bool,
+    #[serde(default)]
+    pub security_headers: SecurityHeaders,
+}
+
+impl Route {
+    /// Path to request on the upstream for an inbound path
+    pub fn upstream_path(&self, path: &str) -> String {
+        if !self.strip_prefix {
+            return path.to_string();
+        }
+
+        match path[self.prefix.len()..].trim_start_matches('/') {
+            "" => "/".to_string(),
+            rest => format!("/{}", rest),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RouteTable {
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: u64,
+    #[serde(default)]
+    pub security_headers: SecurityHeaders,
+    pub upstreams: HashMap<String, UpstreamDef>,
+    pub routes: Vec<Route>,
+}
+
+impl RouteTable {
+    /// Load routes from a TOML/JSON/YAML file, or the bundled defaults when no path is given
+    pub fn load(path: Option<&str>) -> anyhow::Result<Self> {
+        let source = match path {
+            Some(path) => config::Config::builder().add_source(config::File::with_name(path)),
+            None => config::Config::builder()
+                .add_source(config::File::from_str(DEFAULT_ROUTES, config::FileFormat::Toml)),
+        };
+
+        Self::from_source(source)
+    }
+
+    /// Parse a route table from TOML
+    #[cfg(test)]
+    pub fn from_toml(source: &str) -> anyhow::Result<Self> {
+        Self::from_source(
+            config::Config::builder().add_source(config::File::from_str(source, config::FileFormat::Toml)),
+        )
+    }
+
+    fn from_source(
+        source: config::ConfigBuilder<config::builder::DefaultState>,
+    ) -> anyhow::Result<Self> {
+        let mut table: RouteTable = source.build()?.try_deserialize()?;
+        table.validate()?;
+
+        // Longest prefix first so the first match is the most specific
+        table
+            .routes
+            .sort_by_key(|route| std::cmp::Reverse(route.prefix.len()));
+
+        Ok(table)
+    }
+
+    fn validate(&mut self) -> anyhow::Result<()> {
+        for route in &mut self.routes {
+            if !route.prefix.starts_with('/') {
+                anyhow::bail!("Route prefix '{}' must start with '/'", route.prefix);
+            }
+            if !self.upstreams.contains_key(&route.upstream) {
+                anyhow::bail!(
+                    "Route '{}' refers to unknown upstream '{}'",
+                    route.prefix,
+                    route.upstream
+                );
+            }
+            if route.prefix.len() > 1 {
+                route.prefix = route.prefix.trim_end_matches('/').to_string();
+            }
+        }
+
+        self.security_headers = SecurityHeaders::baseline().merge(&self.security_headers);
+        self.security_headers.headers()?;
+        for route in &self.routes {
+            self.security_headers.merge(&route.security_headers).headers()?;
+        }
+
+        for (name, upstream) in self.upstreams.iter_mut() {
+            let mut normalized: Vec<String> = Vec::new();
+            for url in upstream.url.take().into_iter().chain(upstream.urls.drain(..)) {
+                let url = url.trim_end_matches('/').to_string();
+                if !normalized.contains(&url) {
+                    normalized.push(url);
+                }
+            }
+
+            if normalized.is_empty() {
+                anyhow::bail!("Upstream '{}' must set url or urls", name);
+            }
+            upstream.urls = normalized;
+        }
+
+        Ok(())
+    }
+
+    /// Find the route for a request path
+    pub fn find(&self, path: &str) -> Option<&Route> {
+        self.routes.iter().find(|route| {
+            route.prefix == "/"
+                || path == route.prefix
+                || path
+                    .strip_prefix(route.prefix.as_str())
+                    .map(|rest| rest.starts_with('/'))
+                    .unwrap_or(false)
+        })
+    }
+
+    /// Security headers for a response to the request path
+    pub fn security_headers(&self, path: &str) -> Vec<(HeaderName, HeaderValue)> {
+        let headers = match self.find(path) {
+            Some(route) => self.security_headers.merge(&route.security_headers),
+            None => self.security_headers.clone(),
+        };
+
+        // Values were checked when the table was loaded
+        headers.headers().unwrap_or_default()
+    }
+
+    /// Whether the request path needs a session; unknown paths do
+    pub fn requires_auth(&self, path: &str) -> bool {
+        self.find(path).map(|route| route.auth).unwrap_or(true)
+    }
+}
+
+/// Table shared between the proxy and middleware, swapped on reload
+pub struct Shared<T> {
+    inner: Arc<RwLock<Arc<T>>>,
+}
+
+/// Route table shared between the proxy and middleware
+pub type SharedRoutes = Shared<RouteTable>;
+
+impl<T> Shared<T> {
+    pub fn new(table: T) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(Arc::new(table))),
+        }
+    }
+
+    pub fn current(&self) -> Arc<T> {
+        self.inner.read().unwrap().clone()
+    }
+
+    pub fn replace(&self, table: T) {
+        *self.inner.write().unwrap() = Arc::new(table);
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// Reload a table file on SIGHUP or when its modification time changes.
+/// `apply` is called with each table `load` parses successfully; parse
+/// errors are logged and the current table stays in place.
+pub fn watch<T, L, F>(path: String, load: L, apply: F)
+where
+    T: 'static,
+    L: Fn(&str) -> anyhow::Result<T> + 'static,
+    F: Fn(T) + 'static,
+{
+    actix_rt::spawn(async move {
+        let modified = |path: &str| -> Option<SystemTime> {
+            std::fs::metadata(PathBuf::from(path)).and_then(|m| m.modified()).ok()
+        };
+
+        let mut last_modified = modified(&path);
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+
+        #[cfg(unix)]
+        let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+            .expect("Failed to install SIGHUP handler");
+
+        loop {
+            #[cfg(unix)]
+            let signalled = tokio::select! {
+                _ = interval.tick() => false,
+                _ = hangup.recv() => true,
+            };
+            #[cfg(not(unix))]
+            let signalled = {
+                interval.tick().await;
+                false
+            };
+
+            let current = modified(&path);
+            if !signalled && current == last_modified {
+                continue;
+            }
+            last_modified = current;
+
+            match load(&path) {
+                Ok(table) => {
+                    tracing::info!("Reloaded {}", path);
+                    apply(table);
+                }
+                Err(e) => {
+                    tracing::error!("Failed to reload {}, keeping current table: {}", path, e);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_routes() {
+        let table = RouteTable::load(None).unwrap();
+
+        assert_eq!(table.find("/api/orders/123").unwrap().upstream, "order");
+        assert_eq!(table.find("/api/auth/request-otp").unwrap().upstream, "auth");
+        assert!(table.find("/api/ordersx").is_none());
+        assert!(table.find("/unknown").is_none());
+
+        assert!(!table.requires_auth("/api/auth/verify-otp"));
+        assert!(table.requires_auth("/api/auth/sessions"));
+        assert!(table.requires_auth("/api/orders"));
+
+        // Live updates for browsers and kitchen tablets
+        assert!(table.find("/api/orders/123/events").unwrap().long_lived);
+        assert!(table.find("/api/kitchen/tickets").unwrap().long_lived);
+        assert!(!table.find("/api/billing/orders/1").unwrap().long_lived);
+    }
+
+    #[test]
+    fn test_strip_prefix() {
+        let route = Route {
+            prefix: "/api/scheduler".to_string(),
+            upstream: "scheduler".to_string(),
+            strip_prefix: true,
+            auth: true,
+            timeout_ms: None,
+            max_body_bytes: None,
+            long_lived: false,
+            security_headers: SecurityHeaders::default(),
+        };
+
+        assert_eq!(route.upstream_path("/api/scheduler/jobs/1"), "/jobs/1");
+        assert_eq!(route.upstream_path("/api/scheduler"), "/");
+    }
+
+    #[test]
+    fn test_security_header_overrides() {
+        let mut table: RouteTable = config::Config::builder()
+            .add_source(config::File::from_str(
+                r#"
+                [security_headers]
+                content_type_options = ""
+
+                [upstreams.docs]
+                url = "http://localhost:9000"
+
+                [[routes]]
+                prefix = "/docs"
+                upstream = "docs"
+                security_headers = { frame_ancestors = "'self'" }
+                "#,
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        table.validate().unwrap();
+
+        let headers = table.security_headers("/docs/index.html");
+        let value = |name: HeaderName| {
+            headers
+                .iter()
+                .find(|(header, _)| *header == name)
+                .map(|(_, value)| value.to_str().unwrap().to_string())
+        };
+
+        assert_eq!(
+            value(header::STRICT_TRANSPORT_SECURITY).as_deref(),
+            Some("max-age=31536000; includeSubDomains")
+        );
+        assert_eq!(value(header::X_CONTENT_TYPE_OPTIONS), None);
+        assert_eq!(
+            value(header::CONTENT_SECURITY_POLICY).as_deref(),
+            Some("frame-ancestors 'self'")
+        );
+        assert_eq!(
+            table.security_headers("/other").len(),
+            2,
+            "unrouted paths use the table defaults"
+        );
+    }
+}
@@ -0,0 +1,512 @@
+use futures_util::StreamExt;
+use models::user::{Session, SessionInvalidation, SESSION_INVALIDATION_CHANNEL};
+use redis::aio::ConnectionManager;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Delay before resubscribing after the pub/sub connection drops
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(2);
+
+/// Invalidations buffered for long-lived connections watching for revocation
+const REVOCATION_CHANNEL_CAPACITY: usize = 256;
+
+/// Records when a session was last used, for auth-service's session listing,
+/// and extends it by `ARGV[2]` seconds if that is non-zero. The record
+/// expires with the session.
+const SESSION_TOUCH_SCRIPT: &str = r#"
+local ttl = tonumber(ARGV[2])
+if ttl > 0 then
+    redis.call('EXPIRE', KEYS[1], ttl)
+else
+    ttl = redis.call('TTL', KEYS[1])
+end
+if ttl > 0 then
+    redis.call('SET', KEYS[2], ARGV[1], 'EX', ttl)
+end
+return ttl
+"#;
+
+/// Caller credential from the `Authorization` header
+#[derive(Debug, Clone)]
+pub enum Credential {
+    /// `Bearer <token>`: an interactive session
+    Session(String),
+    /// `ApiKey <key>`: a machine client, held as the hash of the key
+    ApiKey(String),
+}
+
+impl Credential {
+    /// Parse an `Authorization` header value
+    pub fn from_header(value: &str) -> Option<Self> {
+        if let Some(token) = value.strip_prefix("Bearer ") {
+            Some(Credential::Session(token.to_string()))
+        } else {
+            value
+                .strip_prefix("ApiKey ")
+                .map(|key| Credential::ApiKey(identity::hash_api_key(key.trim())))
+        }
+    }
+
+    /// Key of the session in Redis, also used as the cache key
+    fn redis_key(&self) -> String {
+        match self {
+            Credential::Session(token) => format!("session:{}", token),
+            Credential::ApiKey(key_hash) => format!("api_key:{}", key_hash),
+        }
+    }
+
+    /// Id passed upstream in the signed identity
+    pub fn session_id(&self, session: &Session) -> String {
+        match (self, &session.scope) {
+            (Credential::Session(token), _) => token.clone(),
+            (Credential::ApiKey(_), Some(scope)) => format!("api_key:{}", scope.api_key_id),
+            (Credential::ApiKey(key_hash), None) => format!("api_key:{}", key_hash),
+        }
+    }
+}
+
+struct CachedSession {
+    session: Session,
+    expires_at: Instant,
+}
+
+/// Extension of interactive sessions on activity
+#[derive(Debug, Clone, Copy)]
+pub struct SlidingExpiry {
+    /// TTL a session is reset to when used
+    pub idle_timeout: Duration,
+    /// Sessions older than this are left to expire
+    pub max_age: Duration,
+}
+
+impl SlidingExpiry {
+    /// Seconds to extend a session created at `created_at` by, if any
+    fn extension(&self, created_at: chrono::DateTime<chrono::Utc>) -> Option<u64> {
+        let age = (chrono::Utc::now() - created_at).to_std().unwrap_or_default();
+        let remaining = self.max_age.checked_sub(age)?;
+        Some(self.idle_timeout.min(remaining).as_secs()).filter(|seconds| *seconds > 0)
+    }
+}
+
+/// Bounded in-memory TTL cache of validated sessions in front of Redis.
+///
+/// Entries live for at most `ttl`, and never past the session's expiry in
+/// Redis, which publishes nothing when it expires a key. Logouts and
+/// deactivations are pushed over Redis pub/sub so they take effect before
+/// the TTL runs out.
+pub struct SessionCache {
+    redis: ConnectionManager,
+    entries: RwLock<HashMap<String, CachedSession>>,
+    capacity: usize,
+    ttl: Duration,
+    /// Bumped on every invalidation so a lookup racing one is not cached
+    generation: AtomicU64,
+    /// Caching is only safe while invalidations are being received
+    subscribed: AtomicBool,
+    /// Invalidations fanned out to long-lived connections
+    revocations: broadcast::Sender<SessionInvalidation>,
+    sliding: Option<SlidingExpiry>,
+    touch_script: redis::Script,
+}
+
+impl SessionCache {
+    pub fn new(redis: ConnectionManager, capacity: usize, ttl: Duration) -> Self {
+        Self {
+            redis,
+            entries: RwLock::new(HashMap::new()),
+            capacity: capacity.max(1),
+            ttl,
+            generation: AtomicU64::new(0),
+            subscribed: AtomicBool::new(false),
+            revocations: broadcast::channel(REVOCATION_CHANNEL_CAPACITY).0,
+            sliding: None,
+            touch_script: redis::Script::new(SESSION_TOUCH_SCRIPT),
+        }
+    }
+
+    /// Extend interactive sessions whenever they are loaded from Redis. Cache
+    /// hits do not, so a busy session is extended about once per cache TTL.
+    pub fn with_sliding_expiry(mut self, sliding: SlidingExpiry) -> Self {
+        self.sliding = Some(sliding);
+        self
+    }
+
+    /// Look up the session behind a credential, going to Redis on a cache miss
+    pub async fn get(&self, credential: &Credential) -> Result<Option<Session>, redis::RedisError> {
+        let key = credential.redis_key();
+        if let Some(session) = self.cached(&key) {
+            return Ok(Some(session));
+        }
+
+        let generation = self.generation.load(Ordering::Acquire);
+        let mut redis = self.redis.clone();
+        let (session_json, mut remaining_ms): (Option<String>, i64) = redis::pipe()
+            .atomic()
+            .get(&key)
+            .pttl(&key)
+            .query_async(&mut redis)
+            .await?;
+
+        let session = match session_json.and_then(|json| serde_json::from_str::<Session>(&json).ok()) {
+            Some(session) => session,
+            None => return Ok(None),
+        };
+
+        if let Credential::Session(token) = credential {
+            let extension = self
+                .sliding
+                .and_then(|sliding| sliding.extension(session.created_at))
+                .unwrap_or(0);
+
+            // Best effort: the session stays valid until its current TTL either way
+            match self
+                .touch_script
+                .key(&key)
+                .key(format!("session_seen:{}", token))
+                .arg(chrono::Utc::now().timestamp())
+                .arg(extension)
+                .invoke_async::<_, i64>(&mut redis)
+                .await
+            {
+                Ok(ttl) if extension > 0 && ttl > 0 => remaining_ms = ttl * 1000,
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to record session activity: {}", e),
+            }
+        }
+
+        if self.subscribed.load(Ordering::Acquire)
+            && self.generation.load(Ordering::Acquire) == generation
+        {
+            self.insert(&key, session.clone(), cache_lifetime(self.ttl, remaining_ms));
+        }
+        Ok(Some(session))
+    }
+
+    fn cached(&self, key: &str) -> Option<Session> {
+        let entries = self.entries.read().unwrap();
+        entries
+            .get(key)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .map(|entry| entry.session.clone())
+    }
+
+    fn insert(&self, key: &str, session: Session, lifetime: Duration) {
+        let mut entries = self.entries.write().unwrap();
+
+        if entries.len() >= self.capacity && !entries.contains_key(key) {
+            let now = Instant::now();
+            entries.retain(|_, entry| entry.expires_at > now);
+
+            // Still full: drop the entry closest to expiry
+            if entries.len() >= self.capacity {
+                if let Some(oldest) = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.expires_at)
+                    .map(|(key, _)| key.clone())
+                {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+
+        entries.insert(
+            key.to_string(),
+            CachedSession {
+                session,
+                expires_at: Instant::now() + lifetime,
+            },
+        );
+    }
+
+    pub fn invalidate_session(&self, token: &str) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        self.entries
+            .write()
+            .unwrap()
+            .remove(&Credential::Session(token.to_string()).redis_key());
+    }
+
+    pub fn invalidate_user(&self, user_id: Uuid) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        self.entries
+            .write()
+            .unwrap()
+            .retain(|_, entry| entry.session.user_id != user_id);
+    }
+
+    pub fn invalidate_api_key(&self, api_key_id: Uuid) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        self.entries
+            .write()
+            .unwrap()
+            .retain(|_, entry| entry.session.scope.as_ref().map(|scope| scope.api_key_id) != Some(api_key_id));
+    }
+
+    pub fn clear(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        self.entries.write().unwrap().clear();
+    }
+
+    fn set_subscribed(&self, subscribed: bool) {
+        self.subscribed.store(subscribed, Ordering::Release);
+        self.clear();
+    }
+
+    fn apply(&self, invalidation: SessionInvalidation) {
+        match &invalidation {
+            SessionInvalidation::Session { session_id } => self.invalidate_session(session_id),
+            SessionInvalidation::User { user_id } => self.invalidate_user(*user_id),
+            SessionInvalidation::ApiKey { api_key_id } => self.invalidate_api_key(*api_key_id),
+        }
+
+        // No receivers just means no long-lived connections are open
+        let _ = self.revocations.send(invalidation);
+    }
+
+    /// Resolve once a session is revoked, either by an invalidation for it,
+    /// its user or its API key, or when a periodic re-check no longer finds it
+    /// in Redis. The re-check covers invalidations missed while the
+    /// subscription was down.
+    pub async fn wait_for_revocation(self: Arc<Self>, credential: Credential, session: Session, recheck: Duration) {
+        let mut revocations = self.revocations.subscribe();
+        let mut interval = tokio::time::interval(recheck);
+        interval.reset();
+
+        loop {
+            let check = tokio::select! {
+                received = revocations.recv() => match received {
+                    Ok(invalidation) if revokes(&invalidation, &credential, &session) => return,
+                    Ok(_) => false,
+                    // Lagged behind and missed some: look the session up instead.
+                    // The channel cannot close while the cache holds its sender.
+                    Err(_) => true,
+                },
+                _ = interval.tick() => true,
+            };
+
+            if check {
+                match self.get(&credential).await {
+                    Ok(None) => return,
+                    Ok(Some(_)) => {}
+                    Err(e) => tracing::warn!("Failed to re-check session of long-lived connection: {}", e),
+                }
+            }
+        }
+    }
+}
+
+/// How long to cache a session whose Redis key has `remaining_ms` to live
+/// (`PTTL`: negative when the key has no expiry)
+fn cache_lifetime(ttl: Duration, remaining_ms: i64) -> Duration {
+    match u64::try_from(remaining_ms) {
+        Ok(remaining_ms) => ttl.min(Duration::from_millis(remaining_ms)),
+        Err(_) => ttl,
+    }
+}
+
+/// Whether an invalidation ends the session behind a credential
+fn revokes(invalidation: &SessionInvalidation, credential: &Credential, session: &Session) -> bool {
+    match (invalidation, credential) {
+        (SessionInvalidation::Session { session_id }, Credential::Session(token)) => session_id == token,
+        (SessionInvalidation::User { user_id }, _) => *user_id == session.user_id,
+        (SessionInvalidation::ApiKey { api_key_id }, Credential::ApiKey(_)) => {
+            session.scope.as_ref().is_some_and(|scope| scope.api_key_id == *api_key_id)
+        }
+        _ => false,
+    }
+}
+
+/// Subscribe to session invalidations and evict matching cache entries.
+/// While the subscription is down invalidations may be missed, so the cache
+/// is cleared and bypassed until it reconnects.
+pub fn spawn_invalidation_listener(client: redis::Client, cache: Arc<SessionCache>) {
+    actix_rt::spawn(async move {
+        loop {
+            match client.get_async_connection().await {
+                Ok(connection) => {
+                    let mut pubsub = connection.into_pubsub();
+
+                    if let Err(e) = pubsub.subscribe(SESSION_INVALIDATION_CHANNEL).await {
+                        tracing::error!("Failed to subscribe to session invalidations: {}", e);
+                    } else {
+                        cache.set_subscribed(true);
+                        tracing::info!("Subscribed to {}", SESSION_INVALIDATION_CHANNEL);
+
+                        let mut messages = pubsub.on_message();
+                        while let Some(message) = messages.next().await {
+                            let payload: String = match message.get_payload() {
+                                Ok(payload) => payload,
+                                Err(e) => {
+                                    tracing::warn!("Invalid session invalidation payload: {}", e);
+                                    continue;
+                                }
+                            };
+
+                            match serde_json::from_str::<SessionInvalidation>(&payload) {
+                                Ok(invalidation) => cache.apply(invalidation),
+                                Err(e) => tracing::warn!("Invalid session invalidation message: {}", e),
+                            }
+                        }
+
+                        tracing::warn!("Session invalidation subscription closed");
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Failed to connect for session invalidations: {}", e);
+                }
+            }
+
+            // Anything cached may now be stale
+            cache.set_subscribed(false);
+            tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+        }
+    });
+}
+
+/// Session cache on an empty stand-in for Redis, for tests elsewhere in the
+/// gateway
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+    use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// A cache whose Redis holds no keys and accepts every write
+    pub async fn empty_cache() -> Arc<SessionCache> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(serve(socket));
+            }
+        });
+
+        let client = redis::Client::open(format!("redis://{}", addr)).unwrap();
+        let redis = ConnectionManager::new(client).await.unwrap();
+        Arc::new(SessionCache::new(redis, 16, Duration::from_secs(60)))
+    }
+
+    /// Deliver an invalidation as if it came over pub/sub
+    pub fn publish(cache: &SessionCache, invalidation: SessionInvalidation) {
+        cache.apply(invalidation);
+    }
+
+    async fn serve(socket: TcpStream) {
+        let (reader, mut writer) = socket.into_split();
+        let mut reader = BufReader::new(reader);
+        let mut transaction: Option<Vec<&str>> = None;
+
+        while let Some(command) = read_command(&mut reader).await {
+            let reply = match command.as_str() {
+                "GET" => "$-1\r\n",
+                "TTL" | "PTTL" => ":-2\r\n",
+                _ => "+OK\r\n",
+            };
+
+            let out = match (command.as_str(), transaction.as_mut()) {
+                ("MULTI", _) => {
+                    transaction = Some(Vec::new());
+                    "+OK\r\n".to_string()
+                }
+                ("EXEC", _) => {
+                    let replies = transaction.take().unwrap_or_default();
+                    format!("*{}\r\n{}", replies.len(), replies.concat())
+                }
+                (_, Some(queued)) => {
+                    queued.push(reply);
+                    "+QUEUED\r\n".to_string()
+                }
+                _ => reply.to_string(),
+            };
+
+            if writer.write_all(out.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Name of the next command, skipping its arguments
+    async fn read_command<R: AsyncBufRead + Unpin>(reader: &mut R) -> Option<String> {
+        let mut line = String::new();
+        reader.read_line(&mut line).await.ok().filter(|read| *read > 0)?;
+        let count: usize = line.trim_end().strip_prefix('*')?.parse().ok()?;
+
+        let mut name = None;
+        for _ in 0..count {
+            line.clear();
+            reader.read_line(&mut line).await.ok()?;
+            let len: usize = line.trim_end().strip_prefix('$')?.parse().ok()?;
+            let mut arg = vec![0; len + 2];
+            reader.read_exact(&mut arg).await.ok()?;
+            name.get_or_insert_with(|| String::from_utf8_lossy(&arg[..len]).to_ascii_uppercase());
+        }
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::user::UserRole;
+
+    fn session() -> Session {
+        Session {
+            user_id: Uuid::new_v4(),
+            email: "cook@example.com".to_string(),
+            role: UserRole::Kitch,
+            created_at: chrono::Utc::now(),
+            scope: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_revocation_on_invalidation() {
+        let cache = testing::empty_cache().await;
+        let session = session();
+        let credential = Credential::Session("token".to_string());
+
+        let mut watch = Box::pin(cache.clone().wait_for_revocation(
+            credential,
+            session.clone(),
+            Duration::from_secs(3600),
+        ));
+        let pending = Duration::from_millis(50);
+        assert!(tokio::time::timeout(pending, &mut watch).await.is_err());
+
+        // Other sessions and users are left alone
+        testing::publish(&cache, SessionInvalidation::Session { session_id: "other".to_string() });
+        testing::publish(&cache, SessionInvalidation::User { user_id: Uuid::new_v4() });
+        assert!(tokio::time::timeout(pending, &mut watch).await.is_err());
+
+        testing::publish(&cache, SessionInvalidation::User { user_id: session.user_id });
+        assert!(tokio::time::timeout(Duration::from_secs(1), watch).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_revocation_on_recheck() {
+        // No invalidation arrives, but the session is gone from Redis
+        let cache = testing::empty_cache().await;
+        let watch = cache.wait_for_revocation(
+            Credential::Session("token".to_string()),
+            session(),
+            Duration::from_millis(20),
+        );
+
+        assert!(tokio::time::timeout(Duration::from_secs(1), watch).await.is_ok());
+    }
+
+    #[test]
+    fn test_cache_lifetime_capped_at_redis_ttl() {
+        let ttl = Duration::from_secs(30);
+
+        assert_eq!(cache_lifetime(ttl, 5_000), Duration::from_secs(5));
+        assert_eq!(cache_lifetime(ttl, 0), Duration::ZERO);
+        assert_eq!(cache_lifetime(ttl, 60_000), ttl);
+        // No expiry (API keys)
+        assert_eq!(cache_lifetime(ttl, -1), ttl);
+    }
+}
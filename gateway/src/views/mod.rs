@@ -0,0 +1,136 @@
+use actix_web::{http::StatusCode, HttpMessage, HttpRequest};
+use error_handling::AppError;
+use identity::Identity;
+use models::billing::Bill;
+use models::kitchen::KitchenTicketResponse;
+use models::order::OrderResponse;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::proxy::ProxyService;
+
+/// Why a section of a view could not be loaded
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SectionError {
+    /// Status of the failed upstream call, or of the gateway's own error
+    pub status: u16,
+    pub message: String,
+}
+
+/// Order with its kitchen ticket and bill, loaded concurrently
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OrderView {
+    pub order_id: Uuid,
+    pub order: Option<OrderResponse>,
+    pub kitchen_ticket: Option<KitchenTicketResponse>,
+    pub bill: Option<Bill>,
+    /// Sections that failed to load. A missing section without an error
+    /// does not exist yet, e.g. the bill of an order still being prepared.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[schema(value_type = BTreeMap<String, SectionError>)]
+    pub errors: BTreeMap<&'static str, SectionError>,
+}
+
+impl OrderView {
+    /// Whether every section failed, leaving nothing to show
+    pub fn is_unavailable(&self) -> bool {
+        self.errors.len() == 3
+    }
+}
+
+/// Outcome of loading one section
+enum Section<T> {
+    Loaded(T),
+    NotFound,
+    Failed(SectionError),
+}
+
+async fn load<T: DeserializeOwned>(proxy: &ProxyService, req: &HttpRequest, path: &str) -> Section<T> {
+    match proxy.fetch(req, path).await {
+        Ok((status, body)) if status.is_success() => match serde_json::from_slice(&body) {
+            Ok(document) => Section::Loaded(document),
+            Err(e) => {
+                tracing::error!("Invalid response from {}: {}", path, e);
+                Section::Failed(SectionError {
+                    status: StatusCode::BAD_GATEWAY.as_u16(),
+                    message: "Invalid response from upstream service".to_string(),
+                })
+            }
+        },
+        Ok((StatusCode::NOT_FOUND, _)) => Section::NotFound,
+        Ok((status, _)) => Section::Failed(SectionError {
+            status: status.as_u16(),
+            message: status.canonical_reason().unwrap_or("Upstream error").to_string(),
+        }),
+        Err(e) => Section::Failed(SectionError {
+            status: e.as_response_error().status_code().as_u16(),
+            message: e.to_string(),
+        }),
+    }
+}
+
+/// Load an order, its kitchen ticket and its bill for the caller.
+///
+/// The order service checks that the caller owns the order, so a missing or
+/// forbidden order fails the whole view. The kitchen and billing services do
+/// not check ownership, so their records are only shown if they belong to
+/// the caller. Other failures leave the section empty with an error marker.
+pub async fn order_view(proxy: &ProxyService, req: &HttpRequest, order_id: Uuid) -> Result<OrderView, actix_web::Error> {
+    let caller = req
+        .extensions()
+        .get::<Identity>()
+        .map(|identity| identity.user_id)
+        .ok_or_else(|| AppError::Unauthorized("Missing identity".to_string()))?;
+
+    let order_path = format!("/api/orders/{}", order_id);
+    let ticket_path = format!("/api/kitchen/orders/{}/ticket", order_id);
+    let bill_path = format!("/api/billing/orders/{}", order_id);
+
+    let (order, kitchen_ticket, bill) = futures_util::join!(
+        load::<OrderResponse>(proxy, req, &order_path),
+        load::<KitchenTicketResponse>(proxy, req, &ticket_path),
+        load::<Bill>(proxy, req, &bill_path),
+    );
+
+    let mut view = OrderView {
+        order_id,
+        order: None,
+        kitchen_ticket: None,
+        bill: None,
+        errors: BTreeMap::new(),
+    };
+
+    match order {
+        Section::Loaded(order) => view.order = Some(order),
+        Section::NotFound => {
+            return Err(AppError::NotFound(format!("Order not found: {}", order_id)).into());
+        }
+        Section::Failed(e) if e.status == 401 || e.status == 403 => {
+            return Err(AppError::Forbidden("Access to this order is denied".to_string()).into());
+        }
+        Section::Failed(e) => {
+            view.errors.insert("order", e);
+        }
+    }
+
+    match kitchen_ticket {
+        Section::Loaded(ticket) if ticket.user_id == caller => view.kitchen_ticket = Some(ticket),
+        Section::Loaded(_) | Section::NotFound => {}
+        Section::Failed(e) => {
+            view.errors.insert("kitchen_ticket", e);
+        }
+    }
+
+    match bill {
+        Section::Loaded(bill) if bill.user_id == caller => view.bill = Some(bill),
+        Section::Loaded(_) | Section::NotFound => {}
+        Section::Failed(e) => {
+            view.errors.insert("bill", e);
+        }
+    }
+
+    Ok(view)
+}
@@ -35,6 +35,13 @@ docker exec restman-kafka kafka-topics --create \
   --replication-factor 1 \
   --if-not-exists
 
+docker exec restman-kafka kafka-topics --create \
+  --bootstrap-server localhost:9093 \
+  --topic audit.events \
+  --partitions 3 \
+  --replication-factor 1 \
+  --if-not-exists
+
 # List all topics
 echo "Listing all topics..."
 docker exec restman-kafka kafka-topics --list --bootstrap-server localhost:9093
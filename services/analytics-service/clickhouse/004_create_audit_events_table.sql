@@ -0,0 +1,18 @@
+-- Create audit log table. Rows are only ever inserted, never updated; a record
+-- delivered twice (offsets are committed after the insert) is collapsed by
+-- event id on merge, and read with FINAL.
+CREATE TABLE IF NOT EXISTS restman_analytics.audit_events (
+    event_id UUID,
+    occurred_at DateTime,
+    user_id Nullable(UUID),
+    role Nullable(String),
+    api_key_id Nullable(UUID),
+    restaurant_id Nullable(UUID),
+    method String,
+    path String,
+    status UInt16,
+    latency_ms UInt32,
+    request_id Nullable(String)
+) ENGINE = ReplacingMergeTree()
+ORDER BY (occurred_at, event_id)
+PARTITION BY toYYYYMM(occurred_at);
@@ -1,6 +1,7 @@
-use crate::domain::{OrdersByStatus, RevenueSummary, TopFood};
+use crate::domain::{AuditRecord, OrdersByStatus, RevenueSummary, TopFood};
 use crate::infrastructure::ClickHouseClient;
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -172,5 +173,59 @@ impl AnalyticsService {
 
         Ok(results)
     }
-}
 
+    /// Get audit records matching the given filters, newest first
+    pub async fn get_audit_records(
+        &self,
+        user_id: Option<Uuid>,
+        restaurant_id: Option<Uuid>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<AuditRecord>> {
+        let mut conditions = Vec::new();
+        if let Some(user_id) = user_id {
+            conditions.push(format!("user_id = '{}'", user_id));
+        }
+        if let Some(restaurant_id) = restaurant_id {
+            conditions.push(format!("restaurant_id = '{}'", restaurant_id));
+        }
+        if let Some(from) = from {
+            conditions.push(format!("occurred_at >= parseDateTimeBestEffort('{}')", from.to_rfc3339()));
+        }
+        if let Some(to) = to {
+            conditions.push(format!("occurred_at < parseDateTimeBestEffort('{}')", to.to_rfc3339()));
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        // ISO timestamps so rows deserialize straight into `AuditRecord`
+        let query = format!(
+            r#"
+            SELECT *
+            FROM audit_events FINAL
+            {}
+            ORDER BY occurred_at DESC, event_id
+            LIMIT {}
+            SETTINGS date_time_output_format = 'iso'
+            "#,
+            where_clause, limit
+        );
+
+        let response = self.clickhouse.query_json(&query).await?;
+
+        // Parse NDJSON response
+        let mut results = Vec::new();
+        for line in response.lines() {
+            if !line.trim().is_empty() {
+                results.push(serde_json::from_str(line)?);
+            }
+        }
+
+        Ok(results)
+    }
+}
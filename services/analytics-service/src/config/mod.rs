@@ -23,6 +23,7 @@ pub struct ClickHouseConfig {
 pub struct KafkaConfig {
     pub brokers: String,
     pub group_id: String,
+    pub audit_group_id: String,
 }
 
 impl Config {
@@ -48,6 +49,7 @@ impl Config {
             brokers: std::env::var("KAFKA_BROKERS")
                 .unwrap_or_else(|_| "localhost:9092".to_string()),
             group_id: "analytics-service-group".to_string(),
+            audit_group_id: "analytics-service-audit-group".to_string(),
         };
 
         Ok(Config {
@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 /// Order record for ClickHouse
@@ -50,7 +51,7 @@ pub struct BillAnalytics {
 }
 
 /// Top food item result
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TopFood {
     pub food_id: Uuid,
     pub food_name: String,
@@ -59,7 +60,7 @@ pub struct TopFood {
 }
 
 /// Revenue summary result
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct RevenueSummary {
     pub total_revenue: Decimal,
     pub total_orders: i64,
@@ -67,9 +68,25 @@ pub struct RevenueSummary {
 }
 
 /// Orders by status result
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct OrdersByStatus {
     pub status: String,
     pub count: i64,
 }
 
+
+/// Audit log record for ClickHouse
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub event_id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    pub user_id: Option<Uuid>,
+    pub role: Option<String>,
+    pub api_key_id: Option<Uuid>,
+    pub restaurant_id: Option<Uuid>,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub latency_ms: u32,
+    pub request_id: Option<String>,
+}
@@ -0,0 +1,155 @@
+use crate::domain::AuditRecord;
+use crate::infrastructure::clickhouse_client::ClickHouseClient;
+use kafka_client::consumer::KafkaConsumer;
+use models::events::AuditRecordedEvent;
+use rdkafka::consumer::{CommitMode, Consumer};
+use rdkafka::message::{BorrowedMessage, Message};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Most records stored in one ClickHouse insert
+const BATCH_SIZE: usize = 500;
+
+/// How long a batch waits for more records after its first one
+const BATCH_LINGER: Duration = Duration::from_secs(1);
+
+/// Delay before retrying a failed insert, doubled up to `MAX_RETRY_DELAY`
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Stores the gateway's audit records, in a consumer group of its own so the
+/// audit trail is not held up by the analytics events.
+///
+/// Records are inserted in batches and their offsets committed only once
+/// ClickHouse has them. A failed insert is retried until it succeeds, holding
+/// up the batches behind it, so no record is skipped. Records redelivered
+/// after a crash are collapsed by event id in the table.
+pub struct AuditConsumer {
+    consumer: KafkaConsumer,
+    clickhouse: Arc<ClickHouseClient>,
+}
+
+impl AuditConsumer {
+    pub fn new(consumer: KafkaConsumer, clickhouse: Arc<ClickHouseClient>) -> Self {
+        Self {
+            consumer,
+            clickhouse,
+        }
+    }
+
+    pub async fn start(self) {
+        tracing::info!("Starting Kafka audit consumer");
+
+        loop {
+            let messages = self.next_batch().await;
+            if messages.is_empty() {
+                continue;
+            }
+
+            let records: Vec<AuditRecord> = messages.iter().filter_map(parse_record).collect();
+            self.insert_with_retry(&records).await;
+
+            match kafka_client::next_offsets(&messages) {
+                Ok(offsets) => {
+                    if let Err(e) = self.consumer.inner().commit(&offsets, CommitMode::Async) {
+                        // Uncommitted records are delivered again and collapsed
+                        tracing::error!("Failed to commit audit offsets: {}", e);
+                    }
+                }
+                Err(e) => tracing::error!("Failed to build audit offsets: {}", e),
+            }
+
+            tracing::debug!("Stored {} audit records", records.len());
+        }
+    }
+
+    /// Wait for a record, then gather whatever else arrives within `BATCH_LINGER`
+    async fn next_batch(&self) -> Vec<BorrowedMessage<'_>> {
+        let consumer = self.consumer.inner();
+        let mut messages = Vec::new();
+
+        match consumer.recv().await {
+            Ok(message) => messages.push(message),
+            Err(e) => {
+                tracing::error!("Kafka audit consumer error: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                return messages;
+            }
+        }
+
+        let deadline = tokio::time::Instant::now() + BATCH_LINGER;
+        while messages.len() < BATCH_SIZE {
+            match tokio::time::timeout_at(deadline, consumer.recv()).await {
+                Ok(Ok(message)) => messages.push(message),
+                Ok(Err(e)) => {
+                    tracing::error!("Kafka audit consumer error: {}", e);
+                    break;
+                }
+                Err(_) => break,
+            }
+        }
+
+        for message in &messages {
+            kafka_client::record_consumed(message);
+        }
+        messages
+    }
+
+    async fn insert_with_retry(&self, records: &[AuditRecord]) {
+        let mut delay = RETRY_DELAY;
+        while let Err(e) = self.clickhouse.insert_json("audit_events", records).await {
+            tracing::error!(
+                "Failed to store {} audit records, retrying in {}s: {}",
+                records.len(),
+                delay.as_secs(),
+                e
+            );
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_RETRY_DELAY);
+        }
+    }
+}
+
+/// Audit record carried by a message. A payload that is not an audit event
+/// can never be stored, so it is logged with its position and passed over.
+fn parse_record<M: Message>(message: &M) -> Option<AuditRecord> {
+    let parsed = match message.payload_view::<str>() {
+        Some(Ok(payload)) => {
+            serde_json::from_str::<AuditRecordedEvent>(payload).map_err(|e| e.to_string())
+        }
+        Some(Err(e)) => Err(e.to_string()),
+        None => Err("empty payload".to_string()),
+    };
+
+    match parsed {
+        Ok(event) => Some(to_record(event)),
+        Err(e) => {
+            tracing::error!(
+                "Skipping malformed audit event at {}/{}@{}: {}",
+                message.topic(),
+                message.partition(),
+                message.offset(),
+                e
+            );
+            None
+        }
+    }
+}
+
+fn to_record(event: AuditRecordedEvent) -> AuditRecord {
+    let data = event.data;
+
+    AuditRecord {
+        event_id: event.event_id,
+        occurred_at: event.timestamp,
+        user_id: data.user_id,
+        role: data.role.map(|role| role.to_string()),
+        api_key_id: data.api_key_id,
+        restaurant_id: data.restaurant_id,
+        method: data.method,
+        path: data.path,
+        status: data.status,
+        latency_ms: data.latency_ms,
+        request_id: data.request_id,
+    }
+}
@@ -27,9 +27,17 @@ impl EventConsumer {
         loop {
             match self.consumer.inner().recv().await {
                 Ok(message) => {
+                    kafka_client::record_consumed(&message);
+
                     if let Some(payload) = message.payload() {
                         if let Ok(payload_str) = std::str::from_utf8(payload) {
-                            if let Err(e) = self.handle_event_str(payload_str).await {
+                            let result = kafka_client::handle_in_context(
+                                &message,
+                                self.handle_event_str(payload_str),
+                            )
+                            .await;
+
+                            if let Err(e) = result {
                                 tracing::error!("Failed to handle event: {}", e);
                             }
                         }
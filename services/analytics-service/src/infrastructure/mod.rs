@@ -1,6 +1,8 @@
+pub mod audit_consumer;
 pub mod clickhouse_client;
 pub mod event_consumer;
 
+pub use audit_consumer::AuditConsumer;
 pub use clickhouse_client::ClickHouseClient;
 pub use event_consumer::EventConsumer;
 
@@ -7,7 +7,7 @@ mod presentation;
 use actix_web::{middleware::Logger, web, App, HttpServer};
 use application::AnalyticsService;
 use config::Config;
-use infrastructure::{ClickHouseClient, EventConsumer};
+use infrastructure::{AuditConsumer, ClickHouseClient, EventConsumer};
 use kafka_client::consumer::KafkaConsumer;
 use std::sync::Arc;
 
@@ -53,6 +53,19 @@ async fn main() -> std::io::Result<()> {
     });
     tracing::info!("Event consumer started");
 
+    // Store the gateway's audit trail
+    let audit_consumer_raw = kafka_client::create_manual_commit_consumer(
+        &config.kafka.brokers,
+        &config.kafka.audit_group_id,
+        &["audit.events"],
+    )
+    .expect("Failed to create Kafka audit consumer");
+    let audit_consumer = AuditConsumer::new(KafkaConsumer::new(audit_consumer_raw), clickhouse.clone());
+    tokio::spawn(async move {
+        audit_consumer.start().await;
+    });
+    tracing::info!("Audit consumer started");
+
     // Start HTTP server
     let server_host = config.server.host.clone();
     let server_port = config.server.port;
@@ -60,10 +73,18 @@ async fn main() -> std::io::Result<()> {
 
     tracing::info!("Starting HTTP server on {}", bind_address);
 
+    let api_doc = web::Data::new(presentation::api_doc::api_doc());
+
     HttpServer::new(move || {
         App::new()
             .wrap(Logger::default())
+            .wrap(tracing_actix_web::TracingLogger::<correlation::RequestIdRootSpan>::new())
+            .wrap(metrics::MetricsMiddleware::new("analytics-service"))
+            .wrap(correlation::RequestIdMiddleware)
             .app_data(web::Data::new(analytics_service.clone()))
+            .route("/metrics", web::get().to(metrics::metrics_handler))
+            .app_data(api_doc.clone())
+            .route("/openapi.json", web::get().to(openapi::openapi_handler))
             .configure(presentation::configure_routes)
     })
     .bind(&bind_address)?
@@ -0,0 +1,22 @@
+use openapi::ServiceDefaults;
+use utoipa::OpenApi;
+
+use super::handlers;
+
+/// OpenAPI document of the public routes, served at `/openapi.json`. The
+/// audit log is left out: operators query it through the gateway.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        handlers::get_top_foods,
+        handlers::get_revenue,
+        handlers::get_orders_by_status,
+        handlers::get_restaurant_top_foods,
+    ),
+    modifiers(&ServiceDefaults)
+)]
+pub struct ApiDoc;
+
+pub fn api_doc() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
@@ -1,10 +1,14 @@
 use crate::application::AnalyticsService;
+use crate::domain::{OrdersByStatus, RevenueSummary, TopFood};
 use actix_web::{web, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::IntoParams;
 use uuid::Uuid;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct TopFoodsQuery {
     #[serde(default = "default_limit")]
     pub limit: usize,
@@ -21,6 +25,25 @@ pub struct RestaurantTopFoodsQuery {
     pub limit: usize,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    pub user_id: Option<Uuid>,
+    pub restaurant_id: Option<Uuid>,
+    /// Inclusive start of the time range
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive end of the time range
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default = "default_audit_limit")]
+    pub limit: usize,
+}
+
+fn default_audit_limit() -> usize {
+    100
+}
+
+/// Most audit records returned by one query
+const MAX_AUDIT_LIMIT: usize = 1000;
+
 #[derive(Debug, Serialize)]
 pub struct HealthResponse {
     pub status: String,
@@ -36,6 +59,16 @@ pub async fn health_check() -> impl Responder {
 }
 
 /// GET /api/analytics/top-foods?limit=10
+#[utoipa::path(
+    get,
+    path = "/api/analytics/top-foods",
+    summary = "Best-selling foods across all restaurants",
+    params(TopFoodsQuery),
+    responses(
+        (status = 200, description = "Foods by quantity sold", body = Vec<TopFood>),
+    ),
+    security(("session" = []))
+)]
 pub async fn get_top_foods(
     service: web::Data<Arc<AnalyticsService>>,
     query: web::Query<TopFoodsQuery>,
@@ -52,6 +85,15 @@ pub async fn get_top_foods(
 }
 
 /// GET /api/analytics/revenue
+#[utoipa::path(
+    get,
+    path = "/api/analytics/revenue",
+    summary = "Revenue summary",
+    responses(
+        (status = 200, description = "Revenue summary", body = RevenueSummary),
+    ),
+    security(("session" = []))
+)]
 pub async fn get_revenue(service: web::Data<Arc<AnalyticsService>>) -> impl Responder {
     match service.get_revenue_summary().await {
         Ok(summary) => HttpResponse::Ok().json(summary),
@@ -65,6 +107,15 @@ pub async fn get_revenue(service: web::Data<Arc<AnalyticsService>>) -> impl Resp
 }
 
 /// GET /api/analytics/orders-by-status
+#[utoipa::path(
+    get,
+    path = "/api/analytics/orders-by-status",
+    summary = "Order counts by status",
+    responses(
+        (status = 200, description = "Counts by status", body = Vec<OrdersByStatus>),
+    ),
+    security(("session" = []))
+)]
 pub async fn get_orders_by_status(service: web::Data<Arc<AnalyticsService>>) -> impl Responder {
     match service.get_orders_by_status().await {
         Ok(orders) => HttpResponse::Ok().json(orders),
@@ -78,6 +129,19 @@ pub async fn get_orders_by_status(service: web::Data<Arc<AnalyticsService>>) ->
 }
 
 /// GET /api/analytics/restaurants/{restaurant_id}/top-foods?limit=10
+#[utoipa::path(
+    get,
+    path = "/api/analytics/restaurants/{restaurant_id}/top-foods",
+    summary = "Best-selling foods of a restaurant",
+    params(
+        ("restaurant_id" = Uuid, Path, description = "Restaurant id"),
+        TopFoodsQuery,
+    ),
+    responses(
+        (status = 200, description = "Foods by quantity sold", body = Vec<TopFood>),
+    ),
+    security(("session" = []))
+)]
 pub async fn get_restaurant_top_foods(
     service: web::Data<Arc<AnalyticsService>>,
     path: web::Path<Uuid>,
@@ -96,6 +160,41 @@ pub async fn get_restaurant_top_foods(
     }
 }
 
+/// GET /api/analytics/audit?user_id=&restaurant_id=&from=&to=&limit=100
+pub async fn get_audit_records(
+    service: web::Data<Arc<AnalyticsService>>,
+    query: web::Query<AuditQuery>,
+) -> impl Responder {
+    let query = query.into_inner();
+
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from >= to {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "'from' must be before 'to'"
+            }));
+        }
+    }
+
+    match service
+        .get_audit_records(
+            query.user_id,
+            query.restaurant_id,
+            query.from,
+            query.to,
+            query.limit.min(MAX_AUDIT_LIMIT),
+        )
+        .await
+    {
+        Ok(records) => HttpResponse::Ok().json(records),
+        Err(e) => {
+            tracing::error!("Failed to get audit records: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to get audit records"
+            }))
+        }
+    }
+}
+
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/api")
@@ -105,6 +204,7 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .route("/top-foods", web::get().to(get_top_foods))
                     .route("/revenue", web::get().to(get_revenue))
                     .route("/orders-by-status", web::get().to(get_orders_by_status))
+                    .route("/audit", web::get().to(get_audit_records))
                     .route(
                         "/restaurants/{restaurant_id}/top-foods",
                         web::get().to(get_restaurant_top_foods),
@@ -0,0 +1,15 @@
+-- Create API keys table in auth schema
+CREATE TABLE IF NOT EXISTS auth.api_keys (
+    id UUID PRIMARY KEY,
+    owner_id UUID NOT NULL REFERENCES auth.users(id),
+    restaurant_id UUID NOT NULL,
+    role TEXT NOT NULL CHECK (role IN ('rest', 'kitch')),
+    name VARCHAR(100) NOT NULL,
+    key_prefix VARCHAR(16) NOT NULL,
+    key_hash CHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    revoked_at TIMESTAMPTZ
+);
+
+-- Create index on owner_id for listing an owner's keys
+CREATE INDEX IF NOT EXISTS idx_api_keys_owner ON auth.api_keys(owner_id);
@@ -1,46 +1,94 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use error_handling::{AppError, AppResult};
-use models::user::{AuthResponse, RequestOtpRequest, Session, VerifyOtpRequest};
+use identity::Identity;
+use models::events::{LoginFailureReason, UserDeactivatedData, UserLoginFailedData};
+use models::user::{
+    ApiKey, AuthResponse, CreateApiKeyRequest, CreateApiKeyResponse, RefreshRequest,
+    RequestOtpRequest, Session, SessionInfo, SessionInvalidation, SessionScope, User, UserRole,
+    VerifyOtpRequest,
+};
+use uuid::Uuid;
 
-use crate::config::Config;
-use crate::domain::generate_otp;
-use crate::infrastructure::{EmailService, EventPublisher, SessionStore, UserRepository};
+use crate::config::{Config, LoginConfig, OtpConfig};
+use crate::domain::{dormant_action, generate_api_key, generate_otp, DormantAction, OtpHasher};
+use crate::infrastructure::session_store::{LoginMetadata, OtpThrottle, RefreshRedemption};
+use crate::infrastructure::{
+    ApiKeyRepository, EmailService, EventPublisher, RestaurantClient, SessionStore, UserRepository,
+};
 
 pub struct AuthService {
     user_repo: UserRepository,
+    api_key_repo: ApiKeyRepository,
     session_store: SessionStore,
     email_service: EmailService,
     event_publisher: EventPublisher,
-    otp_expiry_seconds: u64,
-    otp_length: usize,
+    restaurant_client: RestaurantClient,
+    otp: OtpConfig,
+    otp_hasher: OtpHasher,
+    login: LoginConfig,
+    session_expiry_seconds: u64,
 }
 
 impl AuthService {
     pub fn new(
         user_repo: UserRepository,
+        api_key_repo: ApiKeyRepository,
         session_store: SessionStore,
         email_service: EmailService,
         event_publisher: EventPublisher,
+        restaurant_client: RestaurantClient,
         config: &Config,
     ) -> Self {
         Self {
             user_repo,
+            api_key_repo,
             session_store,
             email_service,
             event_publisher,
-            otp_expiry_seconds: config.otp.expiry_seconds,
-            otp_length: config.otp.length,
+            restaurant_client,
+            otp: config.otp.clone(),
+            otp_hasher: OtpHasher::new(&config.otp.pepper),
+            login: config.login.clone(),
+            session_expiry_seconds: config.session.expiry_seconds,
         }
     }
 
     /// Request OTP - Generate and send OTP to user's email
-    pub async fn request_otp(&mut self, request: RequestOtpRequest) -> AppResult<()> {
+    pub async fn request_otp(
+        &mut self,
+        request: RequestOtpRequest,
+        client_ip: Option<String>,
+    ) -> AppResult<()> {
+        // A locked email gets a fresh code only once the lockout ends
+        self.check_login_lock(&request.email, None).await?;
+
+        // Limit how often codes are emailed to an address or for a client
+        let throttle = self
+            .session_store
+            .reserve_otp_send(&request.email, client_ip.as_deref(), &self.otp)
+            .await?;
+
+        if let Some(throttle) = throttle {
+            tracing::warn!("OTP request for {} throttled: {:?}", request.email, throttle);
+            return Err(match throttle {
+                OtpThrottle::Cooldown(retry_after) => AppError::TooManyRequests {
+                    message: "A code was sent recently, wait before requesting another".to_string(),
+                    retry_after,
+                },
+                OtpThrottle::Quota(retry_after) => AppError::TooManyRequests {
+                    message: "Daily limit of codes reached".to_string(),
+                    retry_after,
+                },
+            });
+        }
+
         // Generate OTP
-        let otp = generate_otp(self.otp_length);
+        let otp = generate_otp(self.otp.length);
 
-        // Store OTP in Redis with expiry
+        // Store a hash of the OTP in Redis with expiry
+        let otp_hash = self.otp_hasher.hash(&request.email, &otp);
         self.session_store
-            .store_otp(&request.email, &otp, self.otp_expiry_seconds)
+            .store_otp(&request.email, &otp_hash, self.otp.expiry_seconds)
             .await?;
 
         // Clone data for background task
@@ -65,15 +113,47 @@ impl AuthService {
     }
 
     /// Verify OTP - Validate OTP and create session
-    pub async fn verify_otp(&mut self, request: VerifyOtpRequest) -> AppResult<AuthResponse> {
+    pub async fn verify_otp(
+        &mut self,
+        request: VerifyOtpRequest,
+        client_ip: Option<String>,
+        user_agent: Option<String>,
+    ) -> AppResult<AuthResponse> {
+        // Locked emails and addresses cannot spend guesses
+        self.check_login_lock(&request.email, client_ip.as_deref()).await?;
+
         // Retrieve and delete OTP from Redis (one-time use)
         let stored_otp = self.session_store.get_and_delete_otp(&request.email).await?;
 
         // Verify OTP matches
-        if stored_otp != request.code {
-            return Err(AppError::Unauthorized("Invalid OTP code".to_string()));
+        let failure = match stored_otp {
+            None => Some(LoginFailureReason::CodeNotFound),
+            Some(otp_hash) if !self.otp_hasher.verify(&request.email, &request.code, &otp_hash) => {
+                Some(LoginFailureReason::InvalidCode)
+            }
+            Some(_) => None,
+        };
+
+        if let Some(reason) = failure {
+            if let Some(retry_after) = self
+                .record_login_failure(&request.email, client_ip.clone(), reason)
+                .await?
+            {
+                return Err(login_locked(retry_after));
+            }
+
+            return Err(match reason {
+                LoginFailureReason::InvalidCode => AppError::Unauthorized("Invalid OTP code".to_string()),
+                LoginFailureReason::CodeNotFound => {
+                    AppError::BadRequest("OTP not found or expired".to_string())
+                }
+            });
         }
 
+        self.session_store
+            .clear_login_failures(&email_subject(&request.email))
+            .await?;
+
         // Find or create user
         let user = match self.user_repo.find_by_email(&request.email).await? {
             Some(mut user) => {
@@ -95,41 +175,397 @@ impl AuthService {
             ));
         }
 
-        // Create session
+        // Publish login success event
+        self.event_publisher
+            .publish_login_success(user.id, user.email.clone(), user.role.clone())
+            .await?;
+
+        tracing::info!("User logged in: {} with role: {:?}", user.email, user.role);
+
+        // Each login starts a new refresh token family
+        let login = LoginMetadata {
+            user_id: user.id,
+            created_at: Utc::now(),
+            user_agent,
+            ip_address: client_ip,
+        };
+        self.start_session(user, Uuid::new_v4(), &login).await
+    }
+
+    /// Exchange a refresh token for a new session and refresh token. Reusing
+    /// a rotated token revokes every token and session of its login.
+    pub async fn refresh(&mut self, request: RefreshRequest) -> AppResult<AuthResponse> {
+        let token = match self.session_store.redeem_refresh_token(&request.refresh_token).await? {
+            RefreshRedemption::Valid(token) => token,
+            RefreshRedemption::Reused { family_id } => {
+                tracing::warn!("Refresh token reused, revoking family {}", family_id);
+                self.session_store.revoke_refresh_family(family_id).await?;
+                return Err(AppError::Unauthorized(
+                    "Refresh token was already used; please log in again".to_string(),
+                ));
+            }
+            RefreshRedemption::Unknown => {
+                return Err(AppError::Unauthorized("Invalid or expired refresh token".to_string()));
+            }
+        };
+
+        let user = match self.user_repo.find_by_id(token.login.user_id).await? {
+            Some(user) if user.is_active => user,
+            _ => {
+                self.session_store.revoke_refresh_family(token.family_id).await?;
+                return Err(AppError::Forbidden("User account is deactivated".to_string()));
+            }
+        };
+
+        // Each family holds a single live session
+        self.session_store.delete_session(&token.session_id).await?;
+
+        tracing::debug!("Session refreshed for user: {}", user.email);
+        self.start_session(user, token.family_id, &token.login).await
+    }
+
+    /// Create a session with the next refresh token of `family_id`
+    async fn start_session(
+        &mut self,
+        user: User,
+        family_id: Uuid,
+        login: &LoginMetadata,
+    ) -> AppResult<AuthResponse> {
         let session = Session {
             user_id: user.id,
             email: user.email.clone(),
             role: user.role.clone(),
             created_at: Utc::now(),
+            scope: None,
         };
 
         let session_id = self.session_store.store_session(&session).await?;
-
-        // Publish login success event
-        self.event_publisher
-            .publish_login_success(user.id, user.email.clone(), user.role.clone())
+        let refresh_token = self
+            .session_store
+            .issue_refresh_token(family_id, login, &session_id)
             .await?;
 
-        tracing::info!("User logged in: {} with role: {:?}", user.email, user.role);
-
         Ok(AuthResponse {
             token: session_id,
+            expires_in: self.session_expiry_seconds,
+            refresh_token,
             user_id: user.id,
             email: user.email,
             role: user.role,
         })
     }
 
-    /// Logout - Delete session
-    pub async fn logout(&mut self, session_id: &str) -> AppResult<()> {
-        self.session_store.delete_session(session_id).await?;
+    /// Reject the attempt while the email or client IP is locked out
+    async fn check_login_lock(&mut self, email: &str, client_ip: Option<&str>) -> AppResult<()> {
+        let mut subjects = vec![email_subject(email)];
+        subjects.extend(client_ip.map(ip_subject));
+
+        for subject in subjects {
+            if let Some(retry_after) = self.session_store.login_locked_for(&subject).await? {
+                return Err(login_locked(retry_after));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Count a failed verification against the email and client IP, locking
+    /// either out once it reaches its limit. Returns the lockout in seconds
+    /// if this failure caused one.
+    async fn record_login_failure(
+        &mut self,
+        email: &str,
+        client_ip: Option<String>,
+        reason: LoginFailureReason,
+    ) -> AppResult<Option<u64>> {
+        let window = self.login.failure_window_seconds;
+        let mut subjects = vec![(email_subject(email), self.login.max_failures)];
+        subjects.extend(client_ip.as_deref().map(|ip| (ip_subject(ip), self.login.max_ip_failures)));
+
+        let mut failed_attempts = 0;
+        let mut locked_for: Option<u64> = None;
+
+        for (subject, limit) in subjects {
+            let failures = self.session_store.record_login_failure(&subject, window).await?;
+            if failed_attempts == 0 {
+                failed_attempts = failures;
+            }
+
+            if failures >= limit {
+                let seconds = self
+                    .session_store
+                    .lock_login(&subject, self.login.lockout_seconds, self.login.max_lockout_seconds)
+                    .await?;
+                tracing::warn!("Login locked for {} for {}s after {} failures", subject, seconds, failures);
+                locked_for = Some(locked_for.unwrap_or(0).max(seconds));
+            }
+        }
+
+        let data = UserLoginFailedData {
+            email: email.to_string(),
+            ip_address: client_ip,
+            reason,
+            failed_attempts,
+            locked_until: locked_for.map(|seconds| Utc::now() + chrono::Duration::seconds(seconds as i64)),
+        };
+
+        // The failure is already counted; a lost event must not change the response
+        if let Err(e) = self.event_publisher.publish_login_failed(data).await {
+            tracing::error!("Failed to publish login failure for {}: {}", email, e);
+        }
+
+        Ok(locked_for)
+    }
+
+    /// Logout - End the session's login, so its refresh token stops working too
+    pub async fn logout(&mut self, session_id: &str, refresh_token: Option<&str>) -> AppResult<()> {
+        self.session_store.end_login(session_id).await?;
+        if let Some(refresh_token) = refresh_token {
+            self.session_store.revoke_refresh_token(refresh_token).await?;
+        }
         tracing::info!("User logged out, session: {}", session_id);
         Ok(())
     }
 
+    /// List the caller's logins, newest first
+    pub async fn list_sessions(&mut self, identity: &Identity) -> AppResult<Vec<SessionInfo>> {
+        let logins = self.session_store.list_user_logins(identity.user_id).await?;
+
+        let mut sessions: Vec<SessionInfo> = logins
+            .into_iter()
+            .map(|login| SessionInfo {
+                id: login.family_id,
+                created_at: login.login.created_at,
+                last_seen_at: login.last_seen_at.unwrap_or(login.login.created_at),
+                user_agent: login.login.user_agent,
+                ip_address: login.login.ip_address,
+                current: login.session_id == identity.session_id,
+            })
+            .collect();
+
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.created_at));
+        Ok(sessions)
+    }
+
+    /// Revoke one of the user's logins: its session and refresh token
+    pub async fn revoke_session(&mut self, user_id: Uuid, login_id: Uuid) -> AppResult<()> {
+        if self.session_store.refresh_family_owner(login_id).await? != Some(user_id) {
+            return Err(AppError::NotFound(format!("Session not found: {}", login_id)));
+        }
+
+        self.session_store.revoke_refresh_family(login_id).await?;
+
+        tracing::info!("Session {} revoked for user {}", login_id, user_id);
+        Ok(())
+    }
+
+    /// Log the user out everywhere, returning how many logins were revoked
+    pub async fn revoke_all_sessions(&mut self, user_id: Uuid) -> AppResult<usize> {
+        let revoked = self.session_store.revoke_user_logins(user_id).await?;
+
+        tracing::info!("Revoked all {} sessions of user {}", revoked, user_id);
+        Ok(revoked)
+    }
+
+    /// Active users who have not logged in with a code for `inactive_days`
+    pub async fn find_dormant_candidates(&self, inactive_days: u32) -> AppResult<Vec<User>> {
+        if inactive_days == 0 {
+            return Err(AppError::BadRequest("inactive_days must be at least 1".to_string()));
+        }
+
+        self.user_repo.find_inactive_users(inactive_days as i64 * 24).await
+    }
+
+    /// Deactivate a dormant candidate unless it still has a live login, which
+    /// refreshes keep alive without a new code. Its sessions and API keys are
+    /// revoked and `user.deactivated` is published. Returns whether the user
+    /// is (or on a dry run, would be) deactivated.
+    pub async fn deactivate_if_dormant(&mut self, user: &User, dry_run: bool) -> AppResult<bool> {
+        let has_live_login = !self.session_store.list_user_logins(user.id).await?.is_empty();
+
+        match dormant_action(has_live_login, dry_run) {
+            DormantAction::Skip => return Ok(false),
+            DormantAction::Report => {
+                tracing::info!("Dry run: would deactivate dormant user {}", user.email);
+                return Ok(true);
+            }
+            DormantAction::Deactivate => {}
+        }
+
+        self.user_repo.deactivate(user.id).await?;
+
+        // Refreshes are refused from here on; end what is already issued
+        self.session_store.revoke_user_logins(user.id).await?;
+        for (api_key_id, key_hash) in self.api_key_repo.revoke_all(user.id).await? {
+            self.session_store.delete_api_key(&key_hash, api_key_id).await?;
+        }
+        self.session_store
+            .publish_invalidation(&SessionInvalidation::User { user_id: user.id })
+            .await;
+
+        let data = UserDeactivatedData {
+            user_id: user.id,
+            email: user.email.clone(),
+            deactivated_at: Utc::now(),
+        };
+        // The account is already deactivated; a lost event must not undo that
+        if let Err(e) = self.event_publisher.publish_user_deactivated(data).await {
+            tracing::error!("Failed to publish deactivation of {}: {}", user.email, e);
+        }
+
+        tracing::info!("Deactivated dormant user {}", user.email);
+        Ok(true)
+    }
+
     /// Validate session - Check if session exists and is valid
     pub async fn validate_session(&mut self, session_id: &str) -> AppResult<Session> {
         self.session_store.get_session(session_id).await
     }
+
+    /// Create an API key bound to one of the owner's restaurants
+    pub async fn create_api_key(
+        &mut self,
+        owner: &Identity,
+        request: CreateApiKeyRequest,
+    ) -> AppResult<CreateApiKeyResponse> {
+        if owner.role != UserRole::Rest {
+            return Err(AppError::Forbidden(
+                "Only restaurant owners can create API keys".to_string(),
+            ));
+        }
+
+        if request.role == UserRole::User {
+            return Err(AppError::BadRequest(
+                "API keys must have the rest or kitch role".to_string(),
+            ));
+        }
+
+        match self.restaurant_client.get_owner_id(request.restaurant_id).await? {
+            Some(owner_id) if owner_id == owner.user_id => {}
+            Some(_) => {
+                return Err(AppError::Forbidden(
+                    "You do not own this restaurant".to_string(),
+                ))
+            }
+            None => {
+                return Err(AppError::NotFound(format!(
+                    "Restaurant not found: {}",
+                    request.restaurant_id
+                )))
+            }
+        }
+
+        let (key, key_prefix) = generate_api_key();
+        let key_hash = identity::hash_api_key(&key);
+
+        let api_key = self
+            .api_key_repo
+            .create(
+                owner.user_id,
+                request.restaurant_id,
+                &request.role,
+                &request.name,
+                &key_prefix,
+                &key_hash,
+            )
+            .await?;
+
+        let session = api_key_session(
+            api_key.id,
+            owner.user_id,
+            &owner.email,
+            api_key.restaurant_id,
+            &api_key.role,
+            api_key.created_at,
+        );
+
+        // The key is never shown again, so don't leave an unusable one active
+        if let Err(e) = self.session_store.store_api_key(&key_hash, &session).await {
+            self.api_key_repo.revoke(api_key.id, owner.user_id).await?;
+            return Err(e);
+        }
+
+        tracing::info!(
+            "API key {} created for restaurant {} with role: {:?}",
+            api_key.id,
+            api_key.restaurant_id,
+            api_key.role
+        );
+
+        Ok(CreateApiKeyResponse { api_key, key })
+    }
+
+    /// List the owner's API keys, including revoked ones
+    pub async fn list_api_keys(&self, owner_id: Uuid) -> AppResult<Vec<ApiKey>> {
+        self.api_key_repo.find_by_owner(owner_id).await
+    }
+
+    /// Revoke one of the owner's API keys
+    pub async fn revoke_api_key(&mut self, owner_id: Uuid, api_key_id: Uuid) -> AppResult<()> {
+        let key_hash = self
+            .api_key_repo
+            .revoke(api_key_id, owner_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("API key not found: {}", api_key_id)))?;
+
+        self.session_store.delete_api_key(&key_hash, api_key_id).await?;
+
+        tracing::info!("API key revoked: {}", api_key_id);
+        Ok(())
+    }
+
+    /// Republish every active API key to Redis, e.g. after Redis lost its data
+    pub async fn sync_api_keys(&mut self) -> AppResult<usize> {
+        let api_keys = self.api_key_repo.find_active().await?;
+
+        for api_key in &api_keys {
+            let session = api_key_session(
+                api_key.id,
+                api_key.owner_id,
+                &api_key.owner_email,
+                api_key.restaurant_id,
+                &api_key.role,
+                api_key.created_at,
+            );
+            self.session_store.store_api_key(&api_key.key_hash, &session).await?;
+        }
+
+        Ok(api_keys.len())
+    }
 }
 
+fn email_subject(email: &str) -> String {
+    format!("email:{}", email)
+}
+
+fn ip_subject(ip: &str) -> String {
+    format!("ip:{}", ip)
+}
+
+fn login_locked(retry_after: u64) -> AppError {
+    AppError::TooManyRequests {
+        message: "Too many failed login attempts, request a new code later".to_string(),
+        retry_after,
+    }
+}
+
+/// Session the gateway synthesises for requests made with an API key
+fn api_key_session(
+    api_key_id: Uuid,
+    owner_id: Uuid,
+    owner_email: &str,
+    restaurant_id: Uuid,
+    role: &UserRole,
+    created_at: DateTime<Utc>,
+) -> Session {
+    Session {
+        user_id: owner_id,
+        email: owner_email.to_string(),
+        role: role.clone(),
+        created_at,
+        scope: Some(SessionScope {
+            api_key_id,
+            restaurant_id,
+        }),
+    }
+}
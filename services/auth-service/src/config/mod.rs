@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use std::net::IpAddr;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
@@ -8,7 +9,12 @@ pub struct Config {
     pub kafka: KafkaConfig,
     pub smtp: SmtpConfig,
     pub otp: OtpConfig,
+    pub login: LoginConfig,
     pub session: SessionConfig,
+    pub restaurant_service: RestaurantServiceConfig,
+    pub identity: IdentityConfig,
+    pub network: NetworkConfig,
+    pub internal: InternalConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -45,11 +51,60 @@ pub struct SmtpConfig {
 pub struct OtpConfig {
     pub expiry_seconds: u64,
     pub length: usize,
+    /// Minimum time between codes sent to one email
+    pub resend_cooldown_seconds: u64,
+    /// Codes sent per email in any 24 hours
+    pub daily_limit_per_email: u32,
+    /// Codes requested per client IP in any 24 hours
+    pub daily_limit_per_ip: u32,
+    /// Server-side secret mixed into stored code hashes
+    pub pepper: String,
+}
+
+/// Failed OTP verification limits
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoginConfig {
+    /// Failures per email before it is locked
+    pub max_failures: u32,
+    /// Failures per client IP, across emails, before it is locked
+    pub max_ip_failures: u32,
+    /// Failures older than this are forgotten
+    pub failure_window_seconds: u64,
+    /// First lockout; each further lockout within a day doubles it
+    pub lockout_seconds: u64,
+    pub max_lockout_seconds: u64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct SessionConfig {
     pub expiry_seconds: u64,
+    /// Lifetime of a refresh token, renewed with each rotation
+    pub refresh_expiry_seconds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RestaurantServiceConfig {
+    pub base_url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdentityConfig {
+    pub signing_secret: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NetworkConfig {
+    /// Peers allowed to report the client address in `X-Forwarded-For`,
+    /// i.e. the gateway
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct InternalConfig {
+    /// Shared secret other services present in `X-Service-Token` to call
+    /// internal endpoints that change accounts; those endpoints are disabled
+    /// when unset
+    pub service_token: Option<String>,
 }
 
 impl Config {
@@ -91,11 +146,75 @@ impl Config {
             .parse()
             .unwrap_or(6);
 
+        let otp_resend_cooldown_seconds = std::env::var("OTP_RESEND_COOLDOWN_SECONDS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .unwrap_or(60);
+        let otp_daily_limit_per_email = std::env::var("OTP_DAILY_LIMIT_PER_EMAIL")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .unwrap_or(10);
+        let otp_daily_limit_per_ip = std::env::var("OTP_DAILY_LIMIT_PER_IP")
+            .unwrap_or_else(|_| "50".to_string())
+            .parse()
+            .unwrap_or(50);
+
+        let otp_pepper = std::env::var("OTP_PEPPER")
+            .expect("OTP_PEPPER must be set");
+
+        let login_max_failures = std::env::var("LOGIN_MAX_FAILURES")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5);
+        let login_max_ip_failures = std::env::var("LOGIN_MAX_IP_FAILURES")
+            .unwrap_or_else(|_| "20".to_string())
+            .parse()
+            .unwrap_or(20);
+        let login_failure_window_seconds = std::env::var("LOGIN_FAILURE_WINDOW_SECONDS")
+            .unwrap_or_else(|_| "900".to_string())
+            .parse()
+            .unwrap_or(900);
+        let login_lockout_seconds = std::env::var("LOGIN_LOCKOUT_SECONDS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .unwrap_or(60);
+        let login_max_lockout_seconds = std::env::var("LOGIN_MAX_LOCKOUT_SECONDS")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse()
+            .unwrap_or(3600);
+
         let session_expiry_seconds = std::env::var("SESSION_EXPIRY_SECONDS")
             .unwrap_or_else(|_| "7200".to_string())
             .parse()
             .unwrap_or(7200);
 
+        let refresh_expiry_seconds = std::env::var("REFRESH_TOKEN_EXPIRY_SECONDS")
+            .unwrap_or_else(|_| "2592000".to_string())
+            .parse()
+            .unwrap_or(2592000);
+
+        let restaurant_service_url = std::env::var("RESTAURANT_SERVICE_URL")
+            .unwrap_or_else(|_| "http://localhost:8002".to_string());
+
+        let identity_signing_secret = std::env::var("IDENTITY_SIGNING_SECRET")
+            .map_err(|_| config::ConfigError::NotFound("IDENTITY_SIGNING_SECRET".to_string()))?;
+
+        let trusted_proxies = std::env::var("TRUSTED_PROXY_IPS")
+            .unwrap_or_else(|_| "127.0.0.1,::1".to_string())
+            .split(',')
+            .map(str::trim)
+            .filter(|ip| !ip.is_empty())
+            .map(|ip| {
+                ip.parse().map_err(|_| {
+                    config::ConfigError::Message(format!("Invalid address in TRUSTED_PROXY_IPS: {}", ip))
+                })
+            })
+            .collect::<Result<Vec<IpAddr>, _>>()?;
+
+        let internal_service_token = std::env::var("INTERNAL_SERVICE_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty());
+
         Ok(Config {
             server: ServerConfig {
                 host: server_host,
@@ -120,9 +239,31 @@ impl Config {
             otp: OtpConfig {
                 expiry_seconds: otp_expiry_seconds,
                 length: otp_length,
+                resend_cooldown_seconds: otp_resend_cooldown_seconds,
+                daily_limit_per_email: otp_daily_limit_per_email,
+                daily_limit_per_ip: otp_daily_limit_per_ip,
+                pepper: otp_pepper,
+            },
+            login: LoginConfig {
+                max_failures: login_max_failures,
+                max_ip_failures: login_max_ip_failures,
+                failure_window_seconds: login_failure_window_seconds,
+                lockout_seconds: login_lockout_seconds,
+                max_lockout_seconds: login_max_lockout_seconds,
             },
             session: SessionConfig {
                 expiry_seconds: session_expiry_seconds,
+                refresh_expiry_seconds,
+            },
+            restaurant_service: RestaurantServiceConfig {
+                base_url: restaurant_service_url,
+            },
+            identity: IdentityConfig {
+                signing_secret: identity_signing_secret,
+            },
+            network: NetworkConfig { trusted_proxies },
+            internal: InternalConfig {
+                service_token: internal_service_token,
             },
         })
     }
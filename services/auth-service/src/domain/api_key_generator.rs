@@ -0,0 +1,35 @@
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+/// Marks a string as a RestMan API key
+const API_KEY_MARKER: &str = "rk";
+
+/// Generate a new API key, returning the key and its display prefix.
+///
+/// Keys look like `rk_<prefix>_<secret>`: the prefix identifies the key in
+/// listings and the secret carries 256 bits from the OS random generator.
+pub fn generate_api_key() -> (String, String) {
+    let mut prefix = [0u8; 4];
+    let mut secret = [0u8; 32];
+    OsRng.fill_bytes(&mut prefix);
+    OsRng.fill_bytes(&mut secret);
+
+    let prefix = hex::encode(prefix);
+    let key = format!("{}_{}_{}", API_KEY_MARKER, prefix, hex::encode(secret));
+    (key, prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_api_key_format() {
+        let (key, prefix) = generate_api_key();
+        assert!(key.starts_with(&format!("rk_{}_", prefix)));
+        assert_eq!(key.len(), "rk_".len() + 8 + 1 + 64);
+
+        let (other, _) = generate_api_key();
+        assert_ne!(key, other);
+    }
+}
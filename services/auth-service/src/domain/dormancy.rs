@@ -0,0 +1,37 @@
+/// What to do with an account found dormant by its last OTP login
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DormantAction {
+    /// A live login, kept alive by refreshes without a new code, means the
+    /// account is still in use
+    Skip,
+    /// Dry run: report the account, change nothing
+    Report,
+    Deactivate,
+}
+
+pub fn dormant_action(has_live_login: bool, dry_run: bool) -> DormantAction {
+    if has_live_login {
+        DormantAction::Skip
+    } else if dry_run {
+        DormantAction::Report
+    } else {
+        DormantAction::Deactivate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_login_skipped() {
+        assert_eq!(dormant_action(true, false), DormantAction::Skip);
+        assert_eq!(dormant_action(true, true), DormantAction::Skip);
+    }
+
+    #[test]
+    fn test_dry_run_only_reports() {
+        assert_eq!(dormant_action(false, true), DormantAction::Report);
+        assert_eq!(dormant_action(false, false), DormantAction::Deactivate);
+    }
+}
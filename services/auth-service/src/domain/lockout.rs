@@ -0,0 +1,20 @@
+/// Length of a login lockout: `base_seconds` for the first, doubling with
+/// each further lockout up to `max_seconds`
+pub fn lockout_seconds(base_seconds: u64, max_seconds: u64, lockouts: u32) -> u64 {
+    let factor = 1u64.checked_shl(lockouts.saturating_sub(1)).unwrap_or(u64::MAX);
+    base_seconds.saturating_mul(factor).min(max_seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockout_backoff() {
+        assert_eq!(lockout_seconds(60, 3600, 1), 60);
+        assert_eq!(lockout_seconds(60, 3600, 2), 120);
+        assert_eq!(lockout_seconds(60, 3600, 4), 480);
+        assert_eq!(lockout_seconds(60, 3600, 7), 3600);
+        assert_eq!(lockout_seconds(60, 3600, 100), 3600);
+    }
+}
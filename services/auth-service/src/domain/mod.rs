@@ -1,4 +1,13 @@
+pub mod api_key_generator;
+pub mod dormancy;
+pub mod lockout;
 pub mod otp_generator;
+pub mod otp_hasher;
+pub mod refresh_token;
 
+pub use api_key_generator::generate_api_key;
+pub use dormancy::{dormant_action, DormantAction};
+pub use lockout::lockout_seconds;
 pub use otp_generator::generate_otp;
-
+pub use otp_hasher::OtpHasher;
+pub use refresh_token::{generate_refresh_token, hash_refresh_token};
@@ -1,18 +1,30 @@
+use rand::rngs::OsRng;
 use rand::Rng;
 
-/// Generate a random OTP code with the specified length
+/// Generate a random OTP code with the specified length.
+///
+/// Digits come straight from the OS random generator; `gen_range` rejects
+/// out-of-range samples, so every digit is equally likely.
 pub fn generate_otp(length: usize) -> String {
-    let mut rng = rand::thread_rng();
-    let otp: String = (0..length)
-        .map(|_| rng.gen_range(0..10).to_string())
-        .collect();
-    otp
+    (0..length)
+        .map(|_| char::from(b'0' + OsRng.gen_range(0..10u8)))
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Chi-squared statistic of digit counts against a uniform distribution
+    fn chi_squared(counts: &[u64; 10]) -> f64 {
+        let total: u64 = counts.iter().sum();
+        let expected = total as f64 / 10.0;
+        counts
+            .iter()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum()
+    }
+
     #[test]
     fn test_generate_otp_length() {
         let otp = generate_otp(6);
@@ -25,6 +37,15 @@ mod tests {
         assert!(otp.chars().all(|c| c.is_numeric()));
     }
 
+    #[test]
+    fn test_generate_otp_ascii_digits() {
+        // Codes are typed on any keyboard, so only 0-9
+        for _ in 0..1_000 {
+            let otp = generate_otp(6);
+            assert!(otp.bytes().all(|b| b.is_ascii_digit()), "not ASCII digits: {}", otp);
+        }
+    }
+
     #[test]
     fn test_generate_otp_different() {
         let otp1 = generate_otp(6);
@@ -34,5 +55,31 @@ mod tests {
         assert_eq!(otp1.len(), 6);
         assert_eq!(otp2.len(), 6);
     }
-}
 
+    #[test]
+    fn test_generate_otp_uniform_digits() {
+        // 9 degrees of freedom; exceeded by chance with probability 1e-6
+        const CRITICAL: f64 = 44.81;
+
+        let mut overall = [0u64; 10];
+        let mut by_position = [[0u64; 10]; 6];
+
+        for _ in 0..20_000 {
+            for (position, digit) in generate_otp(6).bytes().enumerate() {
+                let digit = (digit - b'0') as usize;
+                overall[digit] += 1;
+                by_position[position][digit] += 1;
+            }
+        }
+
+        assert!(chi_squared(&overall) < CRITICAL, "digits are not uniform: {:?}", overall);
+        for (position, counts) in by_position.iter().enumerate() {
+            assert!(
+                chi_squared(counts) < CRITICAL,
+                "digit {} is not uniform: {:?}",
+                position,
+                counts
+            );
+        }
+    }
+}
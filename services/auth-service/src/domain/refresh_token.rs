@@ -0,0 +1,16 @@
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// Generate a refresh token: 256 bits from the OS random generator
+pub fn generate_refresh_token() -> String {
+    let mut secret = [0u8; 32];
+    OsRng.fill_bytes(&mut secret);
+    hex::encode(secret)
+}
+
+/// Hash under which a refresh token is stored. Tokens are long random
+/// secrets, so a fast unsalted hash is sufficient.
+pub fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
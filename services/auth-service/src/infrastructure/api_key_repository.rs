@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use error_handling::AppResult;
+use models::user::{ApiKey, UserRole};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Active API key with what is needed to publish it to Redis
+#[derive(Debug, sqlx::FromRow)]
+pub struct ActiveApiKey {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub owner_email: String,
+    pub restaurant_id: Uuid,
+    pub role: UserRole,
+    pub key_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct ApiKeyRepository {
+    pool: PgPool,
+}
+
+impl ApiKeyRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Create a new API key
+    pub async fn create(
+        &self,
+        owner_id: Uuid,
+        restaurant_id: Uuid,
+        role: &UserRole,
+        name: &str,
+        key_prefix: &str,
+        key_hash: &str,
+    ) -> AppResult<ApiKey> {
+        let api_key = sqlx::query_as::<_, ApiKey>(
+            r#"
+            INSERT INTO auth.api_keys (id, owner_id, restaurant_id, role, name, key_prefix, key_hash, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, owner_id, restaurant_id, role, name, key_prefix, created_at, revoked_at
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(owner_id)
+        .bind(restaurant_id)
+        .bind(role.to_string())
+        .bind(name)
+        .bind(key_prefix)
+        .bind(key_hash)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(api_key)
+    }
+
+    /// List an owner's API keys, newest first
+    pub async fn find_by_owner(&self, owner_id: Uuid) -> AppResult<Vec<ApiKey>> {
+        let api_keys = sqlx::query_as::<_, ApiKey>(
+            r#"
+            SELECT id, owner_id, restaurant_id, role, name, key_prefix, created_at, revoked_at
+            FROM auth.api_keys
+            WHERE owner_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(owner_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(api_keys)
+    }
+
+    /// Revoke one of an owner's keys, returning its hash if it was active
+    pub async fn revoke(&self, api_key_id: Uuid, owner_id: Uuid) -> AppResult<Option<String>> {
+        let key_hash = sqlx::query_scalar::<_, String>(
+            r#"
+            UPDATE auth.api_keys
+            SET revoked_at = $1
+            WHERE id = $2 AND owner_id = $3 AND revoked_at IS NULL
+            RETURNING key_hash
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(api_key_id)
+        .bind(owner_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(key_hash)
+    }
+
+    /// Revoke all of an owner's keys, returning the id and hash of each
+    pub async fn revoke_all(&self, owner_id: Uuid) -> AppResult<Vec<(Uuid, String)>> {
+        let revoked = sqlx::query_as::<_, (Uuid, String)>(
+            r#"
+            UPDATE auth.api_keys
+            SET revoked_at = $1
+            WHERE owner_id = $2 AND revoked_at IS NULL
+            RETURNING id, key_hash
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(owner_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(revoked)
+    }
+
+    /// Unrevoked keys of active owners
+    pub async fn find_active(&self) -> AppResult<Vec<ActiveApiKey>> {
+        let api_keys = sqlx::query_as::<_, ActiveApiKey>(
+            r#"
+            SELECT k.id, k.owner_id, u.email AS owner_email, k.restaurant_id, k.role, k.key_hash, k.created_at
+            FROM auth.api_keys k
+            JOIN auth.users u ON u.id = k.owner_id
+            WHERE k.revoked_at IS NULL AND u.is_active = true
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(api_keys)
+    }
+}
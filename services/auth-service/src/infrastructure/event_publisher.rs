@@ -1,6 +1,8 @@
 use error_handling::AppResult;
 use kafka_client::KafkaProducer;
-use models::events::{event_types, Event, UserLoginSuccessData};
+use models::events::{
+    event_types, Event, UserDeactivatedData, UserLoginFailedData, UserLoginSuccessData,
+};
 use uuid::Uuid;
 
 use models::user::UserRole;
@@ -36,5 +38,28 @@ impl EventPublisher {
         tracing::debug!("Published user.login_success event for user: {}", email);
         Ok(())
     }
-}
 
+    pub async fn publish_login_failed(&self, data: UserLoginFailedData) -> AppResult<()> {
+        let email = data.email.clone();
+        let event = Event::new(event_types::USER_LOGIN_FAILED.to_string(), data);
+
+        self.producer
+            .publish("user.events", Some(&email), &event)
+            .await?;
+
+        tracing::debug!("Published user.login_failed event for: {}", email);
+        Ok(())
+    }
+
+    pub async fn publish_user_deactivated(&self, data: UserDeactivatedData) -> AppResult<()> {
+        let email = data.email.clone();
+        let event = Event::new(event_types::USER_DEACTIVATED.to_string(), data);
+
+        self.producer
+            .publish("user.events", Some(&email), &event)
+            .await?;
+
+        tracing::debug!("Published user.deactivated event for: {}", email);
+        Ok(())
+    }
+}
@@ -1,10 +1,13 @@
+pub mod api_key_repository;
 pub mod email_service;
 pub mod event_publisher;
 pub mod repository;
+pub mod restaurant_client;
 pub mod session_store;
 
+pub use api_key_repository::ApiKeyRepository;
 pub use email_service::EmailService;
 pub use event_publisher::EventPublisher;
 pub use repository::UserRepository;
+pub use restaurant_client::RestaurantClient;
 pub use session_store::SessionStore;
-
@@ -105,7 +105,7 @@ impl UserRepository {
         Ok(())
     }
 
-    /// Find users who haven't verified in the last 2 hours
+    /// Find active users who haven't verified in the last `hours` hours
     pub async fn find_inactive_users(&self, hours: i64) -> AppResult<Vec<User>> {
         let cutoff_time = Utc::now() - chrono::Duration::hours(hours);
         
@@ -0,0 +1,62 @@
+use error_handling::AppError;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+struct RestaurantOwner {
+    owner_id: Uuid,
+}
+
+#[derive(Clone)]
+pub struct RestaurantClient {
+    client: Client,
+    base_url: String,
+}
+
+impl RestaurantClient {
+    pub fn new(client: Client, base_url: String) -> Self {
+        Self { client, base_url }
+    }
+
+    /// Owner of a restaurant, or `None` if it does not exist
+    pub async fn get_owner_id(&self, restaurant_id: Uuid) -> Result<Option<Uuid>, AppError> {
+        let url = format!("{}/api/restaurants/{}", self.base_url, restaurant_id);
+
+        let mut request = self.client.get(&url);
+
+        // Propagate the correlation id of the request being handled
+        if let Some(request_id) = correlation::current() {
+            request = request.header(correlation::REQUEST_ID_HEADER, request_id);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to call Restaurant Service: {}", e);
+                AppError::ExternalService(format!("Failed to fetch restaurant: {}", e))
+            })?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            tracing::error!("Restaurant Service returned error {}: {}", status, error_text);
+            return Err(AppError::ExternalService(format!(
+                "Restaurant Service error: {} - {}",
+                status, error_text
+            )));
+        }
+
+        let restaurant: RestaurantOwner = response.json().await.map_err(|e| {
+            tracing::error!("Failed to parse restaurant response: {}", e);
+            AppError::ExternalService(format!("Failed to parse restaurant: {}", e))
+        })?;
+
+        Ok(Some(restaurant.owner_id))
+    }
+}
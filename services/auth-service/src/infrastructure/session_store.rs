@@ -1,19 +1,154 @@
+use chrono::{DateTime, TimeZone, Utc};
 use error_handling::{AppError, AppResult};
-use models::user::Session;
+use models::user::{Session, SessionInvalidation, SESSION_INVALIDATION_CHANNEL};
 use redis::aio::ConnectionManager;
 use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::config::OtpConfig;
+use crate::domain::{generate_refresh_token, hash_refresh_token, lockout_seconds};
+
+/// How long past lockouts count towards the backoff
+const LOCKOUT_HISTORY_SECONDS: i64 = 24 * 60 * 60;
+
+/// Window of the OTP send quotas
+const OTP_QUOTA_WINDOW_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Checks the resend cooldown and every rolling quota, and only if all pass
+/// records the send. Returns `{0, 0}` when allowed, otherwise `{1, wait}` for
+/// the cooldown or `{2, wait}` for a quota, with `wait` in milliseconds.
+const OTP_SEND_SCRIPT: &str = r#"
+local now = tonumber(ARGV[1])
+local cooldown = tonumber(ARGV[2])
+local window = tonumber(ARGV[3])
+local member = ARGV[4]
+
+local remaining = redis.call('PTTL', KEYS[1])
+if remaining > 0 then
+    return {1, remaining}
+end
+
+local wait = 0
+for i = 2, #KEYS do
+    local limit = tonumber(ARGV[3 + i])
+    redis.call('ZREMRANGEBYSCORE', KEYS[i], 0, now - window)
+    if redis.call('ZCARD', KEYS[i]) >= limit then
+        local oldest = redis.call('ZRANGE', KEYS[i], 0, 0, 'WITHSCORES')
+        local reset = window
+        if oldest[2] then
+            reset = tonumber(oldest[2]) + window - now
+        end
+        wait = math.max(wait, reset)
+    end
+end
+if wait > 0 then
+    return {2, wait}
+end
+
+for i = 2, #KEYS do
+    redis.call('ZADD', KEYS[i], now, member)
+    redis.call('PEXPIRE', KEYS[i], window)
+end
+if cooldown > 0 then
+    redis.call('SET', KEYS[1], 1, 'PX', cooldown)
+end
+return {0, 0}
+"#;
+
+/// Redeems a refresh token and marks it used in one step, so of two
+/// concurrent redemptions exactly one gets the token and the other sees the
+/// reuse. Returns `{1, record}` for a live token, `{2, family_id}` for a used
+/// one and `{0, ""}` otherwise.
+const REFRESH_REDEEM_SCRIPT: &str = r#"
+local record = redis.call('GET', KEYS[1])
+if record then
+    redis.call('DEL', KEYS[1])
+    local family_id = cjson.decode(record)['family_id']
+    redis.call('SET', KEYS[2], family_id, 'EX', tonumber(ARGV[1]))
+    return {1, record}
+end
+
+local used = redis.call('GET', KEYS[2])
+if used then
+    return {2, used}
+end
+return {0, ''}
+"#;
+
+/// Why a code may not be sent yet, with the seconds to wait
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtpThrottle {
+    Cooldown(u64),
+    Quota(u64),
+}
+
+/// Where and when a login happened; kept across refreshes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginMetadata {
+    pub user_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+/// A refresh token as stored in Redis. Tokens rotated from one login form a
+/// family, which is revoked as a whole when a rotated token is reused.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshToken {
+    pub family_id: Uuid,
+    /// Session issued together with the token
+    pub session_id: String,
+    #[serde(flatten)]
+    pub login: LoginMetadata,
+}
+
+/// Current token and session of a refresh token family
+#[derive(Debug, Serialize, Deserialize)]
+struct RefreshFamily {
+    token_hash: String,
+    session_id: String,
+    #[serde(flatten)]
+    login: LoginMetadata,
+}
+
+/// A live login of a user, as found in their session index
+#[derive(Debug, Clone)]
+pub struct UserLogin {
+    pub family_id: Uuid,
+    /// Current session of the login
+    pub session_id: String,
+    pub login: LoginMetadata,
+    /// Last time the gateway loaded the current session
+    pub last_seen_at: Option<DateTime<Utc>>,
+}
+
+/// Outcome of redeeming a refresh token
+#[derive(Debug)]
+pub enum RefreshRedemption {
+    Valid(RefreshToken),
+    /// The token was already rotated: it has leaked or been replayed
+    Reused { family_id: Uuid },
+    /// Never issued, expired or revoked
+    Unknown,
+}
+
 pub struct SessionStore {
     redis: ConnectionManager,
     expiry_seconds: u64,
+    refresh_expiry_seconds: u64,
+    otp_send_script: redis::Script,
+    refresh_redeem_script: redis::Script,
 }
 
 impl SessionStore {
-    pub fn new(redis: ConnectionManager, expiry_seconds: u64) -> Self {
+    pub fn new(redis: ConnectionManager, expiry_seconds: u64, refresh_expiry_seconds: u64) -> Self {
         Self {
             redis,
             expiry_seconds,
+            refresh_expiry_seconds,
+            otp_send_script: redis::Script::new(OTP_SEND_SCRIPT),
+            refresh_redeem_script: redis::Script::new(REFRESH_REDEEM_SCRIPT),
         }
     }
 
@@ -60,20 +195,292 @@ impl SessionStore {
         let session_key = format!("session:{}", session_id);
         
         self.redis
-            .del::<_, ()>(&session_key)
+            .del::<_, ()>(&[
+                session_key,
+                format!("session_seen:{}", session_id),
+                format!("session_family:{}", session_id),
+            ])
             .await
             .map_err(|e| AppError::Redis(e.to_string()))?;
 
         tracing::debug!("Session deleted: {}", session_id);
+
+        self.publish_invalidation(&SessionInvalidation::Session {
+            session_id: session_id.to_string(),
+        })
+        .await;
+
         Ok(())
     }
 
-    /// Store OTP in Redis with TTL
-    pub async fn store_otp(&mut self, email: &str, otp: &str, expiry_seconds: u64) -> AppResult<()> {
+    /// Issue the next refresh token of a family for a new session, and list
+    /// the family in its user's session index
+    pub async fn issue_refresh_token(
+        &mut self,
+        family_id: Uuid,
+        login: &LoginMetadata,
+        session_id: &str,
+    ) -> AppResult<String> {
+        let token = generate_refresh_token();
+        let token_hash = hash_refresh_token(&token);
+        let index_key = format!("user_sessions:{}", login.user_id);
+
+        let record = RefreshToken {
+            family_id,
+            session_id: session_id.to_string(),
+            login: login.clone(),
+        };
+        let family = RefreshFamily {
+            token_hash: token_hash.clone(),
+            session_id: session_id.to_string(),
+            login: login.clone(),
+        };
+        let serialize_error = |e: serde_json::Error| {
+            AppError::Internal(format!("Failed to serialize refresh token: {}", e))
+        };
+        let record_json = serde_json::to_string(&record).map_err(serialize_error)?;
+        let family_json = serde_json::to_string(&family).map_err(serialize_error)?;
+
+        redis::pipe()
+            .atomic()
+            .set_ex(format!("refresh:{}", token_hash), record_json, self.refresh_expiry_seconds)
+            .ignore()
+            .set_ex(format!("refresh_family:{}", family_id), family_json, self.refresh_expiry_seconds)
+            .ignore()
+            .set_ex(format!("session_family:{}", session_id), family_id.to_string(), self.refresh_expiry_seconds)
+            .ignore()
+            .sadd(&index_key, family_id.to_string())
+            .ignore()
+            .expire(&index_key, self.refresh_expiry_seconds as i64)
+            .ignore()
+            .query_async::<_, ()>(&mut self.redis)
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        Ok(token)
+    }
+
+    /// Use up a refresh token. A redeemed token is remembered so a second use
+    /// is told apart from a token that never existed.
+    pub async fn redeem_refresh_token(&mut self, token: &str) -> AppResult<RefreshRedemption> {
+        let token_hash = hash_refresh_token(token);
+
+        let (outcome, value): (i64, String) = self
+            .refresh_redeem_script
+            .key(format!("refresh:{}", token_hash))
+            .key(format!("refresh_used:{}", token_hash))
+            .arg(self.refresh_expiry_seconds)
+            .invoke_async(&mut self.redis)
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        Ok(match outcome {
+            1 => {
+                let record: RefreshToken = serde_json::from_str(&value)
+                    .map_err(|e| AppError::Internal(format!("Failed to deserialize refresh token: {}", e)))?;
+                RefreshRedemption::Valid(record)
+            }
+            2 => match value.parse() {
+                Ok(family_id) => RefreshRedemption::Reused { family_id },
+                Err(_) => RefreshRedemption::Unknown,
+            },
+            _ => RefreshRedemption::Unknown,
+        })
+    }
+
+    /// Revoke a refresh token family: its current token and its session
+    pub async fn revoke_refresh_family(&mut self, family_id: Uuid) -> AppResult<()> {
+        let family: Option<String> = self
+            .redis
+            .get_del(format!("refresh_family:{}", family_id))
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        let Some(family) = family.and_then(|family| serde_json::from_str::<RefreshFamily>(&family).ok()) else {
+            return Ok(());
+        };
+
+        redis::pipe()
+            .del(format!("refresh:{}", family.token_hash))
+            .ignore()
+            .srem(format!("user_sessions:{}", family.login.user_id), family_id.to_string())
+            .ignore()
+            .query_async::<_, ()>(&mut self.redis)
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        self.delete_session(&family.session_id).await?;
+
+        tracing::debug!("Refresh token family revoked: {}", family_id);
+        Ok(())
+    }
+
+    /// End the login a session belongs to: its refresh token family, or just
+    /// the session if it was not issued with a refresh token
+    pub async fn end_login(&mut self, session_id: &str) -> AppResult<()> {
+        let family_id: Option<String> = self
+            .redis
+            .get(format!("session_family:{}", session_id))
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        match family_id.and_then(|id| id.parse().ok()) {
+            Some(family_id) => self.revoke_refresh_family(family_id).await,
+            None => self.delete_session(session_id).await,
+        }
+    }
+
+    /// Live logins of a user. Families that expired since they were indexed
+    /// are dropped from the index on the way.
+    pub async fn list_user_logins(&mut self, user_id: Uuid) -> AppResult<Vec<UserLogin>> {
+        let index_key = format!("user_sessions:{}", user_id);
+
+        let family_ids: Vec<String> = self
+            .redis
+            .smembers(&index_key)
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        if family_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let families: Vec<Option<String>> = self
+            .redis
+            .mget(family_ids.iter().map(|id| format!("refresh_family:{}", id)).collect::<Vec<_>>())
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        let mut logins = Vec::new();
+        let mut expired = Vec::new();
+
+        for (id, family) in family_ids.into_iter().zip(families) {
+            let family = family.and_then(|json| serde_json::from_str::<RefreshFamily>(&json).ok());
+            match (id.parse::<Uuid>(), family) {
+                (Ok(family_id), Some(family)) => logins.push(UserLogin {
+                    family_id,
+                    session_id: family.session_id,
+                    login: family.login,
+                    last_seen_at: None,
+                }),
+                _ => expired.push(id),
+            }
+        }
+
+        if !expired.is_empty() {
+            self.redis
+                .srem::<_, _, ()>(&index_key, expired)
+                .await
+                .map_err(|e| AppError::Redis(e.to_string()))?;
+        }
+
+        if !logins.is_empty() {
+            let last_seen: Vec<Option<i64>> = self
+                .redis
+                .mget(
+                    logins
+                        .iter()
+                        .map(|login| format!("session_seen:{}", login.session_id))
+                        .collect::<Vec<_>>(),
+                )
+                .await
+                .map_err(|e| AppError::Redis(e.to_string()))?;
+
+            for (login, seen) in logins.iter_mut().zip(last_seen) {
+                login.last_seen_at = seen.and_then(|seconds| Utc.timestamp_opt(seconds, 0).single());
+            }
+        }
+
+        Ok(logins)
+    }
+
+    /// Revoke every login of a user, returning how many there were
+    pub async fn revoke_user_logins(&mut self, user_id: Uuid) -> AppResult<usize> {
+        let logins = self.list_user_logins(user_id).await?;
+
+        for login in &logins {
+            self.revoke_refresh_family(login.family_id).await?;
+        }
+
+        Ok(logins.len())
+    }
+
+    /// User a refresh token family belongs to, if it is still live
+    pub async fn refresh_family_owner(&mut self, family_id: Uuid) -> AppResult<Option<Uuid>> {
+        let family: Option<String> = self
+            .redis
+            .get(format!("refresh_family:{}", family_id))
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        Ok(family
+            .and_then(|family| serde_json::from_str::<RefreshFamily>(&family).ok())
+            .map(|family| family.login.user_id))
+    }
+
+    /// Revoke the family of a refresh token that has not been used yet
+    pub async fn revoke_refresh_token(&mut self, token: &str) -> AppResult<()> {
+        match self.redeem_refresh_token(token).await? {
+            RefreshRedemption::Valid(record) => self.revoke_refresh_family(record.family_id).await,
+            RefreshRedemption::Reused { .. } | RefreshRedemption::Unknown => Ok(()),
+        }
+    }
+
+    /// Publish an API key's session for the gateway; it lives until revoked
+    pub async fn store_api_key(&mut self, key_hash: &str, session: &Session) -> AppResult<()> {
+        let session_json = serde_json::to_string(session)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize session: {}", e)))?;
+
+        self.redis
+            .set::<_, _, ()>(format!("api_key:{}", key_hash), session_json)
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Withdraw a revoked API key from the gateway
+    pub async fn delete_api_key(&mut self, key_hash: &str, api_key_id: Uuid) -> AppResult<()> {
+        self.redis
+            .del::<_, ()>(format!("api_key:{}", key_hash))
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        tracing::debug!("API key deleted: {}", api_key_id);
+
+        self.publish_invalidation(&SessionInvalidation::ApiKey { api_key_id })
+            .await;
+
+        Ok(())
+    }
+
+    /// Announce an invalidation so gateways drop cached copies of the session.
+    /// Failures are logged only: the Redis state is already authoritative and
+    /// gateway caches expire on their own.
+    pub async fn publish_invalidation(&mut self, invalidation: &SessionInvalidation) {
+        let payload = match serde_json::to_string(invalidation) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::error!("Failed to serialize session invalidation: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = self
+            .redis
+            .publish::<_, _, ()>(SESSION_INVALIDATION_CHANNEL, payload)
+            .await
+        {
+            tracing::error!("Failed to publish session invalidation: {}", e);
+        }
+    }
+
+    /// Store the hash of an OTP in Redis with TTL; the code itself is never stored
+    pub async fn store_otp(&mut self, email: &str, otp_hash: &str, expiry_seconds: u64) -> AppResult<()> {
         let otp_key = format!("otp:{}", email);
         
         self.redis
-            .set_ex::<_, _, ()>(&otp_key, otp, expiry_seconds)
+            .set_ex::<_, _, ()>(&otp_key, otp_hash, expiry_seconds)
             .await
             .map_err(|e| AppError::Redis(e.to_string()))?;
 
@@ -81,16 +488,275 @@ impl SessionStore {
         Ok(())
     }
 
-    /// Retrieve and delete OTP from Redis (one-time use)
-    pub async fn get_and_delete_otp(&mut self, email: &str) -> AppResult<String> {
+    /// Claim a code send for the email and client IP against the resend
+    /// cooldown and the rolling daily quotas. A refused send claims nothing.
+    pub async fn reserve_otp_send(
+        &mut self,
+        email: &str,
+        client_ip: Option<&str>,
+        config: &OtpConfig,
+    ) -> AppResult<Option<OtpThrottle>> {
+        let mut invocation = self.otp_send_script.prepare_invoke();
+        invocation
+            .key(format!("otp_cooldown:{}", email))
+            .key(format!("otp_quota:email:{}", email))
+            .arg(chrono::Utc::now().timestamp_millis())
+            .arg(config.resend_cooldown_seconds * 1000)
+            .arg(OTP_QUOTA_WINDOW_MS)
+            .arg(Uuid::new_v4().to_string())
+            .arg(config.daily_limit_per_email);
+
+        if let Some(ip) = client_ip {
+            invocation
+                .key(format!("otp_quota:ip:{}", ip))
+                .arg(config.daily_limit_per_ip);
+        }
+
+        let (refusal, wait_ms): (u8, u64) = invocation
+            .invoke_async(&mut self.redis)
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        let wait_seconds = wait_ms.div_ceil(1000).max(1);
+        Ok(match refusal {
+            0 => None,
+            1 => Some(OtpThrottle::Cooldown(wait_seconds)),
+            _ => Some(OtpThrottle::Quota(wait_seconds)),
+        })
+    }
+
+    /// Retrieve and delete the OTP hash from Redis (one-time use)
+    pub async fn get_and_delete_otp(&mut self, email: &str) -> AppResult<Option<String>> {
         let otp_key = format!("otp:{}", email);
         
-        let otp: Option<String> = self.redis
+        self.redis
             .get_del(&otp_key)
             .await
+            .map_err(|e| AppError::Redis(e.to_string()))
+    }
+
+    /// Count a failed login for `subject` (an email or IP address). The count
+    /// is forgotten `window_seconds` after the latest failure.
+    pub async fn record_login_failure(&mut self, subject: &str, window_seconds: u64) -> AppResult<u32> {
+        let failures_key = format!("login_failures:{}", subject);
+
+        let (failures,): (u32,) = redis::pipe()
+            .atomic()
+            .incr(&failures_key, 1)
+            .expire(&failures_key, window_seconds as i64)
+            .ignore()
+            .query_async(&mut self.redis)
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        Ok(failures)
+    }
+
+    /// Lock `subject` out of logging in, returning the lockout in seconds.
+    /// Lockouts within a day back off exponentially.
+    pub async fn lock_login(&mut self, subject: &str, base_seconds: u64, max_seconds: u64) -> AppResult<u64> {
+        let lockouts_key = format!("login_lockouts:{}", subject);
+
+        // The failure count starts over once the lockout ends
+        let (lockouts,): (u32,) = redis::pipe()
+            .atomic()
+            .del(format!("login_failures:{}", subject))
+            .ignore()
+            .incr(&lockouts_key, 1)
+            .expire(&lockouts_key, LOCKOUT_HISTORY_SECONDS)
+            .ignore()
+            .query_async(&mut self.redis)
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        let seconds = lockout_seconds(base_seconds, max_seconds, lockouts);
+        self.redis
+            .set_ex::<_, _, ()>(format!("login_lock:{}", subject), lockouts, seconds)
+            .await
             .map_err(|e| AppError::Redis(e.to_string()))?;
 
-        otp.ok_or_else(|| AppError::BadRequest("OTP not found or expired".to_string()))
+        Ok(seconds)
+    }
+
+    /// Seconds left on `subject`'s lockout, if it is locked
+    pub async fn login_locked_for(&mut self, subject: &str) -> AppResult<Option<u64>> {
+        let ttl: i64 = self
+            .redis
+            .ttl(format!("login_lock:{}", subject))
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        Ok((ttl > 0).then_some(ttl as u64))
+    }
+
+    /// Forget `subject`'s failures and lockout history after a successful login
+    pub async fn clear_login_failures(&mut self, subject: &str) -> AppResult<()> {
+        self.redis
+            .del::<_, ()>(&[
+                format!("login_failures:{}", subject),
+                format!("login_lockouts:{}", subject),
+            ])
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::user::UserRole;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    type Keys = Arc<Mutex<HashMap<String, String>>>;
+
+    /// A session store on an in-process Redis that keeps string keys, answers
+    /// set commands blindly and runs the refresh redeem script by its hash
+    async fn session_store() -> SessionStore {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let keys = Keys::default();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(serve(socket, keys.clone()));
+            }
+        });
+
+        let client = redis::Client::open(format!("redis://{}", addr)).unwrap();
+        SessionStore::new(ConnectionManager::new(client).await.unwrap(), 900, 3600)
+    }
+
+    async fn serve(socket: TcpStream, keys: Keys) {
+        let redeem_hash = redis::Script::new(REFRESH_REDEEM_SCRIPT).get_hash().to_string();
+        let (reader, mut writer) = socket.into_split();
+        let mut reader = BufReader::new(reader);
+        let mut queued: Option<Vec<String>> = None;
+
+        while let Some(args) = read_args(&mut reader).await {
+            let command = args[0].to_ascii_uppercase();
+            let reply = match command.as_str() {
+                "MULTI" => {
+                    queued = Some(Vec::new());
+                    "+OK\r\n".to_string()
+                }
+                "EXEC" => {
+                    let replies = queued.take().unwrap_or_default();
+                    format!("*{}\r\n{}", replies.len(), replies.concat())
+                }
+                _ => {
+                    let reply = run(&mut keys.lock().unwrap(), &redeem_hash, &command, &args);
+                    match queued.as_mut() {
+                        Some(replies) => {
+                            replies.push(reply);
+                            "+QUEUED\r\n".to_string()
+                        }
+                        None => reply,
+                    }
+                }
+            };
+
+            if writer.write_all(reply.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    fn run(keys: &mut HashMap<String, String>, redeem_hash: &str, command: &str, args: &[String]) -> String {
+        let bulk = |value: Option<String>| match value {
+            Some(value) => format!("${}\r\n{}\r\n", value.len(), value),
+            None => "$-1\r\n".to_string(),
+        };
+
+        match command {
+            "GET" => bulk(keys.get(&args[1]).cloned()),
+            "GETDEL" => bulk(keys.remove(&args[1])),
+            "SETEX" => {
+                keys.insert(args[1].clone(), args[3].clone());
+                "+OK\r\n".to_string()
+            }
+            "DEL" => {
+                let deleted = args[1..].iter().filter(|key| keys.remove(*key).is_some()).count();
+                format!(":{}\r\n", deleted)
+            }
+            "EVALSHA" if args[1] == redeem_hash => {
+                let (token_key, used_key) = (&args[3], &args[4]);
+                let (outcome, value) = if let Some(record) = keys.remove(token_key) {
+                    let family_id = serde_json::from_str::<RefreshToken>(&record).unwrap().family_id;
+                    keys.insert(used_key.clone(), family_id.to_string());
+                    (1, record)
+                } else if let Some(family_id) = keys.get(used_key) {
+                    (2, family_id.clone())
+                } else {
+                    (0, String::new())
+                };
+                format!("*2\r\n:{}\r\n{}", outcome, bulk(Some(value)))
+            }
+            _ => ":1\r\n".to_string(),
+        }
+    }
+
+    async fn read_args<R: AsyncBufRead + Unpin>(reader: &mut R) -> Option<Vec<String>> {
+        let mut line = String::new();
+        reader.read_line(&mut line).await.ok().filter(|read| *read > 0)?;
+        let count: usize = line.trim_end().strip_prefix('*')?.parse().ok()?;
+
+        let mut args = Vec::with_capacity(count);
+        for _ in 0..count {
+            line.clear();
+            reader.read_line(&mut line).await.ok()?;
+            let len: usize = line.trim_end().strip_prefix('$')?.parse().ok()?;
+            let mut arg = vec![0; len + 2];
+            reader.read_exact(&mut arg).await.ok()?;
+            args.push(String::from_utf8_lossy(&arg[..len]).into_owned());
+        }
+        Some(args)
+    }
+
+    async fn login(store: &mut SessionStore) -> (String, String) {
+        let session = Session {
+            user_id: Uuid::new_v4(),
+            email: "diner@example.com".to_string(),
+            role: UserRole::User,
+            created_at: Utc::now(),
+            scope: None,
+        };
+        let login = LoginMetadata {
+            user_id: session.user_id,
+            created_at: session.created_at,
+            user_agent: None,
+            ip_address: None,
+        };
+
+        let session_id = store.store_session(&session).await.unwrap();
+        let refresh_token = store
+            .issue_refresh_token(Uuid::new_v4(), &login, &session_id)
+            .await
+            .unwrap();
+        (session_id, refresh_token)
+    }
+
+    #[tokio::test]
+    async fn test_end_login_revokes_refresh_token() {
+        let mut store = session_store().await;
+        let (session_id, refresh_token) = login(&mut store).await;
+        let (other_session_id, other_refresh_token) = login(&mut store).await;
+
+        // Logging out with only the session id ends the login it belongs to
+        store.end_login(&session_id).await.unwrap();
+
+        assert!(store.get_session(&session_id).await.is_err());
+        assert!(matches!(
+            store.redeem_refresh_token(&refresh_token).await.unwrap(),
+            RefreshRedemption::Unknown
+        ));
+
+        assert!(store.get_session(&other_session_id).await.is_ok());
+        assert!(matches!(
+            store.redeem_refresh_token(&other_refresh_token).await.unwrap(),
+            RefreshRedemption::Valid(_)
+        ));
+    }
+}
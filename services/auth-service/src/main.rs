@@ -4,10 +4,11 @@ mod domain;
 mod infrastructure;
 mod presentation;
 
-use actix_cors::Cors;
 use actix_web::{middleware::Logger, web, App, HttpServer};
 use application::AuthService;
-use infrastructure::{EmailService, EventPublisher, SessionStore, UserRepository};
+use infrastructure::{
+    ApiKeyRepository, EmailService, EventPublisher, RestaurantClient, SessionStore, UserRepository,
+};
 use kafka_client::KafkaProducer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -31,7 +32,7 @@ async fn main() -> std::io::Result<()> {
     tracing::info!("Starting Auth Service on {}:{}", config.server.host, config.server.port);
 
     // Create database pool
-    let db_pool = db_utils::create_pg_pool(&config.database.url)
+    let db_pool = db_utils::create_pg_pool(&config.database.url, 10)
         .await
         .expect("Failed to create database pool");
 
@@ -51,37 +52,60 @@ async fn main() -> std::io::Result<()> {
     // Create infrastructure components
     let user_repo = UserRepository::new(db_pool.clone());
     let user_repo_data = web::Data::new(user_repo.clone());
-    let session_store = SessionStore::new(redis_conn, config.session.expiry_seconds);
+    let session_store = SessionStore::new(
+        redis_conn,
+        config.session.expiry_seconds,
+        config.session.refresh_expiry_seconds,
+    );
     let email_service = EmailService::new(&config.smtp).expect("Failed to create email service");
     let event_publisher = EventPublisher::new(KafkaProducer::new(kafka_producer));
+    let api_key_repo = ApiKeyRepository::new(db_pool.clone());
+    let restaurant_client = RestaurantClient::new(
+        reqwest::Client::new(),
+        config.restaurant_service.base_url.clone(),
+    );
 
     // Create auth service
-    let auth_service = AuthService::new(
+    let mut auth_service = AuthService::new(
         user_repo,
+        api_key_repo,
         session_store,
         email_service,
         event_publisher,
+        restaurant_client,
         &config,
     );
 
+    // Make sure every active API key is known to the gateway
+    match auth_service.sync_api_keys().await {
+        Ok(count) => tracing::info!("Published {} active API keys", count),
+        Err(e) => tracing::error!("Failed to publish active API keys: {}", e),
+    }
+
     let auth_service_data = web::Data::new(tokio::sync::Mutex::new(auth_service));
+    let identity_signer_data = web::Data::new(identity::IdentitySigner::new(&config.identity.signing_secret));
+    let network_data = web::Data::new(config.network.clone());
+    let internal_data = web::Data::new(config.internal.clone());
 
     // Start HTTP server
     let server_host = config.server.host.clone();
     let server_port = config.server.port;
 
-    HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_method()
-            .allow_any_header()
-            .max_age(3600);
+    let api_doc = web::Data::new(presentation::api_doc::api_doc());
 
+    HttpServer::new(move || {
         App::new()
-            .wrap(cors)
             .wrap(Logger::default())
-            .wrap(tracing_actix_web::TracingLogger::default())
+            .wrap(tracing_actix_web::TracingLogger::<correlation::RequestIdRootSpan>::new())
+            .wrap(metrics::MetricsMiddleware::new("auth-service"))
+            .wrap(correlation::RequestIdMiddleware)
             .app_data(auth_service_data.clone())
+            .app_data(identity_signer_data.clone())
+            .app_data(network_data.clone())
+            .app_data(internal_data.clone())
+            .route("/metrics", web::get().to(metrics::metrics_handler))
+            .app_data(api_doc.clone())
+            .route("/openapi.json", web::get().to(openapi::openapi_handler))
             .app_data(user_repo_data.clone())
             .configure(presentation::configure_routes)
     })
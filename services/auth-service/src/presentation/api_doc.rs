@@ -0,0 +1,27 @@
+use openapi::ServiceDefaults;
+use utoipa::OpenApi;
+
+use super::handlers;
+
+/// OpenAPI document of the public routes, served at `/openapi.json`
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        handlers::request_otp,
+        handlers::verify_otp,
+        handlers::refresh,
+        handlers::logout,
+        handlers::list_sessions,
+        handlers::revoke_session,
+        handlers::revoke_all_sessions,
+        handlers::create_api_key,
+        handlers::list_api_keys,
+        handlers::revoke_api_key,
+    ),
+    modifiers(&ServiceDefaults)
+)]
+pub struct ApiDoc;
+
+pub fn api_doc() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
@@ -1,17 +1,36 @@
-use actix_web::{web, HttpResponse};
-use error_handling::AppResult;
-use models::user::{RequestOtpRequest, VerifyOtpRequest};
+use actix_web::{web, HttpRequest, HttpResponse};
+use error_handling::{AppError, AppResult, ErrorResponse};
+use models::user::{
+    ApiKey, AuthResponse, CreateApiKeyRequest, CreateApiKeyResponse, DeactivateDormantRequest, DormantAccount, DormantAccountFailure,
+    DormantAccountsReport, RefreshRequest, RequestOtpRequest, SessionInfo, User,
+    VerifyOtpRequest,
+};
+use std::future::Future;
 use serde::Deserialize;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::application::AuthService;
+use crate::config::{InternalConfig, NetworkConfig};
 use crate::infrastructure::repository::UserRepository;
 
 /// Request OTP handler
+#[utoipa::path(
+    post,
+    path = "/api/auth/request-otp",
+    summary = "Email a one-time login code",
+    request_body = RequestOtpRequest,
+    responses(
+        (status = 200, description = "Code sent", body = Object),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 429, description = "Sent too recently or too often; see retry_after", body = ErrorResponse),
+    )
+)]
 pub async fn request_otp(
     auth_service: web::Data<tokio::sync::Mutex<AuthService>>,
     request: web::Json<RequestOtpRequest>,
+    req: HttpRequest,
 ) -> AppResult<HttpResponse> {
     // Validate request
     request.validate()?;
@@ -20,7 +39,7 @@ pub async fn request_otp(
     auth_service
         .lock()
         .await
-        .request_otp(request.into_inner())
+        .request_otp(request.into_inner(), client_ip(&req))
         .await?;
 
     Ok(HttpResponse::Ok().json(serde_json::json!({
@@ -29,9 +48,21 @@ pub async fn request_otp(
 }
 
 /// Verify OTP handler
+#[utoipa::path(
+    post,
+    path = "/api/auth/verify-otp",
+    summary = "Exchange a login code for a session token",
+    request_body = VerifyOtpRequest,
+    responses(
+        (status = 200, description = "Session created", body = AuthResponse),
+        (status = 401, description = "Invalid or expired code", body = ErrorResponse),
+        (status = 429, description = "Locked after failed attempts; see retry_after", body = ErrorResponse),
+    )
+)]
 pub async fn verify_otp(
     auth_service: web::Data<tokio::sync::Mutex<AuthService>>,
     request: web::Json<VerifyOtpRequest>,
+    req: HttpRequest,
 ) -> AppResult<HttpResponse> {
     // Validate request
     request.validate()?;
@@ -40,18 +71,56 @@ pub async fn verify_otp(
     let response = auth_service
         .lock()
         .await
-        .verify_otp(request.into_inner())
+        .verify_otp(request.into_inner(), client_ip(&req), user_agent(&req))
         .await?;
 
     Ok(HttpResponse::Ok().json(response))
 }
 
-#[derive(Debug, Deserialize)]
+/// Refresh session handler
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    summary = "Exchange a refresh token for a new session and refresh token",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "Session created", body = AuthResponse),
+        (status = 401, description = "Invalid, expired or reused refresh token", body = ErrorResponse),
+        (status = 403, description = "User account is deactivated", body = ErrorResponse),
+    )
+)]
+pub async fn refresh(
+    auth_service: web::Data<tokio::sync::Mutex<AuthService>>,
+    request: web::Json<RefreshRequest>,
+) -> AppResult<HttpResponse> {
+    request.validate()?;
+
+    let response = auth_service
+        .lock()
+        .await
+        .refresh(request.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LogoutRequest {
     pub session_id: String,
+    /// Also revokes this refresh token's family, if it is not the session's own
+    pub refresh_token: Option<String>,
 }
 
 /// Logout handler
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    summary = "End a session",
+    request_body = LogoutRequest,
+    responses(
+        (status = 200, description = "Logged out", body = Object),
+    )
+)]
 pub async fn logout(
     auth_service: web::Data<tokio::sync::Mutex<AuthService>>,
     request: web::Json<LogoutRequest>,
@@ -60,7 +129,7 @@ pub async fn logout(
     auth_service
         .lock()
         .await
-        .logout(&request.session_id)
+        .logout(&request.session_id, request.refresh_token.as_deref())
         .await?;
 
     Ok(HttpResponse::Ok().json(serde_json::json!({
@@ -68,6 +137,207 @@ pub async fn logout(
     })))
 }
 
+/// List the caller's logins
+/// GET /api/auth/sessions
+#[utoipa::path(
+    get,
+    path = "/api/auth/sessions",
+    summary = "List the caller's active sessions",
+    responses(
+        (status = 200, description = "Sessions, newest first", body = Vec<SessionInfo>),
+    ),
+    security(("session" = []))
+)]
+pub async fn list_sessions(
+    auth_service: web::Data<tokio::sync::Mutex<AuthService>>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let identity = identity::verified_identity(&req)?;
+
+    let sessions = auth_service
+        .lock()
+        .await
+        .list_sessions(&identity)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(sessions))
+}
+
+/// Revoke one of the caller's logins
+/// DELETE /api/auth/sessions/{id}
+#[utoipa::path(
+    delete,
+    path = "/api/auth/sessions/{id}",
+    summary = "Revoke one of the caller's sessions and its refresh token",
+    params(("id" = Uuid, Path, description = "Session id")),
+    responses(
+        (status = 204, description = "Session revoked"),
+        (status = 404, description = "No such active session", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
+pub async fn revoke_session(
+    auth_service: web::Data<tokio::sync::Mutex<AuthService>>,
+    path: web::Path<Uuid>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let identity = identity::verified_identity(&req)?;
+
+    auth_service
+        .lock()
+        .await
+        .revoke_session(identity.user_id, path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Log the caller out everywhere, including this session
+/// DELETE /api/auth/sessions
+#[utoipa::path(
+    delete,
+    path = "/api/auth/sessions",
+    summary = "Log out everywhere, including the current session",
+    responses(
+        (status = 200, description = "Sessions revoked", body = Object),
+    ),
+    security(("session" = []))
+)]
+pub async fn revoke_all_sessions(
+    auth_service: web::Data<tokio::sync::Mutex<AuthService>>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let identity = identity::verified_identity(&req)?;
+
+    let revoked = auth_service
+        .lock()
+        .await
+        .revoke_all_sessions(identity.user_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Logged out everywhere",
+        "revoked": revoked
+    })))
+}
+
+/// Create an API key for a machine client
+/// POST /api/auth/api-keys
+#[utoipa::path(
+    post,
+    path = "/api/auth/api-keys",
+    summary = "Create an API key bound to one of the caller's restaurants",
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 201, description = "Key created; the key is only returned once", body = CreateApiKeyResponse),
+        (status = 403, description = "Not the owner of the restaurant", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
+pub async fn create_api_key(
+    auth_service: web::Data<tokio::sync::Mutex<AuthService>>,
+    request: web::Json<CreateApiKeyRequest>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    request.validate()?;
+    let owner = identity::verified_identity(&req)?;
+
+    let response = auth_service
+        .lock()
+        .await
+        .create_api_key(&owner, request.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Created().json(response))
+}
+
+/// List the caller's API keys
+/// GET /api/auth/api-keys
+#[utoipa::path(
+    get,
+    path = "/api/auth/api-keys",
+    summary = "List the caller's API keys",
+    responses(
+        (status = 200, description = "API keys, newest first", body = Vec<ApiKey>),
+    ),
+    security(("session" = []))
+)]
+pub async fn list_api_keys(
+    auth_service: web::Data<tokio::sync::Mutex<AuthService>>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let owner = identity::verified_identity(&req)?;
+
+    let api_keys = auth_service
+        .lock()
+        .await
+        .list_api_keys(owner.user_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(api_keys))
+}
+
+/// Revoke one of the caller's API keys
+/// DELETE /api/auth/api-keys/{id}
+#[utoipa::path(
+    delete,
+    path = "/api/auth/api-keys/{id}",
+    summary = "Revoke an API key",
+    params(("id" = Uuid, Path, description = "API key id")),
+    responses(
+        (status = 204, description = "Key revoked"),
+        (status = 404, description = "No such active key", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
+pub async fn revoke_api_key(
+    auth_service: web::Data<tokio::sync::Mutex<AuthService>>,
+    path: web::Path<Uuid>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let owner = identity::verified_identity(&req)?;
+
+    auth_service
+        .lock()
+        .await
+        .revoke_api_key(owner.user_id, path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Client address. `X-Forwarded-For` is only believed from a trusted proxy
+/// (the gateway, which replaces the header), and then only its last entry,
+/// the one that proxy added; anyone else could vary it to dodge per-IP limits.
+fn client_ip(req: &HttpRequest) -> Option<String> {
+    let peer = req.peer_addr()?.ip();
+
+    let trusted = req
+        .app_data::<web::Data<NetworkConfig>>()
+        .is_some_and(|network| network.trusted_proxies.contains(&peer));
+
+    if !trusted {
+        return Some(peer.to_string());
+    }
+
+    let forwarded = req
+        .headers()
+        .get("X-Forwarded-For")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .map(str::trim)
+        .and_then(|ip| ip.parse::<std::net::IpAddr>().ok());
+
+    Some(forwarded.unwrap_or(peer).to_string())
+}
+
+fn user_agent(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(actix_web::http::header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.chars().take(256).collect())
+}
+
 /// Health check handler
 pub async fn health_check() -> HttpResponse {
     HttpResponse::Ok().json(serde_json::json!({
@@ -96,3 +366,189 @@ pub async fn get_user_by_id(
     })))
 }
 
+/// Internal endpoints that change accounts require `X-Service-Token` to
+/// match `INTERNAL_SERVICE_TOKEN`
+fn require_service_token(req: &HttpRequest, internal: &InternalConfig) -> AppResult<()> {
+    identity::require_token(req, "X-Service-Token", internal.service_token.as_deref())
+}
+
+/// Internal endpoint to deactivate dormant accounts, called by scheduler-service
+/// POST /internal/users/deactivate-dormant
+pub async fn deactivate_dormant_users(
+    auth_service: web::Data<tokio::sync::Mutex<AuthService>>,
+    internal: web::Data<InternalConfig>,
+    request: web::Json<DeactivateDormantRequest>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    require_service_token(&req, &internal)?;
+
+    let candidates = auth_service
+        .lock()
+        .await
+        .find_dormant_candidates(request.inactive_days)
+        .await?;
+
+    // Lock per user so logins are not held up for the whole batch
+    let dry_run = request.dry_run;
+    let report = deactivate_each(candidates, dry_run, |user| {
+        let auth_service = auth_service.clone();
+        async move {
+            auth_service
+                .lock()
+                .await
+                .deactivate_if_dormant(&user, dry_run)
+                .await
+        }
+    })
+    .await;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// Run `deactivate` for each candidate. A failure is recorded in the report
+/// and the batch carries on with the next account.
+async fn deactivate_each<F, Fut>(
+    candidates: Vec<User>,
+    dry_run: bool,
+    mut deactivate: F,
+) -> DormantAccountsReport
+where
+    F: FnMut(User) -> Fut,
+    Fut: Future<Output = AppResult<bool>>,
+{
+    let mut report = DormantAccountsReport {
+        dry_run,
+        accounts: Vec::new(),
+        failures: Vec::new(),
+    };
+
+    for user in candidates {
+        match deactivate(user.clone()).await {
+            Ok(true) => report.accounts.push(DormantAccount {
+                user_id: user.id,
+                email: user.email,
+                last_verified_at: user.last_verified_at,
+            }),
+            Ok(false) => {}
+            Err(e) => {
+                tracing::error!("Failed to deactivate dormant user {}: {}", user.email, e);
+                report.failures.push(DormantAccountFailure {
+                    user_id: user.id,
+                    email: user.email,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn network(trusted_proxies: &[&str]) -> web::Data<NetworkConfig> {
+        web::Data::new(NetworkConfig {
+            trusted_proxies: trusted_proxies.iter().map(|ip| ip.parse().unwrap()).collect(),
+        })
+    }
+
+    #[test]
+    fn test_client_ip_ignores_untrusted_forwarded_for() {
+        let req = TestRequest::default()
+            .peer_addr("203.0.113.7:4000".parse().unwrap())
+            .insert_header(("X-Forwarded-For", "198.51.100.1"))
+            .app_data(network(&["10.0.0.2"]))
+            .to_http_request();
+
+        assert_eq!(client_ip(&req).as_deref(), Some("203.0.113.7"));
+    }
+
+    #[test]
+    fn test_client_ip_from_trusted_proxy() {
+        let req = TestRequest::default()
+            .peer_addr("10.0.0.2:4000".parse().unwrap())
+            .insert_header(("X-Forwarded-For", "198.51.100.1, 203.0.113.7"))
+            .app_data(network(&["10.0.0.2"]))
+            .to_http_request();
+
+        assert_eq!(client_ip(&req).as_deref(), Some("203.0.113.7"));
+
+        // A garbled header falls back to the proxy itself
+        let req = TestRequest::default()
+            .peer_addr("10.0.0.2:4000".parse().unwrap())
+            .insert_header(("X-Forwarded-For", "not-an-ip"))
+            .app_data(network(&["10.0.0.2"]))
+            .to_http_request();
+
+        assert_eq!(client_ip(&req).as_deref(), Some("10.0.0.2"));
+    }
+
+    fn user(email: &str) -> User {
+        User {
+            id: Uuid::new_v4(),
+            email: email.to_string(),
+            role: models::user::UserRole::User,
+            is_active: true,
+            last_verified_at: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_deactivate_each_continues_past_failures() {
+        let candidates = vec![user("a@example.com"), user("b@example.com"), user("c@example.com")];
+
+        let report = deactivate_each(candidates, false, |user| async move {
+            match user.email.as_str() {
+                "a@example.com" => Err(AppError::Redis("connection reset".to_string())),
+                // A live login keeps the account
+                "b@example.com" => Ok(false),
+                _ => Ok(true),
+            }
+        })
+        .await;
+
+        let accounts: Vec<_> = report.accounts.iter().map(|a| a.email.as_str()).collect();
+        assert_eq!(accounts, ["c@example.com"]);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].email, "a@example.com");
+        assert!(report.failures[0].error.contains("connection reset"));
+    }
+
+    #[test]
+    fn test_service_token_required() {
+        let internal = InternalConfig {
+            service_token: Some("service-secret".to_string()),
+        };
+
+        let req = TestRequest::default()
+            .insert_header(("X-Service-Token", "service-secret"))
+            .to_http_request();
+        assert!(require_service_token(&req, &internal).is_ok());
+
+        let req = TestRequest::default()
+            .insert_header(("X-Service-Token", "guess"))
+            .to_http_request();
+        assert!(matches!(
+            require_service_token(&req, &internal),
+            Err(AppError::Unauthorized(_))
+        ));
+
+        let req = TestRequest::default().to_http_request();
+        assert!(require_service_token(&req, &internal).is_err());
+
+        // Without a configured token the endpoint is off
+        let disabled = InternalConfig { service_token: None };
+        let req = TestRequest::default()
+            .insert_header(("X-Service-Token", "service-secret"))
+            .to_http_request();
+        assert!(matches!(
+            require_service_token(&req, &disabled),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
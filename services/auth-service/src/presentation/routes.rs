@@ -8,11 +8,22 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
             .route("/health", web::get().to(handlers::health_check))
             .route("/request-otp", web::post().to(handlers::request_otp))
             .route("/verify-otp", web::post().to(handlers::verify_otp))
-            .route("/logout", web::post().to(handlers::logout)),
+            .route("/refresh", web::post().to(handlers::refresh))
+            .route("/logout", web::post().to(handlers::logout))
+            .route("/sessions", web::get().to(handlers::list_sessions))
+            .route("/sessions", web::delete().to(handlers::revoke_all_sessions))
+            .route("/sessions/{id}", web::delete().to(handlers::revoke_session))
+            .route("/api-keys", web::post().to(handlers::create_api_key))
+            .route("/api-keys", web::get().to(handlers::list_api_keys))
+            .route("/api-keys/{id}", web::delete().to(handlers::revoke_api_key)),
     )
     .service(
         web::scope("/internal")
-            .route("/users/{id}", web::get().to(handlers::get_user_by_id)),
+            .route("/users/{id}", web::get().to(handlers::get_user_by_id))
+            .route(
+                "/users/deactivate-dormant",
+                web::post().to(handlers::deactivate_dormant_users),
+            ),
     );
 }
 
@@ -56,10 +56,11 @@ impl BillingService {
     }
 
     /// Get bill by order ID
-    pub async fn get_bill_by_order_id(&self, order_id: Uuid) -> AppResult<Bill> {
+    pub async fn get_bill_by_order_id(&self, order_id: Uuid, restaurant_scope: Option<Uuid>) -> AppResult<Bill> {
         self.bill_repo
             .find_by_order_id(order_id)
             .await?
+            .filter(|bill| restaurant_scope.is_none_or(|restaurant_id| bill.restaurant_id == restaurant_id))
             .ok_or_else(|| AppError::NotFound(format!("Bill not found for order {}", order_id)))
     }
 
@@ -76,9 +77,10 @@ impl BillingService {
         &self,
         order_id: Uuid,
         payment_method: PaymentMethod,
+        restaurant_scope: Option<Uuid>,
     ) -> AppResult<Bill> {
         // Get bill by order ID
-        let bill = self.get_bill_by_order_id(order_id).await?;
+        let bill = self.get_bill_by_order_id(order_id, restaurant_scope).await?;
 
         // Check if already paid
         if bill.status == models::BillStatus::Paid {
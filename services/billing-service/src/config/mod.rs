@@ -17,11 +17,17 @@ pub struct ServerConfig {
     pub port: u16,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct IdentityConfig {
+    pub signing_secret: String,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
     pub database: DatabaseConfig,
     pub kafka: KafkaConfig,
     pub server: ServerConfig,
+    pub identity: IdentityConfig,
 }
 
 impl Config {
@@ -35,6 +41,8 @@ impl Config {
             .unwrap_or_else(|_| "8005".to_string())
             .parse()
             .expect("SERVER_PORT must be a valid number");
+        let identity_signing_secret = std::env::var("IDENTITY_SIGNING_SECRET")
+            .map_err(|_| config::ConfigError::NotFound("IDENTITY_SIGNING_SECRET".to_string()))?;
 
         Ok(Config {
             database: DatabaseConfig {
@@ -48,6 +56,9 @@ impl Config {
                 host: server_host,
                 port: server_port,
             },
+            identity: IdentityConfig {
+                signing_secret: identity_signing_secret,
+            },
         })
     }
 }
@@ -25,12 +25,18 @@ impl EventConsumer {
         loop {
             match self.consumer.inner().recv().await {
                 Ok(message) => {
+                    kafka_client::record_consumed(&message);
+
                     if let Some(payload) = message.payload() {
                         if let Ok(payload_str) = std::str::from_utf8(payload) {
                             if let Ok(event) = serde_json::from_str::<OrderPlacedEvent>(payload_str)
                             {
                                 if event.event_type == event_types::ORDER_PLACED {
-                                    self.handle_order_placed(event).await;
+                                    kafka_client::handle_in_context(
+                                        &message,
+                                        self.handle_order_placed(event),
+                                    )
+                                    .await;
                                 }
                             } else {
                                 tracing::error!("Failed to deserialize order.placed event");
@@ -3,7 +3,6 @@ mod config;
 mod infrastructure;
 mod presentation;
 
-use actix_cors::Cors;
 use actix_web::{middleware::Logger, web, App, HttpServer};
 use std::sync::Arc;
 use tracing_actix_web::TracingLogger;
@@ -33,7 +32,7 @@ async fn main() -> std::io::Result<()> {
     let config = Config::from_env().expect("Failed to load configuration");
 
     // Create database connection pool
-    let db_pool = db_utils::create_pg_pool(&config.database.url)
+    let db_pool = db_utils::create_pg_pool(&config.database.url, 10)
         .await
         .expect("Failed to create database pool");
 
@@ -75,17 +74,20 @@ async fn main() -> std::io::Result<()> {
     let server_address = format!("{}:{}", config.server.host, config.server.port);
     let billing_service_data = web::Data::new(billing_service);
 
-    HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_method()
-            .allow_any_header();
+    let api_doc = web::Data::new(presentation::api_doc::api_doc());
+    let identity_signer = web::Data::new(identity::IdentitySigner::new(&config.identity.signing_secret));
 
+    HttpServer::new(move || {
         App::new()
-            .wrap(TracingLogger::default())
+            .wrap(TracingLogger::<correlation::RequestIdRootSpan>::new())
             .wrap(Logger::default())
-            .wrap(cors)
+            .wrap(metrics::MetricsMiddleware::new("billing-service"))
+            .wrap(correlation::RequestIdMiddleware)
             .app_data(billing_service_data.clone())
+            .app_data(identity_signer.clone())
+            .route("/metrics", web::get().to(metrics::metrics_handler))
+            .app_data(api_doc.clone())
+            .route("/openapi.json", web::get().to(openapi::openapi_handler))
             .configure(routes::configure_routes)
     })
     .bind(&server_address)?
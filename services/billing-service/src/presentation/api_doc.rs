@@ -0,0 +1,21 @@
+use openapi::ServiceDefaults;
+use utoipa::OpenApi;
+
+use super::handlers;
+
+/// OpenAPI document of the public routes, served at `/openapi.json`
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        handlers::get_bill_by_order_id,
+        handlers::finalize_bill,
+        handlers::get_user_bills,
+        handlers::get_restaurant_bills,
+    ),
+    modifiers(&ServiceDefaults)
+)]
+pub struct ApiDoc;
+
+pub fn api_doc() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
@@ -1,6 +1,6 @@
-use actix_web::{web, HttpResponse};
-use error_handling::AppResult;
-use models::FinalizeBillRequest;
+use actix_web::{web, HttpRequest, HttpResponse};
+use error_handling::{AppResult, ErrorResponse};
+use models::{Bill, FinalizeBillRequest};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -15,27 +15,66 @@ pub async fn health_check() -> HttpResponse {
 }
 
 /// Get bill by order ID
+#[utoipa::path(
+    get,
+    path = "/api/billing/orders/{order_id}",
+    summary = "Get the bill of an order",
+    params(("order_id" = Uuid, Path, description = "Order id")),
+    responses(
+        (status = 200, description = "Bill", body = Bill),
+        (status = 404, description = "Bill not found", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 pub async fn get_bill_by_order_id(
+    req: HttpRequest,
     billing_service: web::Data<Arc<BillingService>>,
     order_id: web::Path<Uuid>,
 ) -> AppResult<HttpResponse> {
-    let bill = billing_service.get_bill_by_order_id(order_id.into_inner()).await?;
+    let caller = identity::verified_identity(&req)?;
+    let bill = billing_service
+        .get_bill_by_order_id(order_id.into_inner(), caller.restaurant_id)
+        .await?;
     Ok(HttpResponse::Ok().json(bill))
 }
 
 /// Finalize bill (mark as paid)
+#[utoipa::path(
+    post,
+    path = "/api/billing/orders/{order_id}/finalize",
+    summary = "Mark the bill of an order as paid",
+    params(("order_id" = Uuid, Path, description = "Order id")),
+    request_body = FinalizeBillRequest,
+    responses(
+        (status = 200, description = "Bill paid", body = Bill),
+        (status = 404, description = "Bill not found", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 pub async fn finalize_bill(
+    http_req: HttpRequest,
     billing_service: web::Data<Arc<BillingService>>,
     order_id: web::Path<Uuid>,
     req: web::Json<FinalizeBillRequest>,
 ) -> AppResult<HttpResponse> {
+    let caller = identity::verified_identity(&http_req)?;
     let bill = billing_service
-        .finalize_bill(order_id.into_inner(), req.payment_method.clone())
+        .finalize_bill(order_id.into_inner(), req.payment_method.clone(), caller.restaurant_id)
         .await?;
     Ok(HttpResponse::Ok().json(bill))
 }
 
 /// Get all bills for a user
+#[utoipa::path(
+    get,
+    path = "/api/billing/users/{user_id}",
+    summary = "List a user's bills",
+    params(("user_id" = Uuid, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Bills", body = Vec<Bill>),
+    ),
+    security(("session" = []))
+)]
 pub async fn get_user_bills(
     billing_service: web::Data<Arc<BillingService>>,
     user_id: web::Path<Uuid>,
@@ -45,6 +84,16 @@ pub async fn get_user_bills(
 }
 
 /// Get all bills for a restaurant
+#[utoipa::path(
+    get,
+    path = "/api/billing/restaurants/{restaurant_id}",
+    summary = "List a restaurant's bills",
+    params(("restaurant_id" = Uuid, Path, description = "Restaurant id")),
+    responses(
+        (status = 200, description = "Bills", body = Vec<Bill>),
+    ),
+    security(("session" = []))
+)]
 pub async fn get_restaurant_bills(
     billing_service: web::Data<Arc<BillingService>>,
     restaurant_id: web::Path<Uuid>,
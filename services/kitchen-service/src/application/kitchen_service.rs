@@ -1,5 +1,5 @@
 use error_handling::AppError;
-use models::kitchen::KitchenTicketResponse;
+use models::kitchen::{KitchenTicket, KitchenTicketResponse};
 use uuid::Uuid;
 
 use crate::infrastructure::auth_client::AuthClient;
@@ -30,17 +30,37 @@ impl KitchenService {
     }
 
     /// Get a kitchen ticket by ID
-    pub async fn get_ticket(&self, ticket_id: Uuid) -> Result<KitchenTicketResponse, AppError> {
+    pub async fn get_ticket(
+        &self,
+        ticket_id: Uuid,
+        restaurant_scope: Option<Uuid>,
+    ) -> Result<KitchenTicketResponse, AppError> {
         let ticket = self.repository.get_ticket(ticket_id).await?;
-        self.repository.to_response(ticket)
+        self.repository.to_response(in_scope(ticket, restaurant_scope)?)
     }
 
-    /// List all kitchen tickets with optional status filter
+    /// Get the kitchen ticket for an order
+    pub async fn get_ticket_by_order_id(
+        &self,
+        order_id: Uuid,
+        restaurant_scope: Option<Uuid>,
+    ) -> Result<KitchenTicketResponse, AppError> {
+        let ticket = self
+            .repository
+            .get_ticket_by_order_id(order_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Kitchen ticket not found".to_string()))?;
+        self.repository.to_response(in_scope(ticket, restaurant_scope)?)
+    }
+
+    /// List kitchen tickets with optional status filter, confined to
+    /// `restaurant_scope` if given
     pub async fn list_tickets(
         &self,
         status_filter: Option<String>,
+        restaurant_scope: Option<Uuid>,
     ) -> Result<Vec<KitchenTicketResponse>, AppError> {
-        let tickets = self.repository.list_tickets(status_filter).await?;
+        let tickets = self.repository.list_tickets(status_filter, restaurant_scope).await?;
 
         let mut responses = Vec::new();
         for ticket in tickets {
@@ -55,6 +75,7 @@ impl KitchenService {
         &self,
         ticket_id: Uuid,
         new_status: String,
+        restaurant_scope: Option<Uuid>,
     ) -> Result<KitchenTicketResponse, AppError> {
         // Validate status
         let valid_statuses = vec!["NEW", "ACCEPTED", "IN_PROGRESS", "READY", "DELIVERED_TO_SERVICE"];
@@ -67,7 +88,7 @@ impl KitchenService {
         }
 
         // Get current ticket
-        let current_ticket = self.repository.get_ticket(ticket_id).await?;
+        let current_ticket = in_scope(self.repository.get_ticket(ticket_id).await?, restaurant_scope)?;
         let old_status = current_ticket.status.clone();
 
         // Validate status transition
@@ -97,7 +118,7 @@ impl KitchenService {
             let auth_client = self.auth_client.clone();
 
             // Send email asynchronously in background
-            tokio::spawn(async move {
+            correlation::spawn(async move {
                 match auth_client.get_user_by_id(user_id).await {
                     Ok(user_info) => {
                         let result = email_service.send_order_ready_notification(
@@ -171,3 +192,13 @@ impl KitchenService {
     }
 }
 
+/// Hide tickets of other restaurants from a caller confined to one, such as a
+/// kitchen printer's API key
+fn in_scope(ticket: KitchenTicket, restaurant_scope: Option<Uuid>) -> Result<KitchenTicket, AppError> {
+    match restaurant_scope {
+        Some(restaurant_id) if restaurant_id != ticket.restaurant_id => {
+            Err(AppError::NotFound("Kitchen ticket not found".to_string()))
+        }
+        _ => Ok(ticket),
+    }
+}
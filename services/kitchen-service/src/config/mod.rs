@@ -7,6 +7,7 @@ pub struct Config {
     pub kafka: KafkaConfig,
     pub smtp: SmtpConfig,
     pub auth_service: AuthServiceConfig,
+    pub identity: IdentityConfig,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -41,6 +42,11 @@ pub struct AuthServiceConfig {
     pub url: String,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct IdentityConfig {
+    pub signing_secret: String,
+}
+
 impl Config {
     pub fn from_env() -> Result<Self, config::ConfigError> {
         let server_host = std::env::var("KITCHEN_SERVICE_HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
@@ -75,6 +81,9 @@ impl Config {
         let auth_service_url = std::env::var("AUTH_SERVICE_URL")
             .unwrap_or_else(|_| "http://localhost:8001".to_string());
 
+        let identity_signing_secret = std::env::var("IDENTITY_SIGNING_SECRET")
+            .map_err(|_| config::ConfigError::NotFound("IDENTITY_SIGNING_SECRET".to_string()))?;
+
         Ok(Config {
             server: ServerConfig {
                 host: server_host,
@@ -98,6 +107,9 @@ impl Config {
             auth_service: AuthServiceConfig {
                 url: auth_service_url,
             },
+            identity: IdentityConfig {
+                signing_secret: identity_signing_secret,
+            },
         })
     }
 }
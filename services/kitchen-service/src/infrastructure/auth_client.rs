@@ -28,9 +28,14 @@ impl AuthClient {
     pub async fn get_user_by_id(&self, user_id: Uuid) -> Result<UserInfo, AppError> {
         let url = format!("{}/internal/users/{}", self.base_url, user_id);
 
-        let response = self
-            .client
-            .get(&url)
+        let mut request = self.client.get(&url);
+
+        // Propagate the correlation id of the request being handled
+        if let Some(request_id) = correlation::current() {
+            request = request.header(correlation::REQUEST_ID_HEADER, request_id);
+        }
+
+        let response = request
             .send()
             .await
             .map_err(|e| {
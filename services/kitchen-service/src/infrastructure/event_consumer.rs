@@ -1,9 +1,8 @@
 use error_handling::AppError;
 use models::events::{event_types, OrderPlacedEvent};
 use models::kitchen::KitchenTicketItem;
-use rdkafka::consumer::{Consumer, StreamConsumer};
+use kafka_client::MeteredConsumer;
 use rdkafka::message::Message;
-use rdkafka::ClientConfig;
 use sqlx::PgPool;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -11,7 +10,7 @@ use tokio::sync::Mutex;
 use super::kitchen_ticket_repository::KitchenTicketRepository;
 
 pub struct EventConsumer {
-    consumer: StreamConsumer,
+    consumer: MeteredConsumer,
     repository: Arc<Mutex<KitchenTicketRepository>>,
 }
 
@@ -22,18 +21,9 @@ impl EventConsumer {
         topic: &str,
         pool: PgPool,
     ) -> Result<Self, AppError> {
-        let consumer: StreamConsumer = ClientConfig::new()
-            .set("group.id", group_id)
-            .set("bootstrap.servers", brokers)
-            .set("enable.auto.commit", "true")
-            .set("auto.offset.reset", "earliest")
-            .create()
+        let consumer = kafka_client::create_consumer(brokers, group_id, &[topic])
             .map_err(|e| AppError::Kafka(format!("Failed to create consumer: {}", e)))?;
 
-        consumer
-            .subscribe(&[topic])
-            .map_err(|e| AppError::Kafka(format!("Failed to subscribe to topic: {}", e)))?;
-
         let repository = Arc::new(Mutex::new(KitchenTicketRepository::new(pool)));
 
         Ok(Self {
@@ -48,9 +38,17 @@ impl EventConsumer {
         loop {
             match self.consumer.recv().await {
                 Ok(message) => {
+                    kafka_client::record_consumed(&message);
+
                     if let Some(payload) = message.payload() {
                         if let Ok(payload_str) = std::str::from_utf8(payload) {
-                            if let Err(e) = self.handle_message(payload_str).await {
+                            let result = kafka_client::handle_in_context(
+                                &message,
+                                self.handle_message(payload_str),
+                            )
+                            .await;
+
+                            if let Err(e) = result {
                                 tracing::error!("Error handling message: {}", e);
                             }
                         }
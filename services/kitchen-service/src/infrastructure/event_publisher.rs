@@ -40,7 +40,7 @@ impl EventPublisher {
         // Publish asynchronously
         let producer = self.producer.clone();
         let topic = self.topic.clone();
-        tokio::spawn(async move {
+        correlation::spawn(async move {
             if let Err(e) = kafka_client::publish(&producer, &topic, &key, &payload).await {
                 tracing::error!("Failed to publish order.status_updated event: {}", e);
             } else {
@@ -78,33 +78,26 @@ impl KitchenTicketRepository {
         Ok(ticket)
     }
 
-    /// List all kitchen tickets with optional status filter
-    pub async fn list_tickets(&self, status_filter: Option<String>) -> Result<Vec<KitchenTicket>, AppError> {
-        let tickets = if let Some(status) = status_filter {
-            sqlx::query_as::<_, KitchenTicket>(
-                r#"
-                SELECT id, order_id, restaurant_id, user_id, status, items, special_instructions, created_at, updated_at
-                FROM kitchen.kitchen_tickets
-                WHERE status = $1
-                ORDER BY created_at DESC
-                "#
-            )
-            .bind(status)
-            .fetch_all(&self.pool)
-            .await
-        } else {
-            sqlx::query_as::<_, KitchenTicket>(
-                r#"
-                SELECT id, order_id, restaurant_id, user_id, status, items, special_instructions, created_at, updated_at
-                FROM kitchen.kitchen_tickets
-                ORDER BY created_at DESC
-                "#
-            )
-            .fetch_all(&self.pool)
-            .await
-        };
-
-        tickets.map_err(|e| AppError::Database(e.to_string()))
+    /// List kitchen tickets, optionally filtered by status and restaurant
+    pub async fn list_tickets(
+        &self,
+        status_filter: Option<String>,
+        restaurant_id: Option<Uuid>,
+    ) -> Result<Vec<KitchenTicket>, AppError> {
+        sqlx::query_as::<_, KitchenTicket>(
+            r#"
+            SELECT id, order_id, restaurant_id, user_id, status, items, special_instructions, created_at, updated_at
+            FROM kitchen.kitchen_tickets
+            WHERE ($1::text IS NULL OR status = $1)
+              AND ($2::uuid IS NULL OR restaurant_id = $2)
+            ORDER BY created_at DESC
+            "#
+        )
+        .bind(status_filter)
+        .bind(restaurant_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))
     }
 
     /// Update kitchen ticket status
@@ -3,7 +3,6 @@ mod config;
 mod infrastructure;
 mod presentation;
 
-use actix_cors::Cors;
 use actix_web::{middleware::Logger, web, App, HttpServer};
 use application::kitchen_service::KitchenService;
 use config::Config;
@@ -12,7 +11,6 @@ use infrastructure::email_service::EmailService;
 use infrastructure::event_consumer::EventConsumer;
 use infrastructure::event_publisher::EventPublisher;
 use infrastructure::kitchen_ticket_repository::KitchenTicketRepository;
-use sqlx::postgres::PgPoolOptions;
 use std::sync::Arc;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -36,9 +34,7 @@ async fn main() -> std::io::Result<()> {
     tracing::info!("Starting Kitchen Service on {}:{}", config.server.host, config.server.port);
 
     // Create database connection pool
-    let pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&config.database.url)
+    let pool = db_utils::create_pg_pool(&config.database.url, 5)
         .await
         .expect("Failed to create database pool");
 
@@ -102,18 +98,20 @@ async fn main() -> std::io::Result<()> {
     let server_host = config.server.host.clone();
     let server_port = config.server.port;
 
-    HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_method()
-            .allow_any_header()
-            .max_age(3600);
+    let api_doc = web::Data::new(presentation::api_doc::api_doc());
+    let identity_signer = web::Data::new(identity::IdentitySigner::new(&config.identity.signing_secret));
 
+    HttpServer::new(move || {
         App::new()
-            .wrap(cors)
             .wrap(Logger::default())
-            .wrap(tracing_actix_web::TracingLogger::default())
+            .wrap(tracing_actix_web::TracingLogger::<correlation::RequestIdRootSpan>::new())
+            .wrap(metrics::MetricsMiddleware::new("kitchen-service"))
+            .wrap(correlation::RequestIdMiddleware)
             .app_data(web::Data::from(kitchen_service.clone()))
+            .app_data(identity_signer.clone())
+            .route("/metrics", web::get().to(metrics::metrics_handler))
+            .app_data(api_doc.clone())
+            .route("/openapi.json", web::get().to(openapi::openapi_handler))
             .configure(presentation::configure_routes)
     })
     .bind((server_host.as_str(), server_port))?
@@ -0,0 +1,21 @@
+use openapi::ServiceDefaults;
+use utoipa::OpenApi;
+
+use super::handlers;
+
+/// OpenAPI document of the public routes, served at `/openapi.json`
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        handlers::list_tickets,
+        handlers::get_ticket,
+        handlers::get_ticket_by_order,
+        handlers::update_ticket_status,
+    ),
+    modifiers(&ServiceDefaults)
+)]
+pub struct ApiDoc;
+
+pub fn api_doc() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
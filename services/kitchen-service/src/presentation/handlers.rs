@@ -1,7 +1,8 @@
-use actix_web::{web, HttpResponse, Responder};
-use error_handling::AppError;
-use models::kitchen::UpdateKitchenTicketStatusRequest;
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use error_handling::{AppError, ErrorResponse};
+use models::kitchen::{KitchenTicketResponse, UpdateKitchenTicketStatusRequest};
 use serde::Deserialize;
+use utoipa::IntoParams;
 use uuid::Uuid;
 use validator::Validate;
 
@@ -17,33 +18,96 @@ pub async fn health_check() -> impl Responder {
 
 /// List kitchen tickets
 /// GET /api/kitchen/tickets?status=NEW
+#[utoipa::path(
+    get,
+    path = "/api/kitchen/tickets",
+    summary = "List kitchen tickets",
+    params(ListTicketsQuery),
+    responses(
+        (status = 200, description = "Tickets", body = Vec<KitchenTicketResponse>),
+    ),
+    security(("session" = []))
+)]
 pub async fn list_tickets(
+    req: HttpRequest,
     service: web::Data<KitchenService>,
     query: web::Query<ListTicketsQuery>,
 ) -> Result<HttpResponse, AppError> {
-    let tickets = service.list_tickets(query.status.clone()).await?;
+    let caller = identity::verified_identity(&req)?;
+    let tickets = service.list_tickets(query.status.clone(), caller.restaurant_id).await?;
     Ok(HttpResponse::Ok().json(tickets))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListTicketsQuery {
     pub status: Option<String>,
 }
 
 /// Get kitchen ticket by ID
 /// GET /api/kitchen/tickets/{id}
+#[utoipa::path(
+    get,
+    path = "/api/kitchen/tickets/{id}",
+    summary = "Get a kitchen ticket",
+    params(("id" = Uuid, Path, description = "Ticket id")),
+    responses(
+        (status = 200, description = "Ticket", body = KitchenTicketResponse),
+        (status = 404, description = "Ticket not found", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 pub async fn get_ticket(
+    req: HttpRequest,
     service: web::Data<KitchenService>,
     path: web::Path<Uuid>,
 ) -> Result<HttpResponse, AppError> {
+    let caller = identity::verified_identity(&req)?;
     let ticket_id = path.into_inner();
-    let ticket = service.get_ticket(ticket_id).await?;
+    let ticket = service.get_ticket(ticket_id, caller.restaurant_id).await?;
+    Ok(HttpResponse::Ok().json(ticket))
+}
+
+/// Get the kitchen ticket for an order
+/// GET /api/kitchen/orders/{order_id}/ticket
+#[utoipa::path(
+    get,
+    path = "/api/kitchen/orders/{order_id}/ticket",
+    summary = "Get the kitchen ticket of an order",
+    params(("order_id" = Uuid, Path, description = "Order id")),
+    responses(
+        (status = 200, description = "Ticket", body = KitchenTicketResponse),
+        (status = 404, description = "No ticket for the order", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
+pub async fn get_ticket_by_order(
+    req: HttpRequest,
+    service: web::Data<KitchenService>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let caller = identity::verified_identity(&req)?;
+    let order_id = path.into_inner();
+    let ticket = service.get_ticket_by_order_id(order_id, caller.restaurant_id).await?;
     Ok(HttpResponse::Ok().json(ticket))
 }
 
 /// Update kitchen ticket status
 /// PATCH /api/kitchen/tickets/{id}/status
+#[utoipa::path(
+    patch,
+    path = "/api/kitchen/tickets/{id}/status",
+    summary = "Move a ticket to its next status",
+    params(("id" = Uuid, Path, description = "Ticket id")),
+    request_body = UpdateKitchenTicketStatusRequest,
+    responses(
+        (status = 200, description = "Ticket updated", body = KitchenTicketResponse),
+        (status = 400, description = "Invalid status transition", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 pub async fn update_ticket_status(
+    req: HttpRequest,
     service: web::Data<KitchenService>,
     path: web::Path<Uuid>,
     body: web::Json<UpdateKitchenTicketStatusRequest>,
@@ -51,9 +115,10 @@ pub async fn update_ticket_status(
     body.validate()
         .map_err(|e| AppError::Validation(e.to_string()))?;
 
+    let caller = identity::verified_identity(&req)?;
     let ticket_id = path.into_inner();
     let ticket = service
-        .update_ticket_status(ticket_id, body.status.clone())
+        .update_ticket_status(ticket_id, body.status.clone(), caller.restaurant_id)
         .await?;
 
     Ok(HttpResponse::Ok().json(ticket))
@@ -1,3 +1,4 @@
+pub mod api_doc;
 pub mod handlers;
 
 use actix_web::web;
@@ -11,6 +12,10 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .route("", web::get().to(handlers::list_tickets))
                     .route("/{id}", web::get().to(handlers::get_ticket))
                     .route("/{id}/status", web::patch().to(handlers::update_ticket_status)),
+            )
+            .route(
+                "/kitchen/orders/{order_id}/ticket",
+                web::get().to(handlers::get_ticket_by_order),
             ),
     );
 }
@@ -97,7 +97,7 @@ impl OrderService {
         let delivery_address = request.delivery_address.clone();
         let special_instructions = request.special_instructions.clone();
 
-        tokio::spawn(async move {
+        correlation::spawn(async move {
             if let Err(e) = event_publisher
                 .publish_order_placed(
                     order_id,
@@ -178,17 +178,20 @@ impl OrderService {
         self.order_repository.list_user_orders(user_id).await
     }
 
-    /// Update order status
+    /// Update order status. A caller confined to `restaurant_scope`, such as a
+    /// POS terminal's API key, only sees that restaurant's orders.
     pub async fn update_order_status(
         &self,
         order_id: Uuid,
         new_status: String,
+        restaurant_scope: Option<Uuid>,
     ) -> Result<Order, AppError> {
         // Get current order
         let current_order = self
             .order_repository
             .get_order(order_id)
             .await?
+            .filter(|order| restaurant_scope.is_none_or(|restaurant_id| order.restaurant_id == restaurant_id))
             .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_id)))?;
 
         let old_status = current_order.status.clone();
@@ -208,7 +211,7 @@ impl OrderService {
         let event_publisher = self.event_publisher.clone();
         let restaurant_id = updated_order.restaurant_id;
 
-        tokio::spawn(async move {
+        correlation::spawn(async move {
             if let Err(e) = event_publisher
                 .publish_order_status_updated(order_id, restaurant_id, old_status, new_status)
                 .await
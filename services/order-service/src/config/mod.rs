@@ -6,6 +6,7 @@ pub struct Config {
     pub database: DatabaseConfig,
     pub kafka: KafkaConfig,
     pub restaurant_service: RestaurantServiceConfig,
+    pub identity: IdentityConfig,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -31,6 +32,11 @@ pub struct RestaurantServiceConfig {
     pub base_url: String,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct IdentityConfig {
+    pub signing_secret: String,
+}
+
 impl Config {
     pub fn from_env() -> Result<Self, config::ConfigError> {
         let server_host = std::env::var("ORDER_SERVICE_HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
@@ -52,6 +58,9 @@ impl Config {
         let restaurant_service_url = std::env::var("RESTAURANT_SERVICE_URL")
             .unwrap_or_else(|_| "http://localhost:8002".to_string());
 
+        let identity_signing_secret = std::env::var("IDENTITY_SIGNING_SECRET")
+            .map_err(|_| config::ConfigError::NotFound("IDENTITY_SIGNING_SECRET".to_string()))?;
+
         Ok(Config {
             server: ServerConfig {
                 host: server_host,
@@ -68,6 +77,9 @@ impl Config {
             restaurant_service: RestaurantServiceConfig {
                 base_url: restaurant_service_url,
             },
+            identity: IdentityConfig {
+                signing_secret: identity_signing_secret,
+            },
         })
     }
 }
@@ -31,9 +31,14 @@ impl RestaurantClient {
 
         tracing::info!("Fetching food details from: {}", url);
 
-        let response = self
-            .client
-            .get(&url)
+        let mut request = self.client.get(&url);
+
+        // Propagate the correlation id of the request being handled
+        if let Some(request_id) = correlation::current() {
+            request = request.header(correlation::REQUEST_ID_HEADER, request_id);
+        }
+
+        let response = request
             .send()
             .await
             .map_err(|e| {
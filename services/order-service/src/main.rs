@@ -3,9 +3,7 @@ mod config;
 mod infrastructure;
 mod presentation;
 
-use actix_cors::Cors;
 use actix_web::{middleware::Logger, web, App, HttpServer};
-use sqlx::postgres::PgPoolOptions;
 use tracing_subscriber::EnvFilter;
 
 use config::Config;
@@ -32,9 +30,7 @@ async fn main() -> std::io::Result<()> {
     tracing::info!("Configuration loaded");
 
     // Create database connection pool
-    let db_pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&config.database.url)
+    let db_pool = db_utils::create_pg_pool(&config.database.url, 5)
         .await
         .expect("Failed to connect to database");
     tracing::info!("Database connection established");
@@ -63,20 +59,27 @@ async fn main() -> std::io::Result<()> {
         restaurant_client,
     );
 
+    let identity_signer = web::Data::new(identity::IdentitySigner::new(&config.identity.signing_secret));
+
     let server_host = config.server.host.clone();
     let server_port = config.server.port;
 
     tracing::info!("Starting HTTP server on {}:{}", server_host, server_port);
 
+    let api_doc = web::Data::new(presentation::api_doc::api_doc());
+
     // Start HTTP server
     HttpServer::new(move || {
-        let cors = Cors::permissive();
-
         App::new()
-            .wrap(cors)
             .wrap(Logger::default())
-            .wrap(tracing_actix_web::TracingLogger::default())
+            .wrap(tracing_actix_web::TracingLogger::<correlation::RequestIdRootSpan>::new())
+            .wrap(metrics::MetricsMiddleware::new("order-service"))
+            .wrap(correlation::RequestIdMiddleware)
             .app_data(web::Data::new(order_service.clone()))
+            .route("/metrics", web::get().to(metrics::metrics_handler))
+            .app_data(api_doc.clone())
+            .route("/openapi.json", web::get().to(openapi::openapi_handler))
+            .app_data(identity_signer.clone())
             .service(
                 web::scope("/api")
                     .service(handlers::health_check)
@@ -0,0 +1,21 @@
+use openapi::ServiceDefaults;
+use utoipa::OpenApi;
+
+use super::handlers;
+
+/// OpenAPI document of the public routes, served at `/openapi.json`
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        handlers::create_order,
+        handlers::get_order,
+        handlers::list_user_orders,
+        handlers::update_order_status,
+    ),
+    modifiers(&ServiceDefaults)
+)]
+pub struct ApiDoc;
+
+pub fn api_doc() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
@@ -1,6 +1,6 @@
 use actix_web::{get, patch, post, web, HttpRequest, HttpResponse, Responder};
-use error_handling::AppError;
-use models::order::{CreateOrderRequest, UpdateOrderStatusRequest};
+use error_handling::{AppError, ErrorResponse};
+use models::order::{CreateOrderRequest, Order, OrderResponse, UpdateOrderStatusRequest};
 use serde_json::json;
 use uuid::Uuid;
 use validator::Validate;
@@ -16,16 +16,23 @@ pub async fn health_check() -> impl Responder {
     }))
 }
 
-/// Helper function to extract user_id from X-User-Id header
+/// Helper function to extract user_id from the gateway-signed identity headers
 fn get_user_id(req: &HttpRequest) -> Result<Uuid, AppError> {
-    req.headers()
-        .get("X-User-Id")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|s| Uuid::parse_str(s).ok())
-        .ok_or_else(|| AppError::Unauthorized("Missing or invalid X-User-Id header".to_string()))
+    Ok(identity::verified_identity(req)?.user_id)
 }
 
 /// Create a new order
+#[utoipa::path(
+    post,
+    path = "/api/orders",
+    summary = "Place an order",
+    request_body = CreateOrderRequest,
+    responses(
+        (status = 201, description = "Order placed", body = OrderResponse),
+        (status = 400, description = "Invalid order", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 #[post("/orders")]
 pub async fn create_order(
     req: HttpRequest,
@@ -47,6 +54,17 @@ pub async fn create_order(
 }
 
 /// Get order by ID
+#[utoipa::path(
+    get,
+    path = "/api/orders/{id}",
+    summary = "Get one of the caller's orders",
+    params(("id" = Uuid, Path, description = "Order id")),
+    responses(
+        (status = 200, description = "Order with its items", body = OrderResponse),
+        (status = 404, description = "Order not found", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 #[get("/orders/{id}")]
 pub async fn get_order(
     req: HttpRequest,
@@ -62,6 +80,15 @@ pub async fn get_order(
 }
 
 /// List user's orders
+#[utoipa::path(
+    get,
+    path = "/api/orders",
+    summary = "List the caller's orders",
+    responses(
+        (status = 200, description = "Orders", body = Vec<Order>),
+    ),
+    security(("session" = []))
+)]
 #[get("/orders")]
 pub async fn list_user_orders(
     req: HttpRequest,
@@ -75,8 +102,21 @@ pub async fn list_user_orders(
 }
 
 /// Update order status
+#[utoipa::path(
+    patch,
+    path = "/api/orders/{id}/status",
+    summary = "Update an order's status",
+    params(("id" = Uuid, Path, description = "Order id")),
+    request_body = UpdateOrderStatusRequest,
+    responses(
+        (status = 200, description = "Order updated", body = Order),
+        (status = 404, description = "Order not found", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 #[patch("/orders/{id}/status")]
 pub async fn update_order_status(
+    req: HttpRequest,
     order_service: web::Data<OrderService>,
     path: web::Path<Uuid>,
     request: web::Json<UpdateOrderStatusRequest>,
@@ -88,8 +128,9 @@ pub async fn update_order_status(
         AppError::Validation(format!("Invalid request: {}", e))
     })?;
 
+    let caller = identity::verified_identity(&req)?;
     let updated_order = order_service
-        .update_order_status(order_id, request.status.clone())
+        .update_order_status(order_id, request.status.clone(), caller.restaurant_id)
         .await?;
 
     Ok(HttpResponse::Ok().json(updated_order))
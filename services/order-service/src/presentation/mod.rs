@@ -1,2 +1,3 @@
+pub mod api_doc;
 pub mod handlers;
 
@@ -9,6 +9,7 @@ pub struct Config {
     pub redis: RedisConfig,
     pub kafka: KafkaConfig,
     pub cache: CacheConfig,
+    pub identity: IdentityConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -38,6 +39,11 @@ pub struct CacheConfig {
     pub menu_ttl_seconds: u64,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdentityConfig {
+    pub signing_secret: String,
+}
+
 impl Config {
     pub fn from_env() -> Result<Self> {
         Ok(Config {
@@ -62,6 +68,9 @@ impl Config {
                     .unwrap_or_else(|_| "300".to_string())
                     .parse()?,
             },
+            identity: IdentityConfig {
+                signing_secret: env::var("IDENTITY_SIGNING_SECRET")?,
+            },
         })
     }
 }
@@ -24,10 +24,12 @@ impl MenuCache {
             match serde_json::from_str::<MenuResponse>(&json) {
                 Ok(menu) => {
                     tracing::debug!("Menu cache hit for restaurant {}", restaurant_id);
+                    metrics::record_cache("menu", true);
                     Ok(Some(menu))
                 }
                 Err(e) => {
                     tracing::warn!("Failed to deserialize cached menu: {:?}", e);
+                    metrics::record_cache("menu", false);
                     // Invalidate corrupted cache
                     self.invalidate_menu(restaurant_id).await?;
                     Ok(None)
@@ -35,6 +37,7 @@ impl MenuCache {
             }
         } else {
             tracing::debug!("Menu cache miss for restaurant {}", restaurant_id);
+            metrics::record_cache("menu", false);
             Ok(None)
         }
     }
@@ -3,7 +3,6 @@ mod config;
 mod infrastructure;
 mod presentation;
 
-use actix_cors::Cors;
 use actix_web::{middleware::Logger, web, App, HttpServer};
 use application::RestaurantService;
 use infrastructure::{
@@ -35,7 +34,7 @@ async fn main() -> std::io::Result<()> {
     );
 
     // Create database pool
-    let db_pool = db_utils::create_pg_pool(&config.database.url)
+    let db_pool = db_utils::create_pg_pool(&config.database.url, 10)
         .await
         .expect("Failed to create database pool");
 
@@ -82,23 +81,25 @@ async fn main() -> std::io::Result<()> {
     );
 
     let restaurant_service_data = web::Data::new(tokio::sync::Mutex::new(restaurant_service));
+    let identity_signer_data = web::Data::new(identity::IdentitySigner::new(&config.identity.signing_secret));
 
     // Start HTTP server
     let server_host = config.server.host.clone();
     let server_port = config.server.port;
 
-    HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_method()
-            .allow_any_header()
-            .max_age(3600);
+    let api_doc = web::Data::new(presentation::api_doc::api_doc());
 
+    HttpServer::new(move || {
         App::new()
-            .wrap(cors)
             .wrap(Logger::default())
-            .wrap(tracing_actix_web::TracingLogger::default())
+            .wrap(tracing_actix_web::TracingLogger::<correlation::RequestIdRootSpan>::new())
+            .wrap(metrics::MetricsMiddleware::new("restaurant-service"))
+            .wrap(correlation::RequestIdMiddleware)
             .app_data(restaurant_service_data.clone())
+            .route("/metrics", web::get().to(metrics::metrics_handler))
+            .app_data(api_doc.clone())
+            .route("/openapi.json", web::get().to(openapi::openapi_handler))
+            .app_data(identity_signer_data.clone())
             .configure(presentation::configure_routes)
     })
     .bind((server_host.as_str(), server_port))?
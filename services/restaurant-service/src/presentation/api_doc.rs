@@ -0,0 +1,29 @@
+use openapi::ServiceDefaults;
+use utoipa::OpenApi;
+
+use super::handlers;
+
+/// OpenAPI document of the public routes, served at `/openapi.json`
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        handlers::create_restaurant,
+        handlers::get_restaurant,
+        handlers::get_owner_restaurants,
+        handlers::update_restaurant,
+        handlers::delete_restaurant,
+        handlers::create_category,
+        handlers::update_category,
+        handlers::delete_category,
+        handlers::create_food,
+        handlers::update_food,
+        handlers::delete_food,
+        handlers::get_menu,
+    ),
+    modifiers(&ServiceDefaults)
+)]
+pub struct ApiDoc;
+
+pub fn api_doc() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
@@ -1,5 +1,5 @@
 use actix_web::{web, HttpRequest, HttpResponse};
-use error_handling::{AppError, AppResult};
+use error_handling::{AppResult, ErrorResponse};
 use models::restaurant::*;
 use serde::Deserialize;
 use uuid::Uuid;
@@ -9,18 +9,25 @@ use crate::application::RestaurantService;
 
 // ==================== Helper Functions ====================
 
-/// Extract owner_id from X-Owner-Id header
+/// Extract owner_id from the gateway-signed identity headers
 fn get_owner_id(req: &HttpRequest) -> AppResult<Uuid> {
-    req.headers()
-        .get("X-Owner-Id")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|s| s.parse::<Uuid>().ok())
-        .ok_or_else(|| AppError::BadRequest("Missing or invalid X-Owner-Id header".to_string()))
+    Ok(identity::verified_identity(req)?.user_id)
 }
 
 // ==================== Restaurant Handlers ====================
 
 /// Create a new restaurant
+#[utoipa::path(
+    post,
+    path = "/api/restaurants",
+    summary = "Create a restaurant owned by the caller",
+    request_body = CreateRestaurantRequest,
+    responses(
+        (status = 201, description = "Restaurant created", body = Restaurant),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 pub async fn create_restaurant(
     service: web::Data<tokio::sync::Mutex<RestaurantService>>,
     request: web::Json<CreateRestaurantRequest>,
@@ -36,6 +43,16 @@ pub async fn create_restaurant(
 }
 
 /// Get restaurant by ID
+#[utoipa::path(
+    get,
+    path = "/api/restaurants/{restaurant_id}",
+    summary = "Get a restaurant",
+    params(("restaurant_id" = Uuid, Path, description = "Restaurant id")),
+    responses(
+        (status = 200, description = "Restaurant", body = Restaurant),
+        (status = 404, description = "Restaurant not found", body = ErrorResponse),
+    )
+)]
 pub async fn get_restaurant(
     service: web::Data<tokio::sync::Mutex<RestaurantService>>,
     path: web::Path<Uuid>,
@@ -49,6 +66,15 @@ pub async fn get_restaurant(
 }
 
 /// Get all restaurants for an owner
+#[utoipa::path(
+    get,
+    path = "/api/restaurants/my",
+    summary = "List the caller's restaurants",
+    responses(
+        (status = 200, description = "Restaurants", body = Vec<Restaurant>),
+    ),
+    security(("session" = []))
+)]
 pub async fn get_owner_restaurants(
     service: web::Data<tokio::sync::Mutex<RestaurantService>>,
     req: HttpRequest,
@@ -62,6 +88,18 @@ pub async fn get_owner_restaurants(
 }
 
 /// Update restaurant
+#[utoipa::path(
+    put,
+    path = "/api/restaurants/{restaurant_id}",
+    summary = "Update a restaurant",
+    params(("restaurant_id" = Uuid, Path, description = "Restaurant id")),
+    request_body = CreateRestaurantRequest,
+    responses(
+        (status = 200, description = "Restaurant updated", body = Restaurant),
+        (status = 403, description = "Not the owner", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 pub async fn update_restaurant(
     service: web::Data<tokio::sync::Mutex<RestaurantService>>,
     path: web::Path<Uuid>,
@@ -79,6 +117,17 @@ pub async fn update_restaurant(
 }
 
 /// Delete restaurant
+#[utoipa::path(
+    delete,
+    path = "/api/restaurants/{restaurant_id}",
+    summary = "Delete a restaurant",
+    params(("restaurant_id" = Uuid, Path, description = "Restaurant id")),
+    responses(
+        (status = 200, description = "Restaurant deleted", body = Object),
+        (status = 403, description = "Not the owner", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 pub async fn delete_restaurant(
     service: web::Data<tokio::sync::Mutex<RestaurantService>>,
     path: web::Path<Uuid>,
@@ -98,6 +147,18 @@ pub async fn delete_restaurant(
 // ==================== Category Handlers ====================
 
 /// Create a new food category
+#[utoipa::path(
+    post,
+    path = "/api/restaurants/{restaurant_id}/categories",
+    summary = "Create a menu category",
+    params(("restaurant_id" = Uuid, Path, description = "Restaurant id")),
+    request_body = CreateFoodCategoryRequest,
+    responses(
+        (status = 201, description = "Category created", body = FoodCategory),
+        (status = 403, description = "Not the owner", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 pub async fn create_category(
     service: web::Data<tokio::sync::Mutex<RestaurantService>>,
     path: web::Path<Uuid>,
@@ -121,6 +182,21 @@ pub struct CategoryPath {
 }
 
 /// Update food category
+#[utoipa::path(
+    put,
+    path = "/api/restaurants/{restaurant_id}/categories/{category_id}",
+    summary = "Update a menu category",
+    params(
+        ("restaurant_id" = Uuid, Path, description = "Restaurant id"),
+        ("category_id" = Uuid, Path, description = "Category id"),
+    ),
+    request_body = CreateFoodCategoryRequest,
+    responses(
+        (status = 200, description = "Category updated", body = FoodCategory),
+        (status = 404, description = "Category not found", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 pub async fn update_category(
     service: web::Data<tokio::sync::Mutex<RestaurantService>>,
     path: web::Path<CategoryPath>,
@@ -143,6 +219,20 @@ pub async fn update_category(
 }
 
 /// Delete food category
+#[utoipa::path(
+    delete,
+    path = "/api/restaurants/{restaurant_id}/categories/{category_id}",
+    summary = "Delete a menu category",
+    params(
+        ("restaurant_id" = Uuid, Path, description = "Restaurant id"),
+        ("category_id" = Uuid, Path, description = "Category id"),
+    ),
+    responses(
+        (status = 200, description = "Category deleted", body = Object),
+        (status = 404, description = "Category not found", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 pub async fn delete_category(
     service: web::Data<tokio::sync::Mutex<RestaurantService>>,
     path: web::Path<CategoryPath>,
@@ -162,6 +252,18 @@ pub async fn delete_category(
 // ==================== Food Handlers ====================
 
 /// Create a new food item
+#[utoipa::path(
+    post,
+    path = "/api/restaurants/{restaurant_id}/foods",
+    summary = "Add a food item",
+    params(("restaurant_id" = Uuid, Path, description = "Restaurant id")),
+    request_body = CreateFoodRequest,
+    responses(
+        (status = 201, description = "Food created", body = Food),
+        (status = 403, description = "Not the owner", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 pub async fn create_food(
     service: web::Data<tokio::sync::Mutex<RestaurantService>>,
     path: web::Path<Uuid>,
@@ -185,6 +287,21 @@ pub struct FoodPath {
 }
 
 /// Update food item
+#[utoipa::path(
+    put,
+    path = "/api/restaurants/{restaurant_id}/foods/{food_id}",
+    summary = "Update a food item",
+    params(
+        ("restaurant_id" = Uuid, Path, description = "Restaurant id"),
+        ("food_id" = Uuid, Path, description = "Food id"),
+    ),
+    request_body = CreateFoodRequest,
+    responses(
+        (status = 200, description = "Food updated", body = Food),
+        (status = 404, description = "Food not found", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 pub async fn update_food(
     service: web::Data<tokio::sync::Mutex<RestaurantService>>,
     path: web::Path<FoodPath>,
@@ -207,6 +324,20 @@ pub async fn update_food(
 }
 
 /// Delete food item
+#[utoipa::path(
+    delete,
+    path = "/api/restaurants/{restaurant_id}/foods/{food_id}",
+    summary = "Delete a food item",
+    params(
+        ("restaurant_id" = Uuid, Path, description = "Restaurant id"),
+        ("food_id" = Uuid, Path, description = "Food id"),
+    ),
+    responses(
+        (status = 200, description = "Food deleted", body = Object),
+        (status = 404, description = "Food not found", body = ErrorResponse),
+    ),
+    security(("session" = []))
+)]
 pub async fn delete_food(
     service: web::Data<tokio::sync::Mutex<RestaurantService>>,
     path: web::Path<FoodPath>,
@@ -226,6 +357,16 @@ pub async fn delete_food(
 // ==================== Menu Handlers ====================
 
 /// Get full menu for a restaurant
+#[utoipa::path(
+    get,
+    path = "/api/restaurants/{restaurant_id}/menu",
+    summary = "Get a restaurant's menu",
+    params(("restaurant_id" = Uuid, Path, description = "Restaurant id")),
+    responses(
+        (status = 200, description = "Menu grouped by category", body = MenuResponse),
+        (status = 404, description = "Restaurant not found", body = ErrorResponse),
+    )
+)]
 pub async fn get_menu(
     service: web::Data<tokio::sync::Mutex<RestaurantService>>,
     path: web::Path<Uuid>,
@@ -1,3 +1,4 @@
+pub mod api_doc;
 mod handlers;
 
 use actix_web::web;
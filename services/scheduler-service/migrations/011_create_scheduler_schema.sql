@@ -0,0 +1,9 @@
+-- Create scheduler schema
+CREATE SCHEMA IF NOT EXISTS scheduler;
+
+-- Jobs paused through the admin API; jobs without a row are active
+CREATE TABLE IF NOT EXISTS scheduler.jobs (
+    name VARCHAR(100) PRIMARY KEY,
+    paused BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,21 @@
+-- Create job_runs table
+CREATE TABLE IF NOT EXISTS scheduler.job_runs (
+    id UUID PRIMARY KEY,
+    job_name VARCHAR(100) NOT NULL,
+    trigger VARCHAR(20) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'RUNNING',
+    instance_id VARCHAR(255) NOT NULL,
+    request_id VARCHAR(128) NOT NULL,
+    message TEXT,
+    scheduled_at TIMESTAMPTZ,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ,
+
+    -- Constraints
+    CONSTRAINT valid_trigger CHECK (trigger IN ('schedule', 'manual')),
+    CONSTRAINT valid_status CHECK (status IN ('RUNNING', 'SUCCEEDED', 'FAILED', 'TIMED_OUT'))
+);
+
+-- Create indexes
+CREATE INDEX IF NOT EXISTS idx_job_runs_job_started ON scheduler.job_runs(job_name, started_at DESC);
+CREATE INDEX IF NOT EXISTS idx_job_runs_started ON scheduler.job_runs(started_at);
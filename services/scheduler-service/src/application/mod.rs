@@ -0,0 +1,5 @@
+pub mod registry;
+pub mod scheduler;
+
+pub use registry::{JobRegistry, RegisteredJob};
+pub use scheduler::Scheduler;
@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::domain::Job;
+
+/// A job with its parsed schedule
+#[derive(Clone)]
+pub struct RegisteredJob {
+    pub job: Arc<dyn Job>,
+    pub schedule: Schedule,
+}
+
+impl RegisteredJob {
+    /// First occurrence strictly after `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.schedule.after(&after).next()
+    }
+}
+
+/// Jobs known to the scheduler, in registration order
+#[derive(Default)]
+pub struct JobRegistry {
+    jobs: Vec<RegisteredJob>,
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a job, rejecting duplicate names and invalid schedules
+    pub fn register(&mut self, job: impl Job + 'static) -> anyhow::Result<()> {
+        if self.get(job.name()).is_some() {
+            anyhow::bail!("Job {} is registered twice", job.name());
+        }
+
+        let schedule = Schedule::from_str(job.schedule())
+            .map_err(|e| anyhow::anyhow!("Invalid schedule for job {}: {}", job.name(), e))?;
+
+        tracing::info!("Registered job {} ({})", job.name(), job.schedule());
+        self.jobs.push(RegisteredJob {
+            job: Arc::new(job),
+            schedule,
+        });
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&RegisteredJob> {
+        self.jobs.iter().find(|registered| registered.job.name() == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RegisteredJob> {
+        self.jobs.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{JobContext, JobFuture};
+    use chrono::TimeZone;
+
+    struct TestJob(&'static str, &'static str);
+
+    impl Job for TestJob {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn description(&self) -> &'static str {
+            "Test job"
+        }
+
+        fn schedule(&self) -> &str {
+            self.1
+        }
+
+        fn run(&self, _context: JobContext) -> JobFuture<'_> {
+            Box::pin(async { Ok(String::new()) })
+        }
+    }
+
+    #[test]
+    fn test_register() {
+        let mut registry = JobRegistry::new();
+        registry.register(TestJob("nightly", "0 30 3 * * *")).unwrap();
+
+        assert!(registry.register(TestJob("nightly", "0 0 * * * *")).is_err());
+        assert!(registry.register(TestJob("broken", "every night")).is_err());
+        assert_eq!(registry.iter().count(), 1);
+
+        let nightly = registry.get("nightly").unwrap();
+        let at = Utc.with_ymd_and_hms(2024, 5, 1, 3, 30, 0).unwrap();
+        assert_eq!(
+            nightly.next_after(at),
+            Some(Utc.with_ymd_and_hms(2024, 5, 2, 3, 30, 0).unwrap())
+        );
+    }
+}
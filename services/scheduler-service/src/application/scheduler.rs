@@ -0,0 +1,375 @@
+use chrono::{DateTime, Utc};
+use error_handling::{AppError, AppResult};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::application::{JobRegistry, RegisteredJob};
+use crate::config::SchedulerConfig;
+use crate::domain::{JobContext, JobRun, JobSummary, RunStatus, Trigger};
+use crate::infrastructure::{JobRunRepository, LockStore};
+
+/// How often due jobs are checked and the leader lock renewed
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Extra time a job lock outlives the job's timeout, so a run that is being
+/// recorded as timed out cannot overlap the next one
+const JOB_LOCK_MARGIN: Duration = Duration::from_secs(30);
+
+/// How often the leader looks for runs abandoned by a replica that stopped
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Runs registered jobs on their schedules.
+///
+/// Every replica ticks, but only the holder of the Redis leader lock starts
+/// scheduled runs. Manual triggers run on the replica that receives them.
+/// Either way a run holds a per-job lock, so runs of one job never overlap.
+pub struct Scheduler {
+    registry: JobRegistry,
+    locks: LockStore,
+    runs: JobRunRepository,
+    instance_id: String,
+    leader_ttl: Duration,
+    leader: AtomicBool,
+    /// Next occurrence of each job, tracked while this replica is leader
+    next_runs: Mutex<HashMap<&'static str, DateTime<Utc>>>,
+    /// When abandoned runs were last reconciled while this replica is leader
+    last_reconciled: Mutex<Option<Instant>>,
+}
+
+impl Scheduler {
+    pub fn new(
+        registry: JobRegistry,
+        locks: LockStore,
+        runs: JobRunRepository,
+        config: &SchedulerConfig,
+    ) -> Self {
+        Self {
+            registry,
+            locks,
+            runs,
+            instance_id: config.instance_id.clone(),
+            leader_ttl: Duration::from_secs(config.leader_lock_ttl_seconds),
+            leader: AtomicBool::new(false),
+            next_runs: Mutex::new(HashMap::new()),
+            last_reconciled: Mutex::new(None),
+        }
+    }
+
+    /// Start the scheduling loop
+    pub fn spawn(self: &Arc<Self>) {
+        let scheduler = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(TICK_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                interval.tick().await;
+                scheduler.tick().await;
+            }
+        });
+    }
+
+    /// Give up leadership so another replica takes over without waiting for
+    /// the lock to expire
+    pub async fn shutdown(&self) {
+        if self.leader.swap(false, Ordering::AcqRel) {
+            if let Err(e) = self.locks.release_leader(&self.instance_id).await {
+                tracing::warn!("Failed to release scheduler leader lock: {}", e);
+            }
+            metrics::set_scheduler_leader(false);
+        }
+    }
+
+    async fn tick(self: &Arc<Self>) {
+        let leader = match self.locks.acquire_leader(&self.instance_id, self.leader_ttl).await {
+            Ok(leader) => leader,
+            Err(e) => {
+                tracing::warn!("Failed to renew scheduler leader lock: {}", e);
+                false
+            }
+        };
+
+        if self.leader.swap(leader, Ordering::AcqRel) != leader {
+            // A new leader schedules from now on; occurrences missed meanwhile are skipped
+            self.next_runs.lock().unwrap().clear();
+            *self.last_reconciled.lock().unwrap() = None;
+            if leader {
+                tracing::info!("Instance {} is now the scheduler leader", self.instance_id);
+            } else {
+                tracing::info!("Instance {} is no longer the scheduler leader", self.instance_id);
+            }
+        }
+        metrics::set_scheduler_leader(leader);
+
+        if !leader {
+            return;
+        }
+
+        if self.reconcile_due() {
+            if let Err(e) = self.reconcile_abandoned_runs().await {
+                tracing::error!("Failed to reconcile abandoned job runs: {}", e);
+            }
+        }
+
+        let due = self.take_due(Utc::now());
+        if due.is_empty() {
+            return;
+        }
+
+        let paused = match self.runs.find_paused().await {
+            Ok(paused) => paused,
+            Err(e) => {
+                tracing::error!("Failed to load paused jobs, skipping due runs: {}", e);
+                return;
+            }
+        };
+
+        for (job, scheduled_at) in due {
+            let name = job.job.name();
+            if paused.contains(name) {
+                tracing::debug!("Job {} is paused, skipping run due at {}", name, scheduled_at);
+                continue;
+            }
+
+            match self.locks.claim_occurrence(name, scheduled_at).await {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    tracing::error!("Failed to claim run of job {}: {}", name, e);
+                    continue;
+                }
+            }
+
+            match self
+                .start_run(&job, Trigger::Schedule, Some(scheduled_at), correlation::generate())
+                .await
+            {
+                Ok(Some(_)) => {}
+                Ok(None) => tracing::warn!("Job {} is still running, skipping run due at {}", name, scheduled_at),
+                Err(e) => tracing::error!("Failed to start job {}: {}", name, e),
+            }
+        }
+    }
+
+    /// Jobs whose next occurrence has come, advancing each to the one after
+    fn take_due(&self, now: DateTime<Utc>) -> Vec<(RegisteredJob, DateTime<Utc>)> {
+        let mut next_runs = self.next_runs.lock().unwrap();
+        let mut due = Vec::new();
+
+        for job in self.registry.iter() {
+            let name = job.job.name();
+            let next = match next_runs.get(name) {
+                Some(next) => *next,
+                None => match job.next_after(now) {
+                    Some(next) => *next_runs.entry(name).or_insert(next),
+                    None => continue,
+                },
+            };
+
+            if next <= now {
+                due.push((job.clone(), next));
+                match job.next_after(now) {
+                    Some(following) => next_runs.insert(name, following),
+                    None => next_runs.remove(name),
+                };
+            }
+        }
+
+        due
+    }
+
+    /// Whether to look for abandoned runs: on becoming leader, then every
+    /// `RECONCILE_INTERVAL`
+    fn reconcile_due(&self) -> bool {
+        let mut last = self.last_reconciled.lock().unwrap();
+        if last.is_some_and(|at| at.elapsed() < RECONCILE_INTERVAL) {
+            return false;
+        }
+        *last = Some(Instant::now());
+        true
+    }
+
+    /// Record runs left `RUNNING` by a replica that stopped mid-run as failed.
+    /// A live run holds its job's lock under its own id from before its row
+    /// is written until after its outcome is, so a `RUNNING` row whose lock
+    /// has expired or passed to another run was abandoned.
+    async fn reconcile_abandoned_runs(&self) -> AppResult<()> {
+        for run in self.runs.find_running().await? {
+            let owner = self.locks.job_lock_owner(&run.job_name).await?;
+            if owner.as_deref() == Some(run.id.to_string().as_str()) {
+                continue;
+            }
+
+            let message = format!(
+                "Abandoned: instance {} stopped before the run finished",
+                run.instance_id
+            );
+            if self.runs.fail_abandoned(run.id, &message).await? {
+                tracing::warn!(
+                    "Marked run {} of job {} as failed, abandoned by instance {}",
+                    run.id,
+                    run.job_name,
+                    run.instance_id
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Run a job now, whether or not it is paused
+    pub async fn trigger(self: &Arc<Self>, name: &str, request_id: String) -> AppResult<JobRun> {
+        let job = self.job(name)?.clone();
+
+        self.start_run(&job, Trigger::Manual, None, request_id)
+            .await?
+            .ok_or_else(|| AppError::Conflict(format!("Job {} is already running", name)))
+    }
+
+    /// Lock the job, record the run and execute it in the background.
+    /// Returns `None` if a run of the job is already in progress.
+    async fn start_run(
+        self: &Arc<Self>,
+        job: &RegisteredJob,
+        trigger: Trigger,
+        scheduled_at: Option<DateTime<Utc>>,
+        request_id: String,
+    ) -> AppResult<Option<JobRun>> {
+        let name = job.job.name();
+        let run_id = Uuid::new_v4();
+        let lock_ttl = job.job.timeout() + JOB_LOCK_MARGIN;
+
+        if !self.locks.acquire_job(name, &run_id.to_string(), lock_ttl).await? {
+            return Ok(None);
+        }
+
+        let run = match self
+            .runs
+            .start(run_id, name, trigger, &self.instance_id, &request_id, scheduled_at)
+            .await
+        {
+            Ok(run) => run,
+            Err(e) => {
+                self.release_job(name, run_id).await;
+                return Err(e);
+            }
+        };
+
+        let context = JobContext { run_id, trigger };
+        tokio::spawn(self.clone().execute(job.clone(), context, request_id));
+
+        Ok(Some(run))
+    }
+
+    async fn execute(self: Arc<Self>, job: RegisteredJob, context: JobContext, request_id: String) {
+        let name = job.job.name();
+        let run_id = context.run_id;
+        let trigger = context.trigger;
+        let timeout = job.job.timeout();
+        let span = tracing::info_span!(
+            "job_run",
+            job = name,
+            run_id = %run_id,
+            trigger = trigger.as_str(),
+            correlation_id = %request_id,
+        );
+
+        tracing::info!(parent: &span, "Job {} started", name);
+        let started = Instant::now();
+
+        // Run in its own task so a panic is recorded as a failure
+        let runner = job.job.clone();
+        let mut handle = tokio::spawn(correlation::scope(
+            request_id,
+            async move { runner.run(context).await }.instrument(span.clone()),
+        ));
+
+        let (status, message) = match tokio::time::timeout(timeout, &mut handle).await {
+            Ok(Ok(Ok(summary))) => (RunStatus::Succeeded, summary),
+            Ok(Ok(Err(e))) => (RunStatus::Failed, format!("{:#}", e)),
+            Ok(Err(e)) => (RunStatus::Failed, format!("Job panicked: {}", e)),
+            Err(_) => {
+                handle.abort();
+                (RunStatus::TimedOut, format!("Timed out after {}s", timeout.as_secs()))
+            }
+        };
+
+        let elapsed = started.elapsed();
+        if status == RunStatus::Succeeded {
+            tracing::info!(parent: &span, "Job {} succeeded in {:?}: {}", name, elapsed, message);
+        } else {
+            tracing::error!(parent: &span, "Job {} {} after {:?}: {}", name, status.as_str(), elapsed, message);
+        }
+        metrics::record_job_run(name, trigger.as_str(), &status.as_str().to_ascii_lowercase(), elapsed);
+
+        if let Err(e) = self.runs.finish(run_id, status, &message).await {
+            tracing::error!(parent: &span, "Failed to record outcome of job {}: {}", name, e);
+        }
+        self.release_job(name, run_id).await;
+    }
+
+    async fn release_job(&self, name: &str, run_id: Uuid) {
+        // The lock expires on its own if this fails
+        if let Err(e) = self.locks.release_job(name, &run_id.to_string()).await {
+            tracing::warn!("Failed to release lock of job {}: {}", name, e);
+        }
+    }
+
+    /// Registered jobs with their state and latest run
+    pub async fn list(&self) -> AppResult<Vec<JobSummary>> {
+        let paused = self.runs.find_paused().await?;
+        let mut latest: HashMap<String, JobRun> = self
+            .runs
+            .find_latest()
+            .await?
+            .into_iter()
+            .map(|run| (run.job_name.clone(), run))
+            .collect();
+        let now = Utc::now();
+
+        Ok(self
+            .registry
+            .iter()
+            .map(|job| {
+                let name = job.job.name();
+                JobSummary {
+                    name,
+                    description: job.job.description(),
+                    schedule: job.job.schedule().to_string(),
+                    paused: paused.contains(name),
+                    next_run_at: job.next_after(now),
+                    last_run: latest.remove(name),
+                }
+            })
+            .collect())
+    }
+
+    /// Latest runs of a job, newest first
+    pub async fn runs(&self, name: &str, limit: i64) -> AppResult<Vec<JobRun>> {
+        self.job(name)?;
+        self.runs.find_by_job(name, limit).await
+    }
+
+    /// Pause or resume a job's scheduled runs; a run in progress is not stopped
+    pub async fn set_paused(&self, name: &str, paused: bool) -> AppResult<JobSummary> {
+        self.job(name)?;
+        self.runs.set_paused(name, paused).await?;
+        tracing::info!("Job {} {}", name, if paused { "paused" } else { "resumed" });
+
+        self.list()
+            .await?
+            .into_iter()
+            .find(|job| job.name == name)
+            .ok_or_else(|| AppError::NotFound(format!("Job not found: {}", name)))
+    }
+
+    fn job(&self, name: &str) -> AppResult<&RegisteredJob> {
+        self.registry
+            .get(name)
+            .ok_or_else(|| AppError::NotFound(format!("Job not found: {}", name)))
+    }
+}
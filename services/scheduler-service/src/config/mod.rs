@@ -0,0 +1,127 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub redis: RedisConfig,
+    pub scheduler: SchedulerConfig,
+    pub auth_service: AuthServiceConfig,
+    pub dormant_accounts: DormantAccountsConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DatabaseConfig {
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RedisConfig {
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SchedulerConfig {
+    /// Identifies this replica as lock holder and in run history
+    pub instance_id: String,
+    /// How long the leader lock survives without being renewed
+    pub leader_lock_ttl_seconds: u64,
+    /// Days of run history kept by the `prune_job_runs` job
+    pub history_retention_days: u32,
+    /// Enables the `/admin` endpoints when set
+    pub admin_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthServiceConfig {
+    pub url: String,
+    /// Sent as `X-Service-Token` to auth-service's internal endpoints
+    pub service_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DormantAccountsConfig {
+    /// Days without an OTP login after which an account is deactivated
+    pub inactive_days: u32,
+    /// Only report the accounts that would be deactivated
+    pub dry_run: bool,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, config::ConfigError> {
+        let server_host = std::env::var("SCHEDULER_SERVICE_HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
+        let server_port = std::env::var("SCHEDULER_SERVICE_PORT")
+            .unwrap_or_else(|_| "8007".to_string())
+            .parse()
+            .unwrap_or(8007);
+
+        let database_url = std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must be set");
+
+        let redis_url = std::env::var("REDIS_URL")
+            .unwrap_or_else(|_| "redis://localhost:6379".to_string());
+
+        let instance_id = std::env::var("SCHEDULER_INSTANCE_ID")
+            .or_else(|_| std::env::var("HOSTNAME"))
+            .unwrap_or_else(|_| uuid::Uuid::new_v4().to_string());
+        let leader_lock_ttl_seconds = std::env::var("SCHEDULER_LEADER_LOCK_TTL_SECONDS")
+            .unwrap_or_else(|_| "15".to_string())
+            .parse()
+            .unwrap_or(15);
+        let history_retention_days = std::env::var("SCHEDULER_HISTORY_RETENTION_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .unwrap_or(30);
+        let admin_token = std::env::var("SCHEDULER_ADMIN_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty());
+
+        let auth_service_url = std::env::var("AUTH_SERVICE_URL")
+            .unwrap_or_else(|_| "http://localhost:8001".to_string());
+        let internal_service_token = std::env::var("INTERNAL_SERVICE_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty());
+
+        let dormant_inactive_days = std::env::var("DORMANT_ACCOUNTS_INACTIVE_DAYS")
+            .unwrap_or_else(|_| "90".to_string())
+            .parse()
+            .unwrap_or(90);
+        let dormant_dry_run = std::env::var("DORMANT_ACCOUNTS_DRY_RUN")
+            .unwrap_or_else(|_| "true".to_string())
+            .parse()
+            .unwrap_or(true);
+
+        Ok(Config {
+            server: ServerConfig {
+                host: server_host,
+                port: server_port,
+            },
+            database: DatabaseConfig {
+                url: database_url,
+            },
+            redis: RedisConfig {
+                url: redis_url,
+            },
+            scheduler: SchedulerConfig {
+                instance_id,
+                leader_lock_ttl_seconds,
+                history_retention_days,
+                admin_token,
+            },
+            auth_service: AuthServiceConfig {
+                url: auth_service_url,
+                service_token: internal_service_token,
+            },
+            dormant_accounts: DormantAccountsConfig {
+                inactive_days: dormant_inactive_days,
+                dry_run: dormant_dry_run,
+            },
+        })
+    }
+}
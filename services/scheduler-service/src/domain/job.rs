@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Future returned by [`Job::run`]; resolves to a short summary recorded in
+/// the run history
+pub type JobFuture<'a> = Pin<Box<dyn Future<Output = anyhow::Result<String>> + Send + 'a>>;
+
+/// What started a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    Schedule,
+    Manual,
+}
+
+impl Trigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Trigger::Schedule => "schedule",
+            Trigger::Manual => "manual",
+        }
+    }
+}
+
+/// Details of the run a job is executing
+#[derive(Debug, Clone)]
+pub struct JobContext {
+    pub run_id: Uuid,
+    pub trigger: Trigger,
+}
+
+/// Periodic work run by the scheduler.
+///
+/// Only one run of a job is in progress at a time across all replicas, and
+/// each scheduled occurrence runs at most once. A run that misses its
+/// occurrence, e.g. during a leader change, is not caught up, so jobs should
+/// work through whatever is due rather than a fixed slice of time.
+pub trait Job: Send + Sync {
+    /// Unique name, used in the admin API, run history and metrics
+    fn name(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    /// Cron expression with a seconds field: `sec min hour day month weekday`
+    fn schedule(&self) -> &str;
+
+    /// Runs still going after this long are abandoned and recorded as timed out
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(15 * 60)
+    }
+
+    fn run(&self, context: JobContext) -> JobFuture<'_>;
+}
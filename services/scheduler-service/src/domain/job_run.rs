@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Outcome of a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    Running,
+    Succeeded,
+    Failed,
+    TimedOut,
+}
+
+impl RunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunStatus::Running => "RUNNING",
+            RunStatus::Succeeded => "SUCCEEDED",
+            RunStatus::Failed => "FAILED",
+            RunStatus::TimedOut => "TIMED_OUT",
+        }
+    }
+}
+
+/// One run of a job, as recorded in `scheduler.job_runs`
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct JobRun {
+    pub id: Uuid,
+    pub job_name: String,
+    pub trigger: String,
+    pub status: String,
+    /// Replica that ran the job
+    pub instance_id: String,
+    /// Correlation id of the run, also on its logs and published events
+    pub request_id: String,
+    /// Summary returned by the job, or the error it failed with
+    pub message: Option<String>,
+    pub scheduled_at: Option<DateTime<Utc>>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// A registered job as listed by the admin API
+#[derive(Debug, Clone, Serialize)]
+pub struct JobSummary {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub schedule: String,
+    pub paused: bool,
+    /// Next occurrence; runs only if the job is not paused then
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run: Option<JobRun>,
+}
@@ -0,0 +1,5 @@
+pub mod job;
+pub mod job_run;
+
+pub use job::{Job, JobContext, JobFuture, Trigger};
+pub use job_run::{JobRun, JobSummary, RunStatus};
@@ -0,0 +1,64 @@
+use error_handling::AppError;
+use models::user::{DeactivateDormantRequest, DormantAccountsReport};
+use reqwest::Client;
+
+/// Client for auth-service's internal endpoints
+#[derive(Clone)]
+pub struct AuthClient {
+    client: Client,
+    base_url: String,
+    service_token: Option<String>,
+}
+
+impl AuthClient {
+    pub fn new(base_url: String, service_token: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            base_url,
+            service_token,
+        }
+    }
+
+    /// Deactivate accounts without a login for `inactive_days`, or on a dry
+    /// run only list them
+    pub async fn deactivate_dormant_users(
+        &self,
+        inactive_days: u32,
+        dry_run: bool,
+    ) -> Result<DormantAccountsReport, AppError> {
+        let url = format!("{}/internal/users/deactivate-dormant", self.base_url);
+
+        let mut request = self.client.post(&url).json(&DeactivateDormantRequest {
+            inactive_days,
+            dry_run,
+        });
+
+        if let Some(token) = &self.service_token {
+            request = request.header("X-Service-Token", token);
+        }
+
+        // Propagate the correlation id of the run
+        if let Some(request_id) = correlation::current() {
+            request = request.header(correlation::REQUEST_ID_HEADER, request_id);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Failed to call Auth Service: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(AppError::ExternalService(format!(
+                "Auth Service error: {} - {}",
+                status, error_text
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Failed to parse dormant accounts: {}", e)))
+    }
+}
@@ -0,0 +1,176 @@
+use chrono::{DateTime, Utc};
+use error_handling::{AppError, AppResult};
+use sqlx::PgPool;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::domain::{JobRun, RunStatus, Trigger};
+
+const JOB_RUN_COLUMNS: &str =
+    "id, job_name, trigger, status, instance_id, request_id, message, scheduled_at, started_at, finished_at";
+
+/// Run history and pause state of jobs
+#[derive(Clone)]
+pub struct JobRunRepository {
+    pool: PgPool,
+}
+
+impl JobRunRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record the start of a run
+    pub async fn start(
+        &self,
+        id: Uuid,
+        job_name: &str,
+        trigger: Trigger,
+        instance_id: &str,
+        request_id: &str,
+        scheduled_at: Option<DateTime<Utc>>,
+    ) -> AppResult<JobRun> {
+        sqlx::query_as::<_, JobRun>(&format!(
+            r#"
+            INSERT INTO scheduler.job_runs (id, job_name, trigger, status, instance_id, request_id, scheduled_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING {}
+            "#,
+            JOB_RUN_COLUMNS
+        ))
+        .bind(id)
+        .bind(job_name)
+        .bind(trigger.as_str())
+        .bind(RunStatus::Running.as_str())
+        .bind(instance_id)
+        .bind(request_id)
+        .bind(scheduled_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Record how a run ended
+    pub async fn finish(&self, id: Uuid, status: RunStatus, message: &str) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE scheduler.job_runs
+            SET status = $2, message = $3, finished_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(status.as_str())
+        .bind(message)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Runs recorded as in progress
+    pub async fn find_running(&self) -> AppResult<Vec<JobRun>> {
+        sqlx::query_as::<_, JobRun>(&format!(
+            "SELECT {} FROM scheduler.job_runs WHERE status = $1",
+            JOB_RUN_COLUMNS
+        ))
+        .bind(RunStatus::Running.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Record a run that never reported its outcome as failed, unless it has
+    /// finished meanwhile. Returns whether the run was still in progress.
+    pub async fn fail_abandoned(&self, id: Uuid, message: &str) -> AppResult<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE scheduler.job_runs
+            SET status = $2, message = $3, finished_at = NOW()
+            WHERE id = $1 AND status = $4
+            "#,
+        )
+        .bind(id)
+        .bind(RunStatus::Failed.as_str())
+        .bind(message)
+        .bind(RunStatus::Running.as_str())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Latest runs of a job, newest first
+    pub async fn find_by_job(&self, job_name: &str, limit: i64) -> AppResult<Vec<JobRun>> {
+        sqlx::query_as::<_, JobRun>(&format!(
+            r#"
+            SELECT {}
+            FROM scheduler.job_runs
+            WHERE job_name = $1
+            ORDER BY started_at DESC
+            LIMIT $2
+            "#,
+            JOB_RUN_COLUMNS
+        ))
+        .bind(job_name)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Latest run of each job that has run
+    pub async fn find_latest(&self) -> AppResult<Vec<JobRun>> {
+        sqlx::query_as::<_, JobRun>(&format!(
+            r#"
+            SELECT DISTINCT ON (job_name) {}
+            FROM scheduler.job_runs
+            ORDER BY job_name, started_at DESC
+            "#,
+            JOB_RUN_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// Delete runs started before `cutoff`, returning how many were deleted
+    pub async fn delete_before(&self, cutoff: DateTime<Utc>) -> AppResult<u64> {
+        let result = sqlx::query("DELETE FROM scheduler.job_runs WHERE started_at < $1")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Names of paused jobs
+    pub async fn find_paused(&self) -> AppResult<HashSet<String>> {
+        let names: Vec<(String,)> = sqlx::query_as("SELECT name FROM scheduler.jobs WHERE paused")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(names.into_iter().map(|(name,)| name).collect())
+    }
+
+    pub async fn set_paused(&self, job_name: &str, paused: bool) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO scheduler.jobs (name, paused, updated_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (name) DO UPDATE SET paused = EXCLUDED.paused, updated_at = NOW()
+            "#,
+        )
+        .bind(job_name)
+        .bind(paused)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,271 @@
+use chrono::{DateTime, Utc};
+use error_handling::{AppError, AppResult};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use std::time::Duration;
+
+/// Takes the lock if it is free, or extends it if `ARGV[1]` already holds it
+const ACQUIRE_SCRIPT: &str = r#"
+local owner = redis.call('GET', KEYS[1])
+if owner == ARGV[1] then
+    redis.call('PEXPIRE', KEYS[1], ARGV[2])
+    return 1
+end
+if not owner then
+    redis.call('SET', KEYS[1], ARGV[1], 'PX', ARGV[2])
+    return 1
+end
+return 0
+"#;
+
+/// Deletes the lock only if `ARGV[1]` holds it
+const RELEASE_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// Records an occurrence as claimed unless it, or a later one, already is
+const CLAIM_SCRIPT: &str = r#"
+local last = tonumber(redis.call('GET', KEYS[1]) or '0')
+if tonumber(ARGV[1]) > last then
+    redis.call('SET', KEYS[1], ARGV[1])
+    return 1
+end
+return 0
+"#;
+
+const LEADER_KEY: &str = "scheduler:leader";
+
+/// Redis locks coordinating the scheduler replicas
+#[derive(Clone)]
+pub struct LockStore {
+    redis: ConnectionManager,
+    acquire_script: redis::Script,
+    release_script: redis::Script,
+    claim_script: redis::Script,
+}
+
+impl LockStore {
+    pub fn new(redis: ConnectionManager) -> Self {
+        Self {
+            redis,
+            acquire_script: redis::Script::new(ACQUIRE_SCRIPT),
+            release_script: redis::Script::new(RELEASE_SCRIPT),
+            claim_script: redis::Script::new(CLAIM_SCRIPT),
+        }
+    }
+
+    /// Take or renew the leader lock; only the leader starts scheduled runs
+    pub async fn acquire_leader(&self, instance_id: &str, ttl: Duration) -> AppResult<bool> {
+        self.acquire(LEADER_KEY, instance_id, ttl).await
+    }
+
+    pub async fn release_leader(&self, instance_id: &str) -> AppResult<()> {
+        self.release(LEADER_KEY, instance_id).await
+    }
+
+    /// Lock a job for one run, so runs never overlap
+    pub async fn acquire_job(&self, job_name: &str, run_id: &str, ttl: Duration) -> AppResult<bool> {
+        self.acquire(&job_key(job_name), run_id, ttl).await
+    }
+
+    pub async fn release_job(&self, job_name: &str, run_id: &str) -> AppResult<()> {
+        self.release(&job_key(job_name), run_id).await
+    }
+
+    /// Run id holding a job's lock, if it is held
+    pub async fn job_lock_owner(&self, job_name: &str) -> AppResult<Option<String>> {
+        self.redis
+            .clone()
+            .get(job_key(job_name))
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))
+    }
+
+    /// Claim a scheduled occurrence of a job. Fails if it was already
+    /// claimed, e.g. by a previous leader whose lock has not yet expired.
+    pub async fn claim_occurrence(&self, job_name: &str, scheduled_at: DateTime<Utc>) -> AppResult<bool> {
+        let claimed: i32 = self
+            .claim_script
+            .key(format!("scheduler:job:{}:scheduled", job_name))
+            .arg(scheduled_at.timestamp())
+            .invoke_async(&mut self.redis.clone())
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        Ok(claimed == 1)
+    }
+
+    async fn acquire(&self, key: &str, owner: &str, ttl: Duration) -> AppResult<bool> {
+        let acquired: i32 = self
+            .acquire_script
+            .key(key)
+            .arg(owner)
+            .arg(ttl.as_millis() as u64)
+            .invoke_async(&mut self.redis.clone())
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        Ok(acquired == 1)
+    }
+
+    async fn release(&self, key: &str, owner: &str) -> AppResult<()> {
+        self.release_script
+            .key(key)
+            .arg(owner)
+            .invoke_async::<_, i32>(&mut self.redis.clone())
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+fn job_key(job_name: &str) -> String {
+    format!("scheduler:job:{}:lock", job_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+    use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    type Keys = Arc<Mutex<HashMap<String, (String, Option<Instant>)>>>;
+
+    /// A lock store on an in-process Redis that knows GET and runs the lock
+    /// scripts, looked up by their hash, with the same semantics as the Lua
+    async fn lock_store() -> LockStore {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let keys = Keys::default();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(serve(socket, keys.clone()));
+            }
+        });
+
+        let client = redis::Client::open(format!("redis://{}", addr)).unwrap();
+        LockStore::new(ConnectionManager::new(client).await.unwrap())
+    }
+
+    async fn serve(socket: TcpStream, keys: Keys) {
+        let scripts: HashMap<String, &str> = [ACQUIRE_SCRIPT, RELEASE_SCRIPT, CLAIM_SCRIPT]
+            .into_iter()
+            .map(|script| (redis::Script::new(script).get_hash().to_string(), script))
+            .collect();
+        let (reader, mut writer) = socket.into_split();
+        let mut reader = BufReader::new(reader);
+
+        while let Some(args) = read_args(&mut reader).await {
+            let reply = {
+                let mut keys = keys.lock().unwrap();
+                keys.retain(|_, (_, expires)| expires.is_none_or(|at| at > Instant::now()));
+
+                match args[0].to_ascii_uppercase().as_str() {
+                    "GET" => match keys.get(&args[1]) {
+                        Some((value, _)) => format!("${}\r\n{}\r\n", value.len(), value),
+                        None => "$-1\r\n".to_string(),
+                    },
+                    "EVALSHA" => {
+                        let (key, argv) = (args[3].clone(), &args[4..]);
+                        let owner = keys.get(&key).map(|(value, _)| value.clone());
+                        let done = match scripts[&args[1]] {
+                            ACQUIRE_SCRIPT if owner.is_none() || owner.as_ref() == Some(&argv[0]) => {
+                                let ttl = Duration::from_millis(argv[1].parse().unwrap());
+                                keys.insert(key, (argv[0].clone(), Some(Instant::now() + ttl)));
+                                true
+                            }
+                            RELEASE_SCRIPT if owner.as_ref() == Some(&argv[0]) => {
+                                keys.remove(&key);
+                                true
+                            }
+                            CLAIM_SCRIPT
+                                if argv[0].parse::<i64>().unwrap()
+                                    > owner.map_or(0, |last| last.parse().unwrap()) =>
+                            {
+                                keys.insert(key, (argv[0].clone(), None));
+                                true
+                            }
+                            _ => false,
+                        };
+                        format!(":{}\r\n", done as i32)
+                    }
+                    _ => "+OK\r\n".to_string(),
+                }
+            };
+
+            if writer.write_all(reply.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    async fn read_args<R: AsyncBufRead + Unpin>(reader: &mut R) -> Option<Vec<String>> {
+        let mut line = String::new();
+        reader.read_line(&mut line).await.ok().filter(|read| *read > 0)?;
+        let count: usize = line.trim_end().strip_prefix('*')?.parse().ok()?;
+
+        let mut args = Vec::with_capacity(count);
+        for _ in 0..count {
+            line.clear();
+            reader.read_line(&mut line).await.ok()?;
+            let len: usize = line.trim_end().strip_prefix('$')?.parse().ok()?;
+            let mut arg = vec![0; len + 2];
+            reader.read_exact(&mut arg).await.ok()?;
+            args.push(String::from_utf8_lossy(&arg[..len]).into_owned());
+        }
+        Some(args)
+    }
+
+    #[tokio::test]
+    async fn test_claim_occurrence() {
+        let locks = lock_store().await;
+        let at = |hour| Utc.with_ymd_and_hms(2024, 5, 1, hour, 0, 0).unwrap();
+
+        assert!(locks.claim_occurrence("nightly", at(3)).await.unwrap());
+        // A second leader, or one that took over, does not run it again
+        assert!(!locks.claim_occurrence("nightly", at(3)).await.unwrap());
+        assert!(!locks.claim_occurrence("nightly", at(2)).await.unwrap());
+
+        assert!(locks.claim_occurrence("nightly", at(4)).await.unwrap());
+        assert!(locks.claim_occurrence("hourly", at(3)).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_job_lock_contention() {
+        let locks = lock_store().await;
+        let ttl = Duration::from_secs(60);
+
+        assert!(locks.acquire_job("nightly", "run-1", ttl).await.unwrap());
+        assert!(!locks.acquire_job("nightly", "run-2", ttl).await.unwrap());
+        assert!(locks.acquire_job("hourly", "run-2", ttl).await.unwrap());
+
+        // Only the holder releases the lock
+        locks.release_job("nightly", "run-2").await.unwrap();
+        assert_eq!(locks.job_lock_owner("nightly").await.unwrap().as_deref(), Some("run-1"));
+
+        locks.release_job("nightly", "run-1").await.unwrap();
+        assert_eq!(locks.job_lock_owner("nightly").await.unwrap(), None);
+        assert!(locks.acquire_job("nightly", "run-2", ttl).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_job_lock_expires() {
+        let locks = lock_store().await;
+
+        assert!(locks.acquire_job("nightly", "run-1", Duration::from_millis(50)).await.unwrap());
+        assert!(!locks.acquire_job("nightly", "run-2", Duration::from_secs(60)).await.unwrap());
+
+        // A replica that died mid-run leaves the lock to expire
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(locks.job_lock_owner("nightly").await.unwrap(), None);
+        assert!(locks.acquire_job("nightly", "run-2", Duration::from_secs(60)).await.unwrap());
+    }
+}
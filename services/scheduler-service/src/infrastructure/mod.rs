@@ -0,0 +1,7 @@
+pub mod auth_client;
+pub mod job_run_repository;
+pub mod lock_store;
+
+pub use auth_client::AuthClient;
+pub use job_run_repository::JobRunRepository;
+pub use lock_store::LockStore;
@@ -0,0 +1,145 @@
+use models::user::DormantAccountsReport;
+use std::time::Duration;
+
+use crate::config::DormantAccountsConfig;
+use crate::domain::{Job, JobContext, JobFuture};
+use crate::infrastructure::AuthClient;
+
+/// Accounts named in a run summary; the rest are only counted
+const MAX_LISTED_ACCOUNTS: usize = 20;
+
+/// Deactivates accounts that have not logged in for the configured window.
+/// auth-service does the work: it revokes their sessions and API keys and
+/// publishes `user.deactivated`.
+pub struct DeactivateDormantAccounts {
+    auth_client: AuthClient,
+    config: DormantAccountsConfig,
+}
+
+impl DeactivateDormantAccounts {
+    pub fn new(auth_client: AuthClient, config: DormantAccountsConfig) -> Self {
+        Self { auth_client, config }
+    }
+}
+
+impl Job for DeactivateDormantAccounts {
+    fn name(&self) -> &'static str {
+        "deactivate_dormant_accounts"
+    }
+
+    fn description(&self) -> &'static str {
+        "Deactivate accounts without a login for DORMANT_ACCOUNTS_INACTIVE_DAYS"
+    }
+
+    fn schedule(&self) -> &str {
+        "0 0 4 * * *"
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(60 * 60)
+    }
+
+    fn run(&self, _context: JobContext) -> JobFuture<'_> {
+        Box::pin(async move {
+            let report = self
+                .auth_client
+                .deactivate_dormant_users(self.config.inactive_days, self.config.dry_run)
+                .await?;
+
+            summarize(&report, self.config.inactive_days)
+        })
+    }
+}
+
+/// Run summary for a report. Accounts that failed fail the run, after the
+/// rest of the batch has been processed.
+fn summarize(report: &DormantAccountsReport, inactive_days: u32) -> anyhow::Result<String> {
+    let emails = list(report.accounts.iter().map(|account| account.email.clone()));
+    let verb = if report.dry_run { "Would deactivate" } else { "Deactivated" };
+    let summary = format!(
+        "{} {} accounts inactive for {} days: {}",
+        verb,
+        report.accounts.len(),
+        inactive_days,
+        emails
+    );
+
+    if report.failures.is_empty() {
+        return Ok(summary);
+    }
+
+    let failures = list(
+        report
+            .failures
+            .iter()
+            .map(|failure| format!("{} ({})", failure.email, failure.error)),
+    );
+    anyhow::bail!("{}; failed for {}: {}", summary, report.failures.len(), failures)
+}
+
+/// Join the first `MAX_LISTED_ACCOUNTS` items, marking any left out
+fn list(items: impl ExactSizeIterator<Item = String>) -> String {
+    let truncated = items.len() > MAX_LISTED_ACCOUNTS;
+    let mut listed: Vec<String> = items.take(MAX_LISTED_ACCOUNTS).collect();
+    if truncated {
+        listed.push("...".to_string());
+    }
+    listed.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::user::{DormantAccount, DormantAccountFailure};
+    use uuid::Uuid;
+
+    fn account(email: &str) -> DormantAccount {
+        DormantAccount {
+            user_id: Uuid::new_v4(),
+            email: email.to_string(),
+            last_verified_at: None,
+        }
+    }
+
+    #[test]
+    fn test_summary_lists_accounts() {
+        let report = DormantAccountsReport {
+            dry_run: true,
+            accounts: vec![account("a@example.com"), account("b@example.com")],
+            failures: Vec::new(),
+        };
+
+        assert_eq!(
+            summarize(&report, 90).unwrap(),
+            "Would deactivate 2 accounts inactive for 90 days: a@example.com, b@example.com"
+        );
+
+        let report = DormantAccountsReport {
+            dry_run: false,
+            accounts: (0..25).map(|i| account(&format!("{}@example.com", i))).collect(),
+            failures: Vec::new(),
+        };
+        let summary = summarize(&report, 90).unwrap();
+        assert!(summary.starts_with("Deactivated 25 accounts"));
+        assert!(summary.ends_with("19@example.com, ..."));
+    }
+
+    #[test]
+    fn test_failures_fail_the_run() {
+        let report = DormantAccountsReport {
+            dry_run: false,
+            accounts: vec![account("a@example.com")],
+            failures: vec![DormantAccountFailure {
+                user_id: Uuid::new_v4(),
+                email: "b@example.com".to_string(),
+                error: "Redis error".to_string(),
+            }],
+        };
+
+        let error = summarize(&report, 90).unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "Deactivated 1 accounts inactive for 90 days: a@example.com; failed for 1: b@example.com (Redis error)"
+        );
+    }
+}
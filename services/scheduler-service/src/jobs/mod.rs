@@ -0,0 +1,5 @@
+pub mod deactivate_dormant_accounts;
+pub mod prune_job_runs;
+
+pub use deactivate_dormant_accounts::DeactivateDormantAccounts;
+pub use prune_job_runs::PruneJobRuns;
@@ -0,0 +1,39 @@
+use chrono::{Duration, Utc};
+
+use crate::domain::{Job, JobContext, JobFuture};
+use crate::infrastructure::JobRunRepository;
+
+/// Deletes run history past the retention period
+pub struct PruneJobRuns {
+    runs: JobRunRepository,
+    retention_days: u32,
+}
+
+impl PruneJobRuns {
+    pub fn new(runs: JobRunRepository, retention_days: u32) -> Self {
+        Self { runs, retention_days }
+    }
+}
+
+impl Job for PruneJobRuns {
+    fn name(&self) -> &'static str {
+        "prune_job_runs"
+    }
+
+    fn description(&self) -> &'static str {
+        "Delete job run history past SCHEDULER_HISTORY_RETENTION_DAYS"
+    }
+
+    fn schedule(&self) -> &str {
+        "0 15 3 * * *"
+    }
+
+    fn run(&self, _context: JobContext) -> JobFuture<'_> {
+        Box::pin(async move {
+            let cutoff = Utc::now() - Duration::days(self.retention_days as i64);
+            let deleted = self.runs.delete_before(cutoff).await?;
+
+            Ok(format!("Deleted {} runs older than {} days", deleted, self.retention_days))
+        })
+    }
+}
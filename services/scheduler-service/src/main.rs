@@ -1 +1,110 @@
-fn main() { println!("Service not implemented yet"); }
+mod application;
+mod config;
+mod domain;
+mod infrastructure;
+mod jobs;
+mod presentation;
+
+use actix_web::{middleware::Logger, web, App, HttpServer};
+use application::{JobRegistry, Scheduler};
+use config::Config;
+use infrastructure::{AuthClient, JobRunRepository, LockStore};
+use std::sync::Arc;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    // Load environment variables
+    dotenvy::dotenv().ok();
+
+    // Initialize tracing
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "info,scheduler_service=debug".into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    // Load configuration
+    let config = Config::from_env().expect("Failed to load configuration");
+
+    tracing::info!(
+        "Starting Scheduler Service on {}:{} as instance {}",
+        config.server.host,
+        config.server.port,
+        config.scheduler.instance_id
+    );
+
+    // Create database connection pool
+    let pool = db_utils::create_pg_pool(&config.database.url, 10)
+        .await
+        .expect("Failed to create database pool");
+
+    tracing::info!("Database connection established");
+
+    // Migrations are run manually to avoid conflicts with shared _sqlx_migrations table
+    // Run: docker exec -i restman-cockroachdb cockroach sql --insecure --database=restman_db < services/scheduler-service/migrations/011_create_scheduler_schema.sql
+    // Run: docker exec -i restman-cockroachdb cockroach sql --insecure --database=restman_db < services/scheduler-service/migrations/012_create_job_runs_table.sql
+    tracing::info!("Skipping migrations (run manually)");
+
+    // Create Redis connection
+    let redis_conn = db_utils::create_redis_client(&config.redis.url)
+        .await
+        .expect("Failed to create Redis client");
+
+    tracing::info!("Redis connection established");
+
+    let job_runs = JobRunRepository::new(pool.clone());
+
+    // Register jobs
+    let mut registry = JobRegistry::new();
+    registry
+        .register(jobs::PruneJobRuns::new(
+            job_runs.clone(),
+            config.scheduler.history_retention_days,
+        ))
+        .expect("Failed to register job");
+    registry
+        .register(jobs::DeactivateDormantAccounts::new(
+            AuthClient::new(
+                config.auth_service.url.clone(),
+                config.auth_service.service_token.clone(),
+            ),
+            config.dormant_accounts.clone(),
+        ))
+        .expect("Failed to register job");
+
+    let scheduler = Arc::new(Scheduler::new(
+        registry,
+        LockStore::new(redis_conn),
+        job_runs,
+        &config.scheduler,
+    ));
+    scheduler.spawn();
+
+    // Start HTTP server
+    let server_host = config.server.host.clone();
+    let server_port = config.server.port;
+
+    let scheduler_data = web::Data::from(scheduler.clone());
+    let scheduler_config = web::Data::new(config.scheduler.clone());
+
+    HttpServer::new(move || {
+        App::new()
+            .wrap(Logger::default())
+            .wrap(tracing_actix_web::TracingLogger::<correlation::RequestIdRootSpan>::new())
+            .wrap(metrics::MetricsMiddleware::new("scheduler-service"))
+            .wrap(correlation::RequestIdMiddleware)
+            .app_data(scheduler_data.clone())
+            .app_data(scheduler_config.clone())
+            .route("/metrics", web::get().to(metrics::metrics_handler))
+            .configure(presentation::configure_routes)
+    })
+    .bind((server_host.as_str(), server_port))?
+    .run()
+    .await?;
+
+    scheduler.shutdown().await;
+    Ok(())
+}
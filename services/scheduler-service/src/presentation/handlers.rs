@@ -0,0 +1,100 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use error_handling::AppResult;
+use serde::Deserialize;
+
+use crate::application::Scheduler;
+use crate::config::SchedulerConfig;
+
+/// Default and maximum number of runs returned by `list_runs`
+const DEFAULT_RUN_LIMIT: i64 = 20;
+const MAX_RUN_LIMIT: i64 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct RunsQuery {
+    pub limit: Option<i64>,
+}
+
+/// Admin endpoints require `X-Admin-Token` to match `SCHEDULER_ADMIN_TOKEN`
+fn require_admin(req: &HttpRequest, config: &SchedulerConfig) -> AppResult<()> {
+    identity::require_token(req, "X-Admin-Token", config.admin_token.as_deref())
+}
+
+/// Health check handler
+pub async fn health_check() -> HttpResponse {
+    HttpResponse::Ok().json(serde_json::json!({
+        "status": "healthy",
+        "service": "scheduler-service"
+    }))
+}
+
+/// List registered jobs with their state and latest run
+/// GET /admin/jobs
+pub async fn list_jobs(
+    scheduler: web::Data<Scheduler>,
+    config: web::Data<SchedulerConfig>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    require_admin(&req, &config)?;
+
+    let jobs = scheduler.list().await?;
+    Ok(HttpResponse::Ok().json(jobs))
+}
+
+/// Run history of a job, newest first
+/// GET /admin/jobs/{name}/runs
+pub async fn list_runs(
+    scheduler: web::Data<Scheduler>,
+    config: web::Data<SchedulerConfig>,
+    path: web::Path<String>,
+    query: web::Query<RunsQuery>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    require_admin(&req, &config)?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_RUN_LIMIT).clamp(1, MAX_RUN_LIMIT);
+    let runs = scheduler.runs(&path, limit).await?;
+    Ok(HttpResponse::Ok().json(runs))
+}
+
+/// Start a run of a job now; the run continues after the response
+/// POST /admin/jobs/{name}/trigger
+pub async fn trigger_job(
+    scheduler: web::Data<Scheduler>,
+    config: web::Data<SchedulerConfig>,
+    path: web::Path<String>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    require_admin(&req, &config)?;
+
+    let request_id = correlation::current().unwrap_or_else(correlation::generate);
+    let run = scheduler.into_inner().trigger(&path, request_id).await?;
+    Ok(HttpResponse::Accepted().json(run))
+}
+
+/// Stop scheduled runs of a job
+/// POST /admin/jobs/{name}/pause
+pub async fn pause_job(
+    scheduler: web::Data<Scheduler>,
+    config: web::Data<SchedulerConfig>,
+    path: web::Path<String>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    require_admin(&req, &config)?;
+
+    let job = scheduler.set_paused(&path, true).await?;
+    Ok(HttpResponse::Ok().json(job))
+}
+
+/// Resume scheduled runs of a job
+/// POST /admin/jobs/{name}/resume
+pub async fn resume_job(
+    scheduler: web::Data<Scheduler>,
+    config: web::Data<SchedulerConfig>,
+    path: web::Path<String>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    require_admin(&req, &config)?;
+
+    let job = scheduler.set_paused(&path, false).await?;
+    Ok(HttpResponse::Ok().json(job))
+}
@@ -0,0 +1,18 @@
+pub mod handlers;
+
+use actix_web::web;
+
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api")
+            .route("/health", web::get().to(handlers::health_check)),
+    )
+    .service(
+        web::scope("/admin/jobs")
+            .route("", web::get().to(handlers::list_jobs))
+            .route("/{name}/runs", web::get().to(handlers::list_runs))
+            .route("/{name}/trigger", web::post().to(handlers::trigger_job))
+            .route("/{name}/pause", web::post().to(handlers::pause_job))
+            .route("/{name}/resume", web::post().to(handlers::resume_job)),
+    );
+}
@@ -0,0 +1,188 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderName, HeaderValue},
+    Error, HttpMessage,
+};
+use futures_util::future::LocalBoxFuture;
+use std::future::{ready, Future, Ready};
+use tracing::Span;
+use tracing_actix_web::{DefaultRootSpanBuilder, RootSpanBuilder};
+
+/// Header carrying the correlation id over HTTP
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Kafka message header carrying the correlation id
+pub const KAFKA_HEADER: &str = "x-request-id";
+
+/// Longest inbound id accepted before a fresh one is generated
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Correlation id of the current request, stored in request extensions
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Generate a new correlation id
+pub fn generate() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Correlation id of the request or message being handled by the current task
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Run a future with `id` as the current correlation id
+pub async fn scope<F: Future>(id: String, future: F) -> F::Output {
+    REQUEST_ID.scope(id, future).await
+}
+
+/// Spawn a task that keeps the current correlation id. Task-locals are not
+/// inherited by `tokio::spawn`, so work moved off the request task would
+/// otherwise lose it.
+pub fn spawn<F>(future: F) -> tokio::task::JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match current() {
+        Some(id) => tokio::spawn(scope(id, future)),
+        None => tokio::spawn(future),
+    }
+}
+
+/// Accept an inbound id only if it is short and made of safe characters,
+/// so it can be logged and forwarded as-is
+pub fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+/// Accepts or generates an `X-Request-Id` for every request, makes it the
+/// current correlation id for the handler and echoes it on the response.
+///
+/// Register it after (outside) `TracingLogger::<RequestIdRootSpan>` so the
+/// root span can record the id.
+#[derive(Clone, Copy, Default)]
+pub struct RequestIdMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestIdMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestIdMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestIdMiddlewareService { service }))
+    }
+}
+
+pub struct RequestIdMiddlewareService<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let header = HeaderName::from_static("x-request-id");
+
+        let id = req
+            .headers()
+            .get(&header)
+            .and_then(|h| h.to_str().ok())
+            .filter(|id| is_valid(id))
+            .map(str::to_string)
+            .unwrap_or_else(generate);
+
+        let value = HeaderValue::from_str(&id).expect("request ids are valid header values");
+
+        // Normalise the inbound header so proxied requests carry the same id
+        req.headers_mut().insert(header.clone(), value.clone());
+        req.extensions_mut().insert(RequestId(id.clone()));
+
+        let future = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = scope(id, future).await?;
+            res.headers_mut().insert(header, value);
+            Ok(res)
+        })
+    }
+}
+
+/// Root span builder for `TracingLogger` that records the correlation id
+pub struct RequestIdRootSpan;
+
+impl RootSpanBuilder for RequestIdRootSpan {
+    fn on_request_start(request: &ServiceRequest) -> Span {
+        let correlation_id = request
+            .extensions()
+            .get::<RequestId>()
+            .map(|id| id.0.clone())
+            .unwrap_or_default();
+
+        tracing_actix_web::root_span!(request, correlation_id = %correlation_id)
+    }
+
+    fn on_request_end<B: MessageBody>(span: Span, outcome: &Result<ServiceResponse<B>, Error>) {
+        DefaultRootSpanBuilder::on_request_end(span, outcome);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid() {
+        assert!(is_valid(&generate()));
+        assert!(is_valid("req-123_abc.def:1"));
+        assert!(!is_valid(""));
+        assert!(!is_valid("bad id"));
+        assert!(!is_valid("bad\r\nheader"));
+        assert!(!is_valid(&"a".repeat(MAX_REQUEST_ID_LEN + 1)));
+    }
+
+    #[tokio::test]
+    async fn test_scope_sets_current() {
+        assert_eq!(current(), None);
+        let inside = scope("abc".to_string(), async { current() }).await;
+        assert_eq!(inside.as_deref(), Some("abc"));
+    }
+
+    #[tokio::test]
+    async fn test_spawn_keeps_current() {
+        let (spawned, plain) = scope("abc".to_string(), async {
+            let spawned = spawn(async { current() }).await.unwrap();
+            let plain = tokio::spawn(async { current() }).await.unwrap();
+            (spawned, plain)
+        })
+        .await;
+
+        assert_eq!(spawned.as_deref(), Some("abc"));
+        assert_eq!(plain, None);
+    }
+}
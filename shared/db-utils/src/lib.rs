@@ -2,15 +2,23 @@ use sqlx::postgres::{PgPool, PgPoolOptions};
 use redis::{Client as RedisClient, aio::ConnectionManager};
 use std::time::Duration;
 
-/// Create a PostgreSQL connection pool
-pub async fn create_pg_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
+/// Create a PostgreSQL connection pool of at most `max_connections`,
+/// exporting its saturation as metrics
+pub async fn create_pg_pool(database_url: &str, max_connections: u32) -> Result<PgPool, sqlx::Error> {
     tracing::info!("Creating PostgreSQL connection pool");
     
-    PgPoolOptions::new()
-        .max_connections(10)
+    let pool = PgPoolOptions::new()
+        .max_connections(max_connections)
         .acquire_timeout(Duration::from_secs(5))
         .connect(database_url)
-        .await
+        .await?;
+
+    let sampled = pool.clone();
+    metrics::register_collector(move || {
+        metrics::set_db_pool(sampled.size(), sampled.num_idle(), max_connections);
+    });
+
+    Ok(pool)
 }
 
 /// Create a Redis connection manager
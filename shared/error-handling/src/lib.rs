@@ -1,6 +1,11 @@
-use actix_web::{error::ResponseError, http::StatusCode, HttpResponse};
+use actix_web::{
+    error::ResponseError,
+    http::{header, StatusCode},
+    HttpResponse,
+};
 use serde::Serialize;
 use thiserror::Error;
+use utoipa::ToSchema;
 
 /// Application-wide error type
 #[derive(Debug, Error)]
@@ -40,15 +45,21 @@ pub enum AppError {
 
     #[error("External service error: {0}")]
     ExternalService(String),
+
+    #[error("Too many requests: {message}")]
+    TooManyRequests { message: String, retry_after: u64 },
 }
 
 /// Error response structure
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ErrorResponse {
     pub error: String,
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<String>,
+    /// Seconds to wait before retrying a rate limited request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
 }
 
 impl ResponseError for AppError {
@@ -66,18 +77,28 @@ impl ResponseError for AppError {
             AppError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
             AppError::Conflict(_) => StatusCode::CONFLICT,
             AppError::ExternalService(_) => StatusCode::BAD_GATEWAY,
+            AppError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
         let status_code = self.status_code();
+        let retry_after = match self {
+            AppError::TooManyRequests { retry_after, .. } => Some(*retry_after),
+            _ => None,
+        };
         let error_response = ErrorResponse {
             error: self.error_type(),
             message: self.to_string(),
             details: None,
+            retry_after,
         };
 
-        HttpResponse::build(status_code).json(error_response)
+        let mut response = HttpResponse::build(status_code);
+        if let Some(retry_after) = retry_after {
+            response.insert_header((header::RETRY_AFTER, retry_after.to_string()));
+        }
+        response.json(error_response)
     }
 }
 
@@ -96,6 +117,7 @@ impl AppError {
             AppError::ServiceUnavailable(_) => "SERVICE_UNAVAILABLE".to_string(),
             AppError::Conflict(_) => "CONFLICT".to_string(),
             AppError::ExternalService(_) => "EXTERNAL_SERVICE_ERROR".to_string(),
+            AppError::TooManyRequests { .. } => "TOO_MANY_REQUESTS".to_string(),
         }
     }
 }
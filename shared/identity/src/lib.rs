@@ -0,0 +1,315 @@
+use actix_web::{http::header::HeaderMap, web, HttpRequest};
+use error_handling::{AppError, AppResult};
+use hmac::{Hmac, Mac};
+use models::user::UserRole;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Identity headers set by the gateway on every authenticated upstream request
+pub mod headers {
+    pub const USER_ID: &str = "X-User-Id";
+    pub const USER_EMAIL: &str = "X-User-Email";
+    pub const USER_ROLE: &str = "X-User-Role";
+    pub const SESSION_ID: &str = "X-Session-Id";
+    pub const RESTAURANT_ID: &str = "X-Identity-Restaurant-Id";
+    pub const TIMESTAMP: &str = "X-Identity-Timestamp";
+    pub const SIGNATURE: &str = "X-Identity-Signature";
+}
+
+/// Maximum age of a signed identity before upstreams reject it
+const MAX_SIGNATURE_AGE_SECONDS: i64 = 60;
+
+/// Verified caller identity, derived from the gateway session
+#[derive(Debug, Clone)]
+pub struct Identity {
+    pub user_id: Uuid,
+    pub email: String,
+    pub role: UserRole,
+    pub session_id: String,
+    /// Restaurant an API key is bound to; `None` for user sessions
+    pub restaurant_id: Option<Uuid>,
+}
+
+impl Identity {
+    /// Whether the caller may act on a restaurant's records. API keys are
+    /// confined to their own restaurant; sessions are checked per route.
+    pub fn can_access_restaurant(&self, restaurant_id: Uuid) -> bool {
+        self.restaurant_id.is_none_or(|own| own == restaurant_id)
+    }
+}
+
+/// Returns true for headers that only the gateway is allowed to set.
+/// Inbound client copies of these must be stripped before proxying.
+pub fn is_identity_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.starts_with("x-user-")
+        || name.starts_with("x-identity-")
+        || name == "x-owner-id"
+        || name == "x-session-id"
+}
+
+/// Signs and verifies identity headers with a secret shared between the
+/// gateway and the backend services
+#[derive(Clone)]
+pub struct IdentitySigner {
+    secret: Vec<u8>,
+}
+
+impl IdentitySigner {
+    pub fn new(secret: &str) -> Self {
+        Self {
+            secret: secret.as_bytes().to_vec(),
+        }
+    }
+
+    fn mac(&self, identity: &Identity, timestamp: i64) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.secret)
+            .expect("HMAC accepts keys of any length");
+        let restaurant_id = identity.restaurant_id.map(|id| id.to_string()).unwrap_or_default();
+        let canonical = format!(
+            "v2\n{}\n{}\n{}\n{}\n{}\n{}",
+            identity.user_id, identity.email, identity.role, identity.session_id, restaurant_id, timestamp
+        );
+        mac.update(canonical.as_bytes());
+        mac
+    }
+
+    /// Build the full set of identity headers for an upstream request
+    pub fn sign(&self, identity: &Identity) -> Vec<(&'static str, String)> {
+        let timestamp = chrono::Utc::now().timestamp();
+        let signature = hex::encode(self.mac(identity, timestamp).finalize().into_bytes());
+
+        let mut signed = vec![
+            (headers::USER_ID, identity.user_id.to_string()),
+            (headers::USER_EMAIL, identity.email.clone()),
+            (headers::USER_ROLE, identity.role.to_string()),
+            (headers::SESSION_ID, identity.session_id.clone()),
+            (headers::TIMESTAMP, timestamp.to_string()),
+            (headers::SIGNATURE, signature),
+        ];
+        if let Some(restaurant_id) = identity.restaurant_id {
+            signed.push((headers::RESTAURANT_ID, restaurant_id.to_string()));
+        }
+        signed
+    }
+
+    /// Verify identity headers and return the identity they carry
+    pub fn verify(&self, headers: &HeaderMap) -> AppResult<Identity> {
+        let get = |name: &str| -> AppResult<&str> {
+            headers
+                .get(name)
+                .and_then(|h| h.to_str().ok())
+                .ok_or_else(|| AppError::Unauthorized(format!("Missing or invalid {} header", name)))
+        };
+
+        let identity = Identity {
+            user_id: get(headers::USER_ID)?
+                .parse()
+                .map_err(|_| AppError::Unauthorized("Invalid X-User-Id header".to_string()))?,
+            email: get(headers::USER_EMAIL)?.to_string(),
+            role: get(headers::USER_ROLE)?
+                .parse()
+                .map_err(|_| AppError::Unauthorized("Invalid X-User-Role header".to_string()))?,
+            session_id: get(headers::SESSION_ID)?.to_string(),
+            restaurant_id: match headers.get(headers::RESTAURANT_ID) {
+                Some(value) => Some(
+                    value
+                        .to_str()
+                        .ok()
+                        .and_then(|value| value.parse().ok())
+                        .ok_or_else(|| AppError::Unauthorized("Invalid X-Identity-Restaurant-Id header".to_string()))?,
+                ),
+                None => None,
+            },
+        };
+
+        let timestamp: i64 = get(headers::TIMESTAMP)?
+            .parse()
+            .map_err(|_| AppError::Unauthorized("Invalid X-Identity-Timestamp header".to_string()))?;
+
+        if (chrono::Utc::now().timestamp() - timestamp).abs() > MAX_SIGNATURE_AGE_SECONDS {
+            return Err(AppError::Unauthorized("Identity signature expired".to_string()));
+        }
+
+        let signature = hex::decode(get(headers::SIGNATURE)?)
+            .map_err(|_| AppError::Unauthorized("Invalid X-Identity-Signature header".to_string()))?;
+
+        self.mac(&identity, timestamp)
+            .verify_slice(&signature)
+            .map_err(|_| {
+                tracing::warn!("Rejected identity headers with bad signature for user {}", identity.user_id);
+                AppError::Unauthorized("Invalid identity signature".to_string())
+            })?;
+
+        Ok(identity)
+    }
+}
+
+/// Compare a presented secret token with the expected one in constant time
+pub fn token_matches(provided: &str, expected: &str) -> bool {
+    // Hashing first gives equal lengths, so timing reveals neither length
+    // nor the matching prefix
+    let provided = Sha256::digest(provided.as_bytes());
+    let expected = Sha256::digest(expected.as_bytes());
+    provided
+        .iter()
+        .zip(expected.iter())
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+/// Check a shared-secret header, such as an admin or service token, against
+/// the configured token. Without a configured token the endpoint is disabled.
+pub fn require_token(req: &HttpRequest, header: &str, expected: Option<&str>) -> AppResult<()> {
+    let expected =
+        expected.ok_or_else(|| AppError::NotFound("Endpoint is disabled".to_string()))?;
+
+    let provided = req.headers().get(header).and_then(|h| h.to_str().ok());
+
+    if !provided.is_some_and(|provided| token_matches(provided, expected)) {
+        return Err(AppError::Unauthorized(format!("Invalid {} header", header)));
+    }
+
+    Ok(())
+}
+
+/// Hash of an API key as stored by auth-service and looked up by the gateway.
+/// Keys are long random secrets, so a fast unsalted hash is sufficient.
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Extract the verified identity from a request using the `IdentitySigner`
+/// registered as app data
+pub fn verified_identity(req: &HttpRequest) -> AppResult<Identity> {
+    let signer = req
+        .app_data::<web::Data<IdentitySigner>>()
+        .ok_or_else(|| AppError::Internal("Identity signer not configured".to_string()))?;
+
+    signer.verify(req.headers())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{HeaderName, HeaderValue};
+
+    fn sample_identity() -> Identity {
+        Identity {
+            user_id: Uuid::new_v4(),
+            email: "owner@example.com".to_string(),
+            role: UserRole::Rest,
+            session_id: Uuid::new_v4().to_string(),
+            restaurant_id: None,
+        }
+    }
+
+    fn to_header_map(pairs: Vec<(&'static str, String)>) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (key, value) in pairs {
+            map.insert(
+                HeaderName::from_bytes(key.as_bytes()).unwrap(),
+                HeaderValue::from_str(&value).unwrap(),
+            );
+        }
+        map
+    }
+
+    #[test]
+    fn test_sign_and_verify_roundtrip() {
+        let signer = IdentitySigner::new("secret");
+        let identity = sample_identity();
+
+        let verified = signer.verify(&to_header_map(signer.sign(&identity))).unwrap();
+        assert_eq!(verified.user_id, identity.user_id);
+        assert_eq!(verified.role, identity.role);
+        assert_eq!(verified.session_id, identity.session_id);
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_user_id() {
+        let signer = IdentitySigner::new("secret");
+        let mut headers = to_header_map(signer.sign(&sample_identity()));
+        headers.insert(
+            HeaderName::from_static("x-user-id"),
+            HeaderValue::from_str(&Uuid::new_v4().to_string()).unwrap(),
+        );
+
+        assert!(signer.verify(&headers).is_err());
+    }
+
+    #[test]
+    fn test_restaurant_scope_is_signed() {
+        let signer = IdentitySigner::new("secret");
+        let restaurant_id = Uuid::new_v4();
+        let identity = Identity {
+            restaurant_id: Some(restaurant_id),
+            ..sample_identity()
+        };
+
+        let headers = to_header_map(signer.sign(&identity));
+        let verified = signer.verify(&headers).unwrap();
+        assert_eq!(verified.restaurant_id, Some(restaurant_id));
+        assert!(verified.can_access_restaurant(restaurant_id));
+        assert!(!verified.can_access_restaurant(Uuid::new_v4()));
+
+        // Dropping the scope would turn the key into an unrestricted caller
+        let mut unscoped = headers.clone();
+        unscoped.remove(headers::RESTAURANT_ID);
+        assert!(signer.verify(&unscoped).is_err());
+
+        let mut other = headers;
+        other.insert(
+            HeaderName::from_static("x-identity-restaurant-id"),
+            HeaderValue::from_str(&Uuid::new_v4().to_string()).unwrap(),
+        );
+        assert!(signer.verify(&other).is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_other_secret() {
+        let headers = to_header_map(IdentitySigner::new("secret").sign(&sample_identity()));
+        assert!(IdentitySigner::new("other").verify(&headers).is_err());
+    }
+
+    #[test]
+    fn test_is_identity_header() {
+        assert!(is_identity_header("X-Owner-Id"));
+        assert!(is_identity_header("x-user-role"));
+        assert!(is_identity_header("X-Identity-Signature"));
+        assert!(!is_identity_header("Authorization"));
+    }
+
+    #[test]
+    fn test_token_matches() {
+        assert!(token_matches("service-secret", "service-secret"));
+        assert!(!token_matches("service-secre", "service-secret"));
+        assert!(!token_matches("", "service-secret"));
+    }
+
+    #[test]
+    fn test_require_token() {
+        use actix_web::test::TestRequest;
+
+        let req = TestRequest::default()
+            .insert_header(("X-Admin-Token", "admin-secret"))
+            .to_http_request();
+        assert!(require_token(&req, "X-Admin-Token", Some("admin-secret")).is_ok());
+        assert!(matches!(
+            require_token(&req, "X-Admin-Token", Some("other-secret")),
+            Err(AppError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            require_token(&req, "X-Service-Token", Some("admin-secret")),
+            Err(AppError::Unauthorized(_))
+        ));
+
+        // Without a configured token the endpoint is off
+        assert!(matches!(
+            require_token(&req, "X-Admin-Token", None),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
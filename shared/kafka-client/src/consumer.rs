@@ -1,16 +1,16 @@
-use rdkafka::consumer::StreamConsumer;
+use crate::MeteredConsumer;
 
 /// Wrapper for Kafka consumer
 pub struct KafkaConsumer {
-    consumer: StreamConsumer,
+    consumer: MeteredConsumer,
 }
 
 impl KafkaConsumer {
-    pub fn new(consumer: StreamConsumer) -> Self {
+    pub fn new(consumer: MeteredConsumer) -> Self {
         Self { consumer }
     }
 
-    pub fn inner(&self) -> &StreamConsumer {
+    pub fn inner(&self) -> &MeteredConsumer {
         &self.consumer
     }
 }
@@ -1,8 +1,15 @@
+use rdkafka::client::ClientContext;
 use rdkafka::config::ClientConfig;
+use rdkafka::message::{Header, Headers, Message, OwnedHeaders};
 use rdkafka::producer::{FutureProducer, FutureRecord};
-use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::consumer::{Consumer, ConsumerContext, StreamConsumer};
+use rdkafka::statistics::Statistics;
+use rdkafka::{Offset, TopicPartitionList};
 use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
 use std::time::Duration;
+use tracing::Instrument;
 
 pub mod producer;
 pub mod consumer;
@@ -21,26 +28,125 @@ pub fn create_producer(brokers: &str) -> Result<FutureProducer, rdkafka::error::
         .create()
 }
 
+/// How often librdkafka reports statistics, which carry consumer lag
+const STATISTICS_INTERVAL_MS: &str = "15000";
+
+/// Consumer context exporting per-partition consumer lag from librdkafka statistics
+pub struct MetricsContext;
+
+impl ClientContext for MetricsContext {
+    fn stats(&self, statistics: Statistics) {
+        for (topic_name, topic) in &statistics.topics {
+            for partition in topic.partitions.values() {
+                // Partition -1 is librdkafka's internal unassigned partition,
+                // and lag is -1 until an offset is known
+                if partition.partition >= 0 && partition.consumer_lag >= 0 {
+                    metrics::set_kafka_consumer_lag(
+                        topic_name,
+                        partition.partition,
+                        partition.consumer_lag,
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl ConsumerContext for MetricsContext {}
+
+/// Stream consumer reporting its lag as metrics
+pub type MeteredConsumer = StreamConsumer<MetricsContext>;
+
 /// Create a Kafka consumer
 pub fn create_consumer(
     brokers: &str,
     group_id: &str,
     topics: &[&str],
-) -> Result<StreamConsumer, rdkafka::error::KafkaError> {
+) -> Result<MeteredConsumer, rdkafka::error::KafkaError> {
     tracing::info!("Creating Kafka consumer for group: {}", group_id);
     
-    let consumer: StreamConsumer = ClientConfig::new()
+    let consumer: MeteredConsumer = ClientConfig::new()
         .set("bootstrap.servers", brokers)
         .set("group.id", group_id)
         .set("enable.auto.commit", "true")
         .set("auto.offset.reset", "earliest")
-        .create()?;
+        .set("statistics.interval.ms", STATISTICS_INTERVAL_MS)
+        .create_with_context(MetricsContext)?;
 
     consumer.subscribe(topics)?;
     
     Ok(consumer)
 }
 
+/// Create a consumer that only commits what the caller passes to `commit`,
+/// for records that must not be skipped when handling them fails
+pub fn create_manual_commit_consumer(
+    brokers: &str,
+    group_id: &str,
+    topics: &[&str],
+) -> Result<MeteredConsumer, rdkafka::error::KafkaError> {
+    tracing::info!(
+        "Creating Kafka consumer with manual commits for group: {}",
+        group_id
+    );
+
+    let consumer: MeteredConsumer = ClientConfig::new()
+        .set("bootstrap.servers", brokers)
+        .set("group.id", group_id)
+        .set("enable.auto.commit", "false")
+        .set("auto.offset.reset", "earliest")
+        .set("statistics.interval.ms", STATISTICS_INTERVAL_MS)
+        .create_with_context(MetricsContext)?;
+
+    consumer.subscribe(topics)?;
+
+    Ok(consumer)
+}
+
+/// Offsets to commit once `messages` are handled: the one after the last
+/// message of each partition
+pub fn next_offsets<M: Message>(
+    messages: &[M],
+) -> Result<TopicPartitionList, rdkafka::error::KafkaError> {
+    let mut next: BTreeMap<(&str, i32), i64> = BTreeMap::new();
+    for message in messages {
+        let offset = next
+            .entry((message.topic(), message.partition()))
+            .or_default();
+        *offset = (*offset).max(message.offset() + 1);
+    }
+
+    let mut offsets = TopicPartitionList::new();
+    for ((topic, partition), offset) in next {
+        offsets.add_partition_offset(topic, partition, Offset::Offset(offset))?;
+    }
+    Ok(offsets)
+}
+
+/// Create a consumer in a consumer group of its own, so every instance of a
+/// service receives every message. It starts from the latest offset and
+/// never commits, since there is nothing to resume after a restart.
+pub fn create_broadcast_consumer(
+    brokers: &str,
+    group_prefix: &str,
+    topics: &[&str],
+) -> Result<MeteredConsumer, rdkafka::error::KafkaError> {
+    let group_id = format!("{}-{}", group_prefix, uuid::Uuid::new_v4());
+    tracing::info!("Creating Kafka broadcast consumer for group: {}", group_id);
+
+    let consumer: MeteredConsumer = ClientConfig::new()
+        .set("bootstrap.servers", brokers)
+        .set("group.id", &group_id)
+        .set("enable.auto.commit", "false")
+        .set("auto.offset.reset", "latest")
+        .set("statistics.interval.ms", STATISTICS_INTERVAL_MS)
+        .create_with_context(MetricsContext)?;
+
+    consumer.subscribe(topics)?;
+
+    Ok(consumer)
+}
+
 /// Publish a message to Kafka
 pub async fn publish_message<T: Serialize>(
     producer: &FutureProducer,
@@ -50,16 +156,17 @@ pub async fn publish_message<T: Serialize>(
 ) -> Result<(), anyhow::Error> {
     let payload_json = serde_json::to_string(payload)?;
 
-    let mut record = FutureRecord::to(topic).payload(&payload_json);
+    let mut record = FutureRecord::to(topic)
+        .payload(&payload_json)
+        .headers(correlation_headers());
 
     if let Some(k) = key {
         record = record.key(k);
     }
 
-    producer
-        .send(record, Duration::from_secs(5))
-        .await
-        .map_err(|(err, _)| anyhow::anyhow!("Failed to send message: {}", err))?;
+    let result = producer.send(record, Duration::from_secs(5)).await;
+    metrics::record_kafka_published(topic, result.is_ok());
+    result.map_err(|(err, _)| anyhow::anyhow!("Failed to send message: {}", err))?;
 
     tracing::debug!("Published message to topic: {}", topic);
 
@@ -75,15 +182,108 @@ pub async fn publish(
 ) -> Result<(), rdkafka::error::KafkaError> {
     let record = FutureRecord::to(topic)
         .key(key)
-        .payload(payload);
+        .payload(payload)
+        .headers(correlation_headers());
 
-    producer
-        .send(record, Duration::from_secs(5))
-        .await
-        .map_err(|(err, _)| err)?;
+    let result = producer.send(record, Duration::from_secs(5)).await;
+    metrics::record_kafka_published(topic, result.is_ok());
+    result.map_err(|(err, _)| err)?;
 
     tracing::debug!("Published message to topic: {}", topic);
 
     Ok(())
 }
 
+/// Headers carrying the current correlation id, if any
+fn correlation_headers() -> OwnedHeaders {
+    let headers = OwnedHeaders::new();
+
+    match correlation::current() {
+        Some(id) => headers.insert(Header {
+            key: correlation::KAFKA_HEADER,
+            value: Some(&id),
+        }),
+        None => headers,
+    }
+}
+
+/// Count a consumed message
+pub fn record_consumed<M: Message>(message: &M) {
+    metrics::record_kafka_consumed(message.topic());
+}
+
+/// Correlation id carried by a consumed message, if any
+pub fn message_request_id<M: Message>(message: &M) -> Option<String> {
+    message
+        .headers()?
+        .iter()
+        .find(|header| header.key == correlation::KAFKA_HEADER)
+        .and_then(|header| header.value)
+        .and_then(|value| std::str::from_utf8(value).ok())
+        .filter(|id| correlation::is_valid(id))
+        .map(str::to_string)
+}
+
+/// Handle a consumed message inside a span that records its correlation id,
+/// with the id restored so anything published while handling it carries it on
+pub async fn handle_in_context<M, F>(message: &M, handler: F) -> F::Output
+where
+    M: Message,
+    F: Future,
+{
+    let request_id = message_request_id(message).unwrap_or_else(correlation::generate);
+    let span = tracing::info_span!(
+        "kafka_message",
+        topic = message.topic(),
+        partition = message.partition(),
+        offset = message.offset(),
+        correlation_id = %request_id,
+    );
+
+    correlation::scope(request_id, handler.instrument(span)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_correlation_header_survives_spawn() {
+        let headers = correlation::scope("req-1".to_string(), async {
+            correlation::spawn(async { correlation_headers() }).await.unwrap()
+        })
+        .await;
+
+        let header = headers.iter().find(|header| header.key == correlation::KAFKA_HEADER);
+        assert_eq!(header.and_then(|header| header.value), Some("req-1".as_bytes()));
+    }
+
+    #[test]
+    fn test_next_offsets() {
+        use rdkafka::message::{OwnedMessage, Timestamp};
+
+        let message = |partition, offset| {
+            OwnedMessage::new(
+                None,
+                None,
+                "audit.events".to_string(),
+                Timestamp::NotAvailable,
+                partition,
+                offset,
+                None,
+            )
+        };
+        let offsets =
+            next_offsets(&[message(0, 7), message(1, 3), message(0, 8), message(1, 4)]).unwrap();
+
+        assert_eq!(offsets.count(), 2);
+        assert_eq!(
+            offsets.find_partition("audit.events", 0).unwrap().offset(),
+            Offset::Offset(9)
+        );
+        assert_eq!(
+            offsets.find_partition("audit.events", 1).unwrap().offset(),
+            Offset::Offset(5)
+        );
+    }
+}
@@ -0,0 +1,404 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpResponse,
+};
+use futures_util::future::LocalBoxFuture;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use std::future::{ready, Ready};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Label used for requests that did not match a registered route
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+type Collector = Box<dyn Fn() + Send + Sync>;
+
+type RouteLabel = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_duration: HistogramVec,
+    upstream_requests: IntCounterVec,
+    upstream_duration: HistogramVec,
+    kafka_published: IntCounterVec,
+    kafka_consumed: IntCounterVec,
+    kafka_consumer_lag: IntGaugeVec,
+    db_pool_connections: IntGaugeVec,
+    db_pool_max_connections: IntGauge,
+    cache_requests: IntCounterVec,
+    job_runs: IntCounterVec,
+    job_duration: HistogramVec,
+    scheduler_leader: IntGauge,
+    /// Refresh sampled gauges right before each scrape
+    collectors: Mutex<Vec<Collector>>,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests handled"),
+            &["service", "method", "route", "status"],
+        )
+        .unwrap();
+        let http_duration = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "HTTP request latency"),
+            &["service", "method", "route"],
+        )
+        .unwrap();
+        let upstream_requests = IntCounterVec::new(
+            Opts::new("gateway_upstream_requests_total", "Requests proxied to upstream services"),
+            &["upstream", "route", "status"],
+        )
+        .unwrap();
+        let upstream_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "gateway_upstream_request_duration_seconds",
+                "Upstream latency until response headers",
+            ),
+            &["upstream", "route"],
+        )
+        .unwrap();
+        let kafka_published = IntCounterVec::new(
+            Opts::new("kafka_messages_published_total", "Kafka messages published"),
+            &["topic", "result"],
+        )
+        .unwrap();
+        let kafka_consumed = IntCounterVec::new(
+            Opts::new("kafka_messages_consumed_total", "Kafka messages consumed"),
+            &["topic"],
+        )
+        .unwrap();
+        let kafka_consumer_lag = IntGaugeVec::new(
+            Opts::new("kafka_consumer_lag", "Messages behind the partition high watermark"),
+            &["topic", "partition"],
+        )
+        .unwrap();
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new("db_pool_connections", "Database pool connections by state"),
+            &["state"],
+        )
+        .unwrap();
+        let db_pool_max_connections = IntGauge::new(
+            "db_pool_max_connections",
+            "Maximum database pool connections",
+        )
+        .unwrap();
+        let cache_requests = IntCounterVec::new(
+            Opts::new("cache_requests_total", "Cache lookups by result"),
+            &["cache", "result"],
+        )
+        .unwrap();
+
+        let job_runs = IntCounterVec::new(
+            Opts::new("scheduler_job_runs_total", "Scheduled job runs by outcome"),
+            &["job", "trigger", "outcome"],
+        )
+        .unwrap();
+        let job_duration = HistogramVec::new(
+            HistogramOpts::new("scheduler_job_duration_seconds", "Scheduled job run time")
+                .buckets(vec![0.1, 0.5, 1.0, 5.0, 15.0, 60.0, 300.0, 900.0, 3600.0]),
+            &["job"],
+        )
+        .unwrap();
+        let scheduler_leader = IntGauge::new(
+            "scheduler_leader",
+            "1 while this replica holds the scheduler leader lock",
+        )
+        .unwrap();
+
+        registry.register(Box::new(http_requests.clone())).unwrap();
+        registry.register(Box::new(http_duration.clone())).unwrap();
+        registry.register(Box::new(upstream_requests.clone())).unwrap();
+        registry.register(Box::new(upstream_duration.clone())).unwrap();
+        registry.register(Box::new(kafka_published.clone())).unwrap();
+        registry.register(Box::new(kafka_consumed.clone())).unwrap();
+        registry.register(Box::new(kafka_consumer_lag.clone())).unwrap();
+        registry.register(Box::new(db_pool_connections.clone())).unwrap();
+        registry.register(Box::new(db_pool_max_connections.clone())).unwrap();
+        registry.register(Box::new(cache_requests.clone())).unwrap();
+        registry.register(Box::new(job_runs.clone())).unwrap();
+        registry.register(Box::new(job_duration.clone())).unwrap();
+        registry.register(Box::new(scheduler_leader.clone())).unwrap();
+
+        Self {
+            registry,
+            http_requests,
+            http_duration,
+            upstream_requests,
+            upstream_duration,
+            kafka_published,
+            kafka_consumed,
+            kafka_consumer_lag,
+            db_pool_connections,
+            db_pool_max_connections,
+            cache_requests,
+            job_runs,
+            job_duration,
+            scheduler_leader,
+            collectors: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::new)
+}
+
+/// Render all metrics in the Prometheus text format
+pub fn render() -> String {
+    let metrics = metrics();
+
+    for collect in metrics.collectors.lock().unwrap().iter() {
+        collect();
+    }
+
+    let mut buffer = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&metrics.registry.gather(), &mut buffer) {
+        tracing::error!("Failed to encode metrics: {}", e);
+    }
+
+    String::from_utf8(buffer).unwrap_or_default()
+}
+
+/// `GET /metrics` handler
+pub async fn metrics_handler() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(prometheus::TEXT_FORMAT)
+        .body(render())
+}
+
+/// Register a callback that refreshes sampled gauges before each scrape
+pub fn register_collector<F>(collect: F)
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    metrics().collectors.lock().unwrap().push(Box::new(collect));
+}
+
+/// Record a request proxied by the gateway; `status` is the upstream
+/// status code, or a failure kind such as `timeout`
+pub fn record_upstream(upstream: &str, route: &str, status: &str, elapsed: Duration) {
+    let metrics = metrics();
+    metrics
+        .upstream_requests
+        .with_label_values(&[upstream, route, status])
+        .inc();
+    metrics
+        .upstream_duration
+        .with_label_values(&[upstream, route])
+        .observe(elapsed.as_secs_f64());
+}
+
+pub fn record_kafka_published(topic: &str, success: bool) {
+    let result = if success { "ok" } else { "error" };
+    metrics()
+        .kafka_published
+        .with_label_values(&[topic, result])
+        .inc();
+}
+
+pub fn record_kafka_consumed(topic: &str) {
+    metrics().kafka_consumed.with_label_values(&[topic]).inc();
+}
+
+pub fn set_kafka_consumer_lag(topic: &str, partition: i32, lag: i64) {
+    metrics()
+        .kafka_consumer_lag
+        .with_label_values(&[topic, &partition.to_string()])
+        .set(lag);
+}
+
+/// Set database pool gauges
+pub fn set_db_pool(size: u32, idle: usize, max: u32) {
+    let metrics = metrics();
+    let idle = idle as i64;
+    metrics
+        .db_pool_connections
+        .with_label_values(&["active"])
+        .set(size as i64 - idle);
+    metrics
+        .db_pool_connections
+        .with_label_values(&["idle"])
+        .set(idle);
+    metrics.db_pool_max_connections.set(max as i64);
+}
+
+pub fn record_cache(cache: &str, hit: bool) {
+    let result = if hit { "hit" } else { "miss" };
+    metrics()
+        .cache_requests
+        .with_label_values(&[cache, result])
+        .inc();
+}
+
+/// Record a finished job run; `outcome` is e.g. `succeeded` or `failed`
+pub fn record_job_run(job: &str, trigger: &str, outcome: &str, elapsed: Duration) {
+    let metrics = metrics();
+    metrics
+        .job_runs
+        .with_label_values(&[job, trigger, outcome])
+        .inc();
+    metrics
+        .job_duration
+        .with_label_values(&[job])
+        .observe(elapsed.as_secs_f64());
+}
+
+pub fn set_scheduler_leader(leader: bool) {
+    metrics().scheduler_leader.set(leader as i64);
+}
+
+/// Middleware recording request counts and latency per route
+#[derive(Clone)]
+pub struct MetricsMiddleware {
+    service: &'static str,
+    route_label: Option<RouteLabel>,
+}
+
+impl MetricsMiddleware {
+    pub fn new(service: &'static str) -> Self {
+        Self {
+            service,
+            route_label: None,
+        }
+    }
+
+    /// Label requests that match no registered route, such as those handled
+    /// by a default service, with the route `label` returns for their path
+    pub fn with_route_label<F>(mut self, label: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.route_label = Some(Arc::new(label));
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for MetricsMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = MetricsMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MetricsMiddlewareService {
+            service,
+            name: self.service,
+            route_label: self.route_label.clone(),
+        }))
+    }
+}
+
+pub struct MetricsMiddlewareService<S> {
+    service: S,
+    name: &'static str,
+    route_label: Option<RouteLabel>,
+}
+
+impl<S, B> Service<ServiceRequest> for MetricsMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let started = Instant::now();
+        let service = self.name;
+        let method = req.method().to_string();
+        let path = req.path().to_string();
+        let route_label = self.route_label.clone();
+        let future = self.service.call(req);
+
+        Box::pin(async move {
+            let result = future.await;
+
+            // Routing has run by now, so the matched pattern is known. The
+            // request is only read back from the response: holding a clone of
+            // it while routing runs would stop the router recording the match
+            let (status, pattern) = match &result {
+                Ok(res) => (res.status(), res.request().match_pattern()),
+                Err(e) => (e.as_response_error().status_code(), None),
+            };
+            let route = pattern
+                .or_else(|| route_label.and_then(|label| label(&path)))
+                .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+
+            let metrics = metrics();
+            metrics
+                .http_requests
+                .with_label_values(&[service, &method, &route, status.as_str()])
+                .inc();
+            metrics
+                .http_duration
+                .with_label_values(&[service, &method, &route])
+                .observe(started.elapsed().as_secs_f64());
+
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_includes_recorded_metrics() {
+        record_cache("menu", true);
+        record_kafka_published("order.events", true);
+
+        let output = render();
+        assert!(output.contains(r#"cache_requests_total{cache="menu",result="hit"} 1"#));
+        assert!(output.contains(r#"kafka_messages_published_total{result="ok",topic="order.events"} 1"#));
+    }
+
+    #[actix_web::test]
+    async fn test_default_service_labeled_by_route_label() {
+        use actix_web::{test, web, App};
+
+        let middleware = MetricsMiddleware::new("label-test").with_route_label(|path| {
+            path.starts_with("/api/orders").then(|| "/api/orders".to_string())
+        });
+        let app = test::init_service(
+            App::new()
+                .wrap(middleware)
+                .route("/health", web::get().to(HttpResponse::Ok))
+                .default_service(web::to(HttpResponse::Ok)),
+        )
+        .await;
+
+        for uri in ["/health", "/api/orders/1", "/api/orders/2", "/unknown"] {
+            test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        }
+
+        let output = render();
+        let count = |route: &str| {
+            format!(
+                r#"http_requests_total{{method="GET",route="{}",service="label-test",status="200"}}"#,
+                route
+            )
+        };
+        assert!(output.contains(&format!("{} 1", count("/health"))));
+        assert!(output.contains(&format!("{} 2", count("/api/orders"))));
+        assert!(output.contains(&format!("{} 1", count("unmatched"))));
+    }
+}
@@ -46,7 +46,28 @@ pub struct UserDeactivatedData {
     pub deactivated_at: DateTime<Utc>,
 }
 
+/// Why an OTP verification failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoginFailureReason {
+    InvalidCode,
+    /// No code was pending: never requested, expired or already used
+    CodeNotFound,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserLoginFailedData {
+    pub email: String,
+    pub ip_address: Option<String>,
+    pub reason: LoginFailureReason,
+    /// Consecutive failures for the email, including this one
+    pub failed_attempts: u32,
+    /// Set when this failure locked the email or IP address
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
 pub type UserLoginSuccessEvent = Event<UserLoginSuccessData>;
+pub type UserLoginFailedEvent = Event<UserLoginFailedData>;
 pub type UserDeactivatedEvent = Event<UserDeactivatedData>;
 
 // ============================================================================
@@ -140,6 +161,28 @@ pub struct BillPaidData {
 pub type BillGeneratedEvent = Event<BillGeneratedData>;
 pub type BillPaidEvent = Event<BillPaidData>;
 
+// ============================================================================
+// Audit Events
+// ============================================================================
+
+/// A mutating API call as seen by the gateway
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecordedData {
+    pub user_id: Option<Uuid>,
+    pub role: Option<UserRole>,
+    /// Set when the call was made with an API key
+    pub api_key_id: Option<Uuid>,
+    /// Restaurant the call was scoped to, from the API key or the path
+    pub restaurant_id: Option<Uuid>,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub latency_ms: u32,
+    pub request_id: Option<String>,
+}
+
+pub type AuditRecordedEvent = Event<AuditRecordedData>;
+
 // ============================================================================
 // Event Type Constants
 // ============================================================================
@@ -147,6 +190,7 @@ pub type BillPaidEvent = Event<BillPaidData>;
 pub mod event_types {
     // User events
     pub const USER_LOGIN_SUCCESS: &str = "user.login_success";
+    pub const USER_LOGIN_FAILED: &str = "user.login_failed";
     pub const USER_DEACTIVATED: &str = "user.deactivated";
 
     // Menu events
@@ -160,5 +204,8 @@ pub mod event_types {
     // Bill events
     pub const BILL_GENERATED: &str = "bill.generated";
     pub const BILL_PAID: &str = "bill.paid";
+
+    // Audit events
+    pub const AUDIT_RECORDED: &str = "audit.recorded";
 }
 
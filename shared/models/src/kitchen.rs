@@ -2,6 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
@@ -59,7 +60,7 @@ pub struct KitchenTicket {
 }
 
 /// Kitchen ticket item (for JSON storage and response)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct KitchenTicketItem {
     pub food_id: Uuid,
     pub food_name: String,
@@ -67,7 +68,7 @@ pub struct KitchenTicketItem {
 }
 
 /// Kitchen ticket response (with parsed items)
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct KitchenTicketResponse {
     pub id: Uuid,
     pub order_id: Uuid,
@@ -81,7 +82,7 @@ pub struct KitchenTicketResponse {
 }
 
 /// Update kitchen ticket status request
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdateKitchenTicketStatusRequest {
     pub status: String,
 }
@@ -2,6 +2,7 @@ use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
@@ -32,7 +33,7 @@ impl std::fmt::Display for OrderStatus {
 }
 
 /// Order model
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Order {
     pub id: Uuid,
     pub user_id: Uuid,
@@ -46,7 +47,7 @@ pub struct Order {
 }
 
 /// Order item model
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct OrderItem {
     pub id: Uuid,
     pub order_id: Uuid,
@@ -60,7 +61,7 @@ pub struct OrderItem {
 }
 
 /// Create order item request
-#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
 pub struct CreateOrderItemRequest {
     pub food_id: Uuid,
     #[validate(range(min = 1, max = 100))]
@@ -68,7 +69,7 @@ pub struct CreateOrderItemRequest {
 }
 
 /// Create order request
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateOrderRequest {
     pub restaurant_id: Uuid,
     #[validate(length(min = 1))]
@@ -78,7 +79,7 @@ pub struct CreateOrderRequest {
 }
 
 /// Order response with items
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct OrderResponse {
     #[serde(flatten)]
     pub order: Order,
@@ -86,7 +87,7 @@ pub struct OrderResponse {
 }
 
 /// Update order status request
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdateOrderStatusRequest {
     #[validate(length(min = 1))]
     pub status: String,
@@ -2,11 +2,12 @@ use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
 /// Restaurant model
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Restaurant {
     pub id: Uuid,
     pub owner_id: Uuid,
@@ -20,7 +21,7 @@ pub struct Restaurant {
 }
 
 /// Food category model
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct FoodCategory {
     pub id: Uuid,
     pub restaurant_id: Uuid,
@@ -33,7 +34,7 @@ pub struct FoodCategory {
 }
 
 /// Food item model
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Food {
     pub id: Uuid,
     pub restaurant_id: Uuid,
@@ -47,7 +48,7 @@ pub struct Food {
 }
 
 /// Create restaurant request
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateRestaurantRequest {
     #[validate(length(min = 1, max = 255))]
     pub name: String,
@@ -57,7 +58,7 @@ pub struct CreateRestaurantRequest {
 }
 
 /// Create food category request
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateFoodCategoryRequest {
     #[validate(length(min = 1, max = 255))]
     pub name: String,
@@ -66,7 +67,7 @@ pub struct CreateFoodCategoryRequest {
 }
 
 /// Create food request
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateFoodRequest {
     pub category_id: Uuid,
     #[validate(length(min = 1, max = 255))]
@@ -76,7 +77,7 @@ pub struct CreateFoodRequest {
 }
 
 /// Menu response (category with foods)
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct MenuCategory {
     #[serde(flatten)]
     pub category: FoodCategory,
@@ -84,7 +85,7 @@ pub struct MenuCategory {
 }
 
 /// Full menu response
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct MenuResponse {
     pub restaurant: Restaurant,
     pub categories: Vec<MenuCategory>,
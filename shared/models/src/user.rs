@@ -1,11 +1,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
 /// User role enum
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum UserRole {
     #[serde(rename = "user")]
@@ -44,6 +45,19 @@ impl std::fmt::Display for UserRole {
     }
 }
 
+impl std::str::FromStr for UserRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "user" => Ok(UserRole::User),
+            "rest" => Ok(UserRole::Rest),
+            "kitch" => Ok(UserRole::Kitch),
+            _ => Err(format!("Invalid user role: {}", s)),
+        }
+    }
+}
+
 /// User model
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct User {
@@ -67,7 +81,7 @@ pub struct Otp {
 }
 
 /// Request OTP payload
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct RequestOtpRequest {
     #[validate(email(message = "Invalid email address"))]
     pub email: String,
@@ -75,7 +89,7 @@ pub struct RequestOtpRequest {
 }
 
 /// Verify OTP payload
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct VerifyOtpRequest {
     #[validate(email(message = "Invalid email address"))]
     pub email: String,
@@ -85,14 +99,25 @@ pub struct VerifyOtpRequest {
 }
 
 /// Auth response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AuthResponse {
     pub token: String,
+    /// Seconds until the session token expires without activity
+    pub expires_in: u64,
+    /// Single-use token for `/api/auth/refresh`; each use returns a new one
+    pub refresh_token: String,
     pub user_id: Uuid,
     pub email: String,
     pub role: UserRole,
 }
 
+/// Refresh session payload
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct RefreshRequest {
+    #[validate(length(min = 1, message = "Refresh token is required"))]
+    pub refresh_token: String,
+}
+
 /// Session data stored in Redis
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
@@ -100,5 +125,109 @@ pub struct Session {
     pub email: String,
     pub role: UserRole,
     pub created_at: DateTime<Utc>,
+    /// Set on sessions synthesised from an API key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<SessionScope>,
+}
+
+/// A login as listed to its user. Refreshing keeps the id, so it names the
+/// login rather than the current session token.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SessionInfo {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    /// Last request through the gateway, to within its session cache TTL
+    pub last_seen_at: DateTime<Utc>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    /// Whether this is the session making the request
+    pub current: bool,
+}
+
+/// Deactivate dormant accounts payload, sent by scheduler-service
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeactivateDormantRequest {
+    /// Days since the last OTP login after which an account is dormant
+    pub inactive_days: u32,
+    /// Only report the accounts that would be deactivated
+    pub dry_run: bool,
+}
+
+/// An account found dormant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DormantAccount {
+    pub user_id: Uuid,
+    pub email: String,
+    pub last_verified_at: Option<DateTime<Utc>>,
 }
 
+/// A dormant account that could not be checked or deactivated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DormantAccountFailure {
+    pub user_id: Uuid,
+    pub email: String,
+    pub error: String,
+}
+
+/// Accounts deactivated, or that would be on a dry run, and those that failed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DormantAccountsReport {
+    pub dry_run: bool,
+    pub accounts: Vec<DormantAccount>,
+    #[serde(default)]
+    pub failures: Vec<DormantAccountFailure>,
+}
+
+/// Restriction on a session synthesised from an API key
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SessionScope {
+    pub api_key_id: Uuid,
+    /// The only restaurant the key may act on
+    pub restaurant_id: Uuid,
+}
+
+/// API key issued to a machine client; the secret itself is never stored
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub restaurant_id: Uuid,
+    pub role: UserRole,
+    pub name: String,
+    /// Leading characters of the key, to tell keys apart
+    pub key_prefix: String,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Create API key payload
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateApiKeyRequest {
+    pub restaurant_id: Uuid,
+    pub role: UserRole,
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: String,
+}
+
+/// Create API key response, the only time the key is returned
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreateApiKeyResponse {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+/// Redis pub/sub channel on which auth-service announces ended sessions
+pub const SESSION_INVALIDATION_CHANNEL: &str = "session.invalidations";
+
+/// Session invalidation message, used by the gateway to evict cached sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "scope", rename_all = "snake_case")]
+pub enum SessionInvalidation {
+    /// A single session ended (logout, revocation)
+    Session { session_id: String },
+    /// Every session of a user ended (deactivation)
+    User { user_id: Uuid },
+    /// An API key was revoked
+    ApiKey { api_key_id: Uuid },
+}
@@ -0,0 +1,71 @@
+use actix_web::{web, HttpResponse};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::{OpenApi, Tag};
+use utoipa::Modify;
+
+/// Name of the security scheme of routes that require a session, for
+/// `security(("session" = []))` on `#[utoipa::path]`
+pub const SESSION_SCHEME: &str = "session";
+
+/// Conventions shared by the services' documents, applied with
+/// `#[openapi(modifiers(&ServiceDefaults))]`: every operation is
+/// tagged with the service name, which the gateway groups the merged
+/// document by, and the session security scheme is registered.
+pub struct ServiceDefaults;
+
+impl Modify for ServiceDefaults {
+    fn modify(&self, openapi: &mut OpenApi) {
+        let service = openapi.info.title.clone();
+        for item in openapi.paths.paths.values_mut() {
+            let operations = [
+                &mut item.get,
+                &mut item.put,
+                &mut item.post,
+                &mut item.delete,
+                &mut item.patch,
+            ];
+            for operation in operations.into_iter().flatten() {
+                operation.tags = Some(vec![service.clone()]);
+            }
+        }
+        openapi.tags = Some(vec![Tag::new(&service)]);
+
+        openapi.components.get_or_insert_with(Default::default).add_security_scheme(
+            SESSION_SCHEME,
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some("Session token from /api/auth/verify-otp"))
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// Serve the service's document at `/openapi.json`
+pub async fn openapi_handler(document: web::Data<OpenApi>) -> HttpResponse {
+    HttpResponse::Ok().json(document.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utoipa::OpenApi as _;
+
+    #[utoipa::path(get, path = "/api/items/{id}", security(("session" = [])), responses((status = 200)))]
+    #[allow(dead_code)]
+    async fn get_item() {}
+
+    #[derive(utoipa::OpenApi)]
+    #[openapi(info(title = "item-service"), paths(get_item), modifiers(&ServiceDefaults))]
+    struct ApiDoc;
+
+    #[test]
+    fn test_service_defaults() {
+        let document = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+        assert_eq!(document["paths"]["/api/items/{id}"]["get"]["tags"], serde_json::json!(["item-service"]));
+        assert_eq!(document["tags"][0]["name"], "item-service");
+        assert_eq!(document["components"]["securitySchemes"]["session"]["scheme"], "bearer");
+    }
+}